@@ -1,6 +1,7 @@
 use accessory::Error;
 use async_trait::async_trait;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
@@ -18,4 +19,14 @@ pub trait Accessory: Send + Sync + 'static {
         service_name: ServiceName,
         characteristic_name: CharacteristicName,
     ) -> Result<Characteristic, Error>;
+
+    /// Identifies the accessory, e.g by blinking an LED, so it can be told apart from the others during setup
+    async fn identify(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Capabilities announced to the hub, `None` to use the defaults of the accessory type
+    fn capabilities(&self) -> Option<Capabilities> {
+        None
+    }
 }
@@ -1,29 +1,44 @@
 use async_trait::async_trait;
 use houseflow_accessory_hal::Accessory;
 use houseflow_api::hub::hive::HiveClient;
+use houseflow_config::accessory::services;
 use houseflow_config::accessory::Services;
+use houseflow_config::Command;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::characteristics::CurrentHeatingCoolingState;
 use houseflow_types::accessory::characteristics::CurrentTemperature;
+use houseflow_types::accessory::characteristics::HeatingCoolingMode;
+use houseflow_types::accessory::characteristics::HeatingCoolingState;
+use houseflow_types::accessory::characteristics::TargetHeatingCoolingState;
+use houseflow_types::accessory::characteristics::TargetTemperature;
+use houseflow_types::accessory::characteristics::TemperatureDisplayUnits;
+use houseflow_types::accessory::characteristics::TemperatureUnits;
 use houseflow_types::accessory::services::ServiceName;
 use houseflow_types::accessory::Error;
 use std::str::FromStr;
+use std::time::Duration;
 
 pub struct VirtualAccessory {
     services: Services,
+    temperature_display_units: TemperatureUnits,
 }
 
 impl VirtualAccessory {
     pub fn new(client: HiveClient<VirtualAccessory>, services: Services) -> Self {
-        let Services { temperature_sensor } = services.clone();
+        let Services {
+            temperature_sensor,
+            thermostat,
+            air_quality_sensor,
+        } = services.clone();
         if let Some(service) = temperature_sensor {
+            let client = client.clone();
             tokio::spawn(async move {
                 loop {
-                    let temperature = service.current_temperature.command.execute().unwrap();
-                    let temperature = std::str::from_utf8(&temperature).unwrap().trim();
-                    let temperature = f32::from_str(temperature).unwrap();
+                    let temperature = read(&service.current_temperature.command);
                     let characteristic =
                         Characteristic::CurrentTemperature(CurrentTemperature { temperature });
                     client
@@ -33,7 +48,141 @@ impl VirtualAccessory {
                 }
             });
         };
-        Self { services }
+        if let Some(service) = air_quality_sensor {
+            for (characteristic_name, command, interval) in air_quality_readings(&service) {
+                let client = client.clone();
+                let command = command.clone();
+                tokio::spawn(async move {
+                    loop {
+                        client
+                            .update(
+                                ServiceName::AirQualitySensor,
+                                read_air_quality(characteristic_name, &command),
+                            )
+                            .await;
+                        tokio::time::sleep(interval).await;
+                    }
+                });
+            }
+        };
+        if let Some(service) = thermostat {
+            tokio::spawn(async move {
+                loop {
+                    let temperature = read(&service.current_temperature.command);
+                    client
+                        .update(
+                            ServiceName::Thermostat,
+                            Characteristic::CurrentTemperature(CurrentTemperature { temperature }),
+                        )
+                        .await;
+                    client
+                        .update(
+                            ServiceName::Thermostat,
+                            Characteristic::CurrentHeatingCoolingState(
+                                CurrentHeatingCoolingState {
+                                    state: heating_cooling_state(&service),
+                                },
+                            ),
+                        )
+                        .await;
+                    tokio::time::sleep(service.current_temperature.interval).await;
+                }
+            });
+        };
+        Self {
+            services,
+            temperature_display_units: TemperatureUnits::Celsius,
+        }
+    }
+}
+
+/// Executes the command and parses its trimmed output.
+fn read<T: FromStr>(command: &Command) -> T
+where
+    T::Err: std::fmt::Debug,
+{
+    let output = command.execute().unwrap();
+    let output = std::str::from_utf8(&output).unwrap().trim();
+    T::from_str(output).unwrap()
+}
+
+fn failed(err: std::io::Error) -> Error {
+    tracing::error!("failed to execute the command: {}", err);
+    accessory::Error::Failed
+}
+
+/// Derives what the thermostat is doing from its mode and temperatures.
+fn heating_cooling_state(service: &services::Thermostat) -> HeatingCoolingState {
+    let current: f32 = read(&service.current_temperature.command);
+    let target: f32 = read(&service.target_temperature.read);
+    let mode: HeatingCoolingMode = read(&service.target_heating_cooling_state.read);
+    match mode {
+        HeatingCoolingMode::Heat | HeatingCoolingMode::Auto if current < target => {
+            HeatingCoolingState::Heat
+        }
+        HeatingCoolingMode::Cool | HeatingCoolingMode::Auto if current > target => {
+            HeatingCoolingState::Cool
+        }
+        _ => HeatingCoolingState::Off,
+    }
+}
+
+/// Configured characteristics of the air quality sensor with the commands reading them.
+fn air_quality_readings(
+    service: &services::AirQualitySensor,
+) -> Vec<(CharacteristicName, &Command, Duration)> {
+    let mut readings = vec![(
+        CharacteristicName::AirQuality,
+        &service.air_quality.command,
+        service.air_quality.interval,
+    )];
+    if let Some(carbon_dioxide_level) = &service.carbon_dioxide_level {
+        readings.push((
+            CharacteristicName::CarbonDioxideLevel,
+            &carbon_dioxide_level.command,
+            carbon_dioxide_level.interval,
+        ));
+    }
+    let densities = [
+        (CharacteristicName::Pm25Density, &service.pm25_density),
+        (CharacteristicName::Pm10Density, &service.pm10_density),
+        (CharacteristicName::VocDensity, &service.voc_density),
+    ];
+    for (characteristic_name, density) in densities {
+        if let Some(density) = density {
+            readings.push((characteristic_name, &density.command, density.interval));
+        }
+    }
+    readings
+}
+
+fn read_air_quality(characteristic_name: CharacteristicName, command: &Command) -> Characteristic {
+    match characteristic_name {
+        CharacteristicName::AirQuality => Characteristic::AirQuality(characteristics::AirQuality {
+            quality: read(command),
+        }),
+        CharacteristicName::CarbonDioxideLevel => {
+            Characteristic::CarbonDioxideLevel(characteristics::CarbonDioxideLevel {
+                ppm: read(command),
+            })
+        }
+        CharacteristicName::Pm25Density => {
+            Characteristic::Pm25Density(characteristics::Pm25Density {
+                density: read(command),
+            })
+        }
+        CharacteristicName::Pm10Density => {
+            Characteristic::Pm10Density(characteristics::Pm10Density {
+                density: read(command),
+            })
+        }
+        CharacteristicName::VocDensity => Characteristic::VocDensity(characteristics::VocDensity {
+            density: read(command),
+        }),
+        _ => unreachable!(
+            "{} is not an air quality characteristic",
+            characteristic_name
+        ),
     }
 }
 
@@ -53,8 +202,56 @@ impl Accessory for VirtualAccessory {
                     _ => return Err(accessory::Error::CharacteristicNotSupported),
                 }
             }
+            ServiceName::AirQualitySensor if self.services.air_quality_sensor.is_some() => {
+                match characteristic {
+                    Characteristic::AirQuality(_)
+                    | Characteristic::CarbonDioxideLevel(_)
+                    | Characteristic::Pm25Density(_)
+                    | Characteristic::Pm10Density(_)
+                    | Characteristic::VocDensity(_) => {
+                        return Err(accessory::Error::CharacteristicReadOnly)
+                    }
+                    _ => return Err(accessory::Error::CharacteristicNotSupported),
+                }
+            }
+            ServiceName::Thermostat if self.services.thermostat.is_some() => {
+                let service = self.services.thermostat.as_ref().unwrap();
+                match characteristic {
+                    Characteristic::TargetTemperature(TargetTemperature { temperature }) => {
+                        service
+                            .target_temperature
+                            .write
+                            .execute_with_value(temperature)
+                            .map_err(failed)?;
+                        Ok(())
+                    }
+                    Characteristic::TargetHeatingCoolingState(TargetHeatingCoolingState {
+                        mode,
+                    }) => {
+                        service
+                            .target_heating_cooling_state
+                            .write
+                            .execute_with_value(mode)
+                            .map_err(failed)?;
+                        Ok(())
+                    }
+                    Characteristic::TemperatureDisplayUnits(TemperatureDisplayUnits { units }) => {
+                        self.temperature_display_units = units;
+                        Ok(())
+                    }
+                    Characteristic::CurrentTemperature(_)
+                    | Characteristic::CurrentHeatingCoolingState(_) => {
+                        return Err(accessory::Error::CharacteristicReadOnly)
+                    }
+                    _ => return Err(accessory::Error::CharacteristicNotSupported),
+                }
+            }
             _ => return Err(accessory::Error::ServiceNotSupported),
-        };
+        }
+    }
+
+    fn capabilities(&self) -> Option<Capabilities> {
+        Some(self.services.capabilities())
     }
 
     async fn read_characteristic(
@@ -67,13 +264,53 @@ impl Accessory for VirtualAccessory {
                 let service = self.services.temperature_sensor.as_ref().unwrap();
                 match characteristic_name {
                     CharacteristicName::CurrentTemperature => {
-                        let temperature = service.current_temperature.command.execute().unwrap();
-                        let temperature = std::str::from_utf8(&temperature).unwrap().trim();
-                        let temperature = f32::from_str(temperature).unwrap();
+                        let temperature = read(&service.current_temperature.command);
+                        Ok(Characteristic::CurrentTemperature(CurrentTemperature {
+                            temperature,
+                        }))
+                    }
+                    _ => return Err(accessory::Error::CharacteristicNotSupported),
+                }
+            }
+            ServiceName::AirQualitySensor if self.services.air_quality_sensor.is_some() => {
+                let service = self.services.air_quality_sensor.as_ref().unwrap();
+                air_quality_readings(service)
+                    .into_iter()
+                    .find(|(name, _, _)| *name == characteristic_name)
+                    .map(|(name, command, _)| read_air_quality(name, command))
+                    .ok_or(accessory::Error::CharacteristicNotSupported)
+            }
+            ServiceName::Thermostat if self.services.thermostat.is_some() => {
+                let service = self.services.thermostat.as_ref().unwrap();
+                match characteristic_name {
+                    CharacteristicName::CurrentTemperature => {
+                        let temperature = read(&service.current_temperature.command);
                         Ok(Characteristic::CurrentTemperature(CurrentTemperature {
                             temperature,
                         }))
                     }
+                    CharacteristicName::TargetTemperature => {
+                        let temperature = read(&service.target_temperature.read);
+                        Ok(Characteristic::TargetTemperature(TargetTemperature {
+                            temperature,
+                        }))
+                    }
+                    CharacteristicName::CurrentHeatingCoolingState => Ok(
+                        Characteristic::CurrentHeatingCoolingState(CurrentHeatingCoolingState {
+                            state: heating_cooling_state(service),
+                        }),
+                    ),
+                    CharacteristicName::TargetHeatingCoolingState => {
+                        let mode = read(&service.target_heating_cooling_state.read);
+                        Ok(Characteristic::TargetHeatingCoolingState(
+                            TargetHeatingCoolingState { mode },
+                        ))
+                    }
+                    CharacteristicName::TemperatureDisplayUnits => Ok(
+                        Characteristic::TemperatureDisplayUnits(TemperatureDisplayUnits {
+                            units: self.temperature_display_units,
+                        }),
+                    ),
                     _ => return Err(accessory::Error::CharacteristicNotSupported),
                 }
             }
@@ -28,10 +28,11 @@ async fn main() -> Result<(), anyhow::Error> {
         Err(err) => panic!("Config error: {}", err),
     };
     tracing::debug!("Config: {:#?}", config);
-    let (_, future) = HiveClient::connect(
+    let (_, future) = HiveClient::connect_with_encoding(
         |client| VirtualAccessory::new(client, config.services),
         config.credentials,
         config.hub.url,
+        config.hub.encoding,
     )
     .await;
     future.await.unwrap();
@@ -0,0 +1,87 @@
+use super::send_request;
+use crate::Error;
+use houseflow_config::client::Hub;
+use houseflow_types::accessory;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::hub;
+use houseflow_types::meta;
+use url::Url;
+
+/// Blocking version of [`crate::hub::HubClient`]
+#[derive(Debug, Clone)]
+pub struct HubClient {
+    client: reqwest::blocking::Client,
+    hub: Hub,
+}
+
+impl HubClient {
+    pub fn new(hub: Hub) -> Self {
+        Self {
+            hub,
+            client: Default::default(),
+        }
+    }
+
+    fn url(&self, path: &str) -> Url {
+        self.hub.url.join(path).unwrap()
+    }
+
+    fn meta_url(&self, path: &str) -> Url {
+        self.url(&format!("controller/meta/{}", path))
+    }
+
+    /// Checks whether the hub is up, fails if it's unreachable or responds with an unsuccessful status
+    pub fn health_check(&self) -> Result<(), Error> {
+        self.client
+            .get(self.url("health-check"))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub fn read_characteristic(
+        &self,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic_name: &CharacteristicName,
+    ) -> Result<Result<Characteristic, hub::Error>, Error> {
+        let url = self.meta_url(&format!(
+            "characteristic/{}/{}/{}",
+            accessory_id, service_name, characteristic_name
+        ));
+        send_request(self.client.get(url))
+    }
+
+    pub fn write_characteristic(
+        &self,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic: &Characteristic,
+    ) -> Result<Result<(), hub::Error>, Error> {
+        let url = self.meta_url(&format!("characteristic/{}/{}", accessory_id, service_name));
+        send_request(self.client.post(url).json(characteristic))
+    }
+
+    pub fn batch_read_characteristics(
+        &self,
+        request: &meta::BatchReadRequest,
+    ) -> Result<Result<meta::BatchReadResponse, hub::Error>, Error> {
+        let url = self.meta_url("characteristics/batch-read");
+        send_request(self.client.post(url).json(request))
+    }
+
+    pub fn batch_write_characteristics(
+        &self,
+        request: &meta::BatchWriteRequest,
+    ) -> Result<Result<meta::BatchWriteResponse, hub::Error>, Error> {
+        let url = self.meta_url("characteristics/batch-write");
+        send_request(self.client.post(url).json(request))
+    }
+
+    pub fn identify(&self, accessory_id: &accessory::ID) -> Result<Result<(), hub::Error>, Error> {
+        let url = self.meta_url(&format!("accessory/{}/identify", accessory_id));
+        send_request(self.client.post(url))
+    }
+}
@@ -0,0 +1,24 @@
+//! Synchronous versions of the clients, for use without an async runtime.
+//!
+//! Like with `reqwest::blocking`, they must not be used from within an async runtime.
+
+#[cfg(any(feature = "server-auth", feature = "server-meta"))]
+pub mod server;
+
+#[cfg(feature = "hub-meta")]
+pub mod hub;
+
+#[cfg(any(feature = "server-auth", feature = "server-meta", feature = "hub-meta"))]
+use crate::Error;
+#[cfg(any(feature = "server-auth", feature = "server-meta", feature = "hub-meta"))]
+use serde::de::DeserializeOwned;
+
+#[cfg(any(feature = "server-auth", feature = "server-meta", feature = "hub-meta"))]
+pub(crate) fn send_request<B: DeserializeOwned, E: DeserializeOwned>(
+    request: reqwest::blocking::RequestBuilder,
+) -> Result<Result<B, E>, Error> {
+    let response = request.send()?;
+    let status_code = response.status();
+    let bytes = response.bytes()?;
+    crate::parse_response(status_code, &bytes)
+}
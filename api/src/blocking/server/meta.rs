@@ -0,0 +1,159 @@
+use super::Client;
+use crate::Error;
+use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::errors::ServerError;
+use houseflow_types::meta;
+use houseflow_types::token::AccessToken;
+use reqwest::Url;
+
+impl Client {
+    fn meta_url(&self, path: &str) -> Url {
+        self.config
+            .server
+            .url
+            .join(&format!("controller/meta/{}", path))
+            .unwrap()
+    }
+
+    pub fn read_characteristics(
+        &self,
+        access_token: &AccessToken,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic_name: &CharacteristicName,
+    ) -> Result<Result<meta::ReadResponse, ServerError>, Error> {
+        let url = self.meta_url(&format!(
+            "characteristic/{}/{}/{}",
+            accessory_id, service_name, characteristic_name
+        ));
+        self.get_with_token(url, &(), access_token)
+    }
+
+    pub fn write_characteristics(
+        &self,
+        access_token: &AccessToken,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic: &Characteristic,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self.meta_url(&format!("characteristic/{}/{}", accessory_id, service_name));
+        self.post_with_token(url, characteristic, access_token)
+    }
+
+    pub fn batch_read_characteristics(
+        &self,
+        request: &meta::BatchReadRequest,
+    ) -> Result<Result<meta::BatchReadResponse, ServerError>, Error> {
+        let url = self.meta_url("characteristics/batch-read");
+        self.post(url, request)
+    }
+
+    pub fn batch_write_characteristics(
+        &self,
+        request: &meta::BatchWriteRequest,
+    ) -> Result<Result<meta::BatchWriteResponse, ServerError>, Error> {
+        let url = self.meta_url("characteristics/batch-write");
+        self.post(url, request)
+    }
+
+    pub fn identify(
+        &self,
+        access_token: &AccessToken,
+        accessory_id: &accessory::ID,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self.meta_url(&format!("accessory/{}/identify", accessory_id));
+        self.post_with_token(url, &(), access_token)
+    }
+
+    pub fn list_accessories(
+        &self,
+        access_token: &AccessToken,
+    ) -> Result<Result<meta::ListAccessoriesResponse, ServerError>, Error> {
+        let url = self.meta_url("accessories");
+        self.get_with_token(url, &(), access_token)
+    }
+
+    pub fn accessory_capabilities(
+        &self,
+        accessory_id: &accessory::ID,
+    ) -> Result<Result<Capabilities, ServerError>, Error> {
+        let url = self.meta_url(&format!("accessory/{}/capabilities", accessory_id));
+        self.get(url, &())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Path;
+    use axum::routing::get;
+    use axum::Json;
+    use houseflow_config::client::Config;
+    use houseflow_types::accessory::manufacturers;
+    use houseflow_types::errors::ControllerError;
+
+    fn thermometer() -> accessory::Type {
+        accessory::Type::XiaomiMijia(manufacturers::XiaomiMijia::HygroThermometer)
+    }
+
+    /// Serves the capabilities of the nil accessory, other accessories are not connected
+    async fn capabilities(
+        Path(accessory_id): Path<accessory::ID>,
+    ) -> Result<Json<Capabilities>, ServerError> {
+        if accessory_id.is_nil() {
+            Ok(Json(thermometer().capabilities()))
+        } else {
+            Err(ControllerError::AccessoryNotConnected.into())
+        }
+    }
+
+    /// Runs the server on a separate thread, as the blocking client can't be used inside an async runtime
+    fn serve(app: axum::Router) -> Url {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async move {
+                    axum::Server::from_tcp(listener)
+                        .unwrap()
+                        .serve(app.into_make_service())
+                        .await
+                })
+                .unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn accessory_capabilities() {
+        let app = axum::Router::new().route(
+            "/controller/meta/accessory/:accessory_id/capabilities",
+            get(capabilities),
+        );
+        let client = Client::new(Config {
+            server: houseflow_config::client::Server { url: serve(app) },
+            ..Default::default()
+        });
+
+        let response = client
+            .accessory_capabilities(&accessory::ID::nil())
+            .unwrap();
+        assert_eq!(response, Ok(thermometer().capabilities()));
+        let response = client
+            .accessory_capabilities(&accessory::ID::new_v4())
+            .unwrap();
+        assert_eq!(
+            response,
+            Err(ServerError::ControllerError(
+                ControllerError::AccessoryNotConnected
+            ))
+        );
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(feature = "server-auth")]
+pub mod auth;
+
+#[cfg(feature = "server-meta")]
+pub mod meta;
+
+use super::send_request;
+use crate::Error;
+use houseflow_config::client::Config;
+use houseflow_types::token::Token;
+use houseflow_types::token::TokenClaims;
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use url::Url;
+
+/// Blocking version of [`crate::server::Client`]
+#[derive(Debug, Clone)]
+pub struct Client {
+    client: reqwest::blocking::Client,
+    config: Config,
+}
+
+impl Client {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            client: Default::default(),
+        }
+    }
+
+    pub(crate) fn post<B, E>(&self, url: Url, body: &impl Serialize) -> Result<Result<B, E>, Error>
+    where
+        B: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let request = self.client.post(url).json(body);
+        send_request(request)
+    }
+
+    #[cfg(feature = "server-meta")]
+    pub(crate) fn get<B, E>(&self, url: Url, body: &impl Serialize) -> Result<Result<B, E>, Error>
+    where
+        B: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let request = self.client.get(url).json(body);
+        send_request(request)
+    }
+
+    pub(crate) fn post_with_token<TC, B, E>(
+        &self,
+        url: Url,
+        body: &impl Serialize,
+        token: &Token<TC>,
+    ) -> Result<Result<B, E>, Error>
+    where
+        TC: TokenClaims,
+        B: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let request = self.client.post(url).json(body).bearer_auth(token);
+        send_request(request)
+    }
+
+    pub(crate) fn get_with_token<TC, B, E>(
+        &self,
+        url: Url,
+        body: &impl Serialize,
+        token: &Token<TC>,
+    ) -> Result<Result<B, E>, Error>
+    where
+        TC: TokenClaims,
+        B: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let request = self.client.get(url).json(body).bearer_auth(token);
+        send_request(request)
+    }
+}
@@ -3,15 +3,26 @@ use ezsockets::ClientConfig;
 use futures::Future;
 use houseflow_accessory_hal::Accessory;
 use houseflow_config::accessory::Credentials;
+use houseflow_types::accessory;
+use houseflow_types::accessory::characteristics;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::hive;
 use houseflow_types::hive::AccessoryFrame;
+use houseflow_types::hive::BatchCharacteristicReadResult;
+use houseflow_types::hive::BatchCharacteristicWriteResult;
+use houseflow_types::hive::BatchReadCharacteristic;
+use houseflow_types::hive::BatchWriteCharacteristic;
 use houseflow_types::hive::CharacteristicReadResult;
 use houseflow_types::hive::CharateristicWriteResult;
 use houseflow_types::hive::HubFrame;
+use houseflow_types::hive::HubFrameType;
+use houseflow_types::hive::Identify;
+use houseflow_types::hive::IdentifyResult;
 use houseflow_types::hive::ReadCharacteristic;
 use houseflow_types::hive::UpdateCharacteristic;
 use houseflow_types::hive::WriteCharacteristic;
+use houseflow_types::protocol;
 use reqwest::Url;
 
 pub struct HiveClient<A: Accessory> {
@@ -31,54 +42,145 @@ impl<A: Accessory> HiveClient<A> {
         accessory_fn: impl FnOnce(Self) -> A,
         credentials: Credentials,
         hub_url: Url,
+    ) -> (Self, impl Future<Output = Result<(), ezsockets::Error>>) {
+        Self::connect_with_encoding(accessory_fn, credentials, hub_url, Default::default()).await
+    }
+
+    /// Connects to the hub, preferring the encoding for the frames sent after the handshake.
+    ///
+    /// Falls back to JSON if the hub doesn't support the encoding.
+    pub async fn connect_with_encoding(
+        accessory_fn: impl FnOnce(Self) -> A,
+        credentials: Credentials,
+        hub_url: Url,
+        encoding: protocol::Encoding,
     ) -> (Self, impl Future<Output = Result<(), ezsockets::Error>>) {
         let hive_url = hub_url.join("provider/hive/websocket").unwrap();
+        let config = ClientConfig::new(hive_url).basic(
+            &credentials.id.to_string(),
+            &credentials.password.to_string(),
+        );
+        Self::connect_with_config(accessory_fn, config, encoding).await
+    }
+
+    async fn connect_with_config(
+        accessory_fn: impl FnOnce(Self) -> A,
+        config: ClientConfig,
+        encoding: protocol::Encoding,
+    ) -> (Self, impl Future<Output = Result<(), ezsockets::Error>>) {
         let (client, future) = ezsockets::connect(
-            |client| {
+            move |client| {
                 let client = Self { client };
                 let accessory = accessory_fn(client.clone());
-                HiveClientActor { accessory, client }
+                HiveClientActor {
+                    accessory,
+                    client,
+                    preferred_encoding: encoding,
+                    peer: None,
+                    encoding: Default::default(),
+                }
             },
-            ClientConfig::new(hive_url).basic(
-                &credentials.id.to_string(),
-                &credentials.password.to_string(),
-            ),
+            config,
         )
         .await;
         (Self { client }, future)
     }
 
-    async fn frame(&self, frame: AccessoryFrame) {
-        let s = serde_json::to_string(&frame).unwrap();
-        self.client.text(s).await;
+    pub async fn update(&self, service_name: ServiceName, characteristic: Characteristic) {
+        self.client
+            .call(AccessoryFrame::UpdateCharacteristic(UpdateCharacteristic {
+                service_name,
+                characteristic,
+            }))
+            .await;
     }
 
-    pub async fn update(&self, service_name: ServiceName, characteristic: Characteristic) {
-        self.frame(AccessoryFrame::UpdateCharacteristic(UpdateCharacteristic {
-            service_name,
-            characteristic,
-        }))
-        .await;
+    /// Reports whether the binary sensor service, e.g. a motion sensor, detected something.
+    ///
+    /// Fails with `ServiceNotSupported` if the service isn't a binary sensor.
+    pub async fn update_detected(
+        &self,
+        service_name: ServiceName,
+        detected: bool,
+    ) -> Result<(), accessory::Error> {
+        let characteristic = service_name
+            .detected(detected)
+            .ok_or(accessory::Error::ServiceNotSupported)?;
+        self.update(service_name, characteristic).await;
+        Ok(())
+    }
+
+    /// Reports whether the sensor has been tampered with.
+    pub async fn update_tampered(&self, service_name: ServiceName, tampered: bool) {
+        let characteristic =
+            Characteristic::StatusTampered(characteristics::StatusTampered { tampered });
+        self.update(service_name, characteristic).await;
+    }
+
+    /// Reports whether the battery of the sensor is low.
+    pub async fn update_low_battery(&self, service_name: ServiceName, low_battery: bool) {
+        let characteristic =
+            Characteristic::StatusLowBattery(characteristics::StatusLowBattery { low_battery });
+        self.update(service_name, characteristic).await;
     }
 }
 
 struct HiveClientActor<A: Accessory> {
     accessory: A,
     client: HiveClient<A>,
+    /// Encoding preferred in our `Hello`
+    preferred_encoding: protocol::Encoding,
+    /// Handshake sent by the hub, `None` until the hub responds to our `Hello`
+    peer: Option<protocol::Hello>,
+    /// Encoding chosen by the hub, JSON until the hub responds to our `Hello`
+    encoding: protocol::Encoding,
 }
 
-#[async_trait]
-impl<A: Accessory> ezsockets::ClientExt for HiveClientActor<A> {
-    type Params = ();
-
-    async fn call(&mut self, params: Self::Params) -> Result<(), ezsockets::Error> {
-        let () = params;
+impl<A: Accessory> HiveClientActor<A> {
+    async fn send(&mut self, frame: AccessoryFrame) -> Result<(), ezsockets::Error> {
+        match self.encoding.encode(&frame)? {
+            protocol::Payload::Text(text) => self.client.client.text(text).await,
+            protocol::Payload::Binary(bytes) => self.client.client.binary(bytes).await,
+        }
         Ok(())
     }
 
-    async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
-        let frame = serde_json::from_str::<HubFrame>(&text)?;
+    async fn frame(
+        &mut self,
+        frame: Result<HubFrame, protocol::Error>,
+    ) -> Result<(), ezsockets::Error> {
         let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                tracing::warn!("received invalid frame: {}", err);
+                return self.send(AccessoryFrame::Error(err)).await;
+            }
+        };
+        let frame = match frame {
+            HubFrame::Hello(hello) => {
+                self.encoding = hello.encoding();
+                tracing::info!(software_version = %hello.software_version, encoding = %self.encoding, "negotiated hive protocol version {}", hello.protocol_version);
+                let capabilities = if hello.protocol_version >= hive::CAPABILITIES_PROTOCOL_VERSION
+                {
+                    Some(AccessoryFrame::Capabilities(hive::Capabilities {
+                        capabilities: self.accessory.capabilities(),
+                    }))
+                } else {
+                    None
+                };
+                self.peer = Some(hello);
+                capabilities
+            }
+            HubFrame::Error(err) => match err {
+                protocol::Error::UnsupportedVersion { .. } => {
+                    tracing::error!("hub rejected the connection: {}", err);
+                    return Err(err.into());
+                }
+                err => {
+                    tracing::warn!("hub responded with error: {}", err);
+                    None
+                }
+            },
             HubFrame::ReadCharacteristic(ReadCharacteristic {
                 id,
                 service_name,
@@ -109,16 +211,230 @@ impl<A: Accessory> ezsockets::ClientExt for HiveClientActor<A> {
                 };
                 Some(AccessoryFrame::CharacteristicWriteResult(frame))
             }
-            _ => unimplemented!(),
+            HubFrame::BatchReadCharacteristic(BatchReadCharacteristic { id, reads }) => {
+                let mut results = Vec::with_capacity(reads.len());
+                for read in reads {
+                    let result = self
+                        .accessory
+                        .read_characteristic(read.service_name, read.characteristic_name)
+                        .await;
+                    results.push(result.into());
+                }
+                let frame = BatchCharacteristicReadResult { id, results };
+                Some(AccessoryFrame::BatchCharacteristicReadResult(frame))
+            }
+            HubFrame::BatchWriteCharacteristic(BatchWriteCharacteristic { id, writes }) => {
+                let mut results = Vec::with_capacity(writes.len());
+                for write in writes {
+                    let result = self
+                        .accessory
+                        .write_characteristic(write.service_name, write.characteristic)
+                        .await;
+                    results.push(result.into());
+                }
+                let frame = BatchCharacteristicWriteResult { id, results };
+                Some(AccessoryFrame::BatchCharacteristicWriteResult(frame))
+            }
+            HubFrame::Identify(Identify { id }) => {
+                let result = self.accessory.identify().await;
+                let frame = IdentifyResult {
+                    id,
+                    result: result.into(),
+                };
+                Some(AccessoryFrame::IdentifyResult(frame))
+            }
+            frame => {
+                let frame_type = HubFrameType::from(&frame).to_string();
+                Some(AccessoryFrame::Error(protocol::Error::UnknownFrame(
+                    frame_type,
+                )))
+            }
         };
         if let Some(frame) = frame {
-            self.client.frame(frame).await;
+            self.send(frame).await?;
         }
 
         Ok(())
     }
+}
+
+#[async_trait]
+impl<A: Accessory> ezsockets::ClientExt for HiveClientActor<A> {
+    type Params = AccessoryFrame;
+
+    async fn call(&mut self, params: Self::Params) -> Result<(), ezsockets::Error> {
+        self.send(params).await
+    }
+
+    async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode(&text, HubFrameType::VARIANTS);
+        self.frame(frame).await
+    }
+
+    async fn binary(&mut self, bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode_binary(&bytes, HubFrameType::VARIANTS);
+        self.frame(frame).await
+    }
+
+    async fn connected(&mut self) -> Result<(), ezsockets::Error> {
+        // the hub has a new session, which knows nothing about the previous connection
+        self.peer = None;
+        self.encoding = Default::default();
+        let hello = protocol::hello!(
+            hive::PROTOCOL_VERSION,
+            hive::MIN_PROTOCOL_VERSION,
+            HubFrameType::VARIANTS,
+        );
+        let hello = AccessoryFrame::Hello(hello.prefer(self.preferred_encoding));
+        self.client
+            .client
+            .text(serde_json::to_string(&hello)?)
+            .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ws::Message;
+    use axum::extract::ws::WebSocket;
+    use axum::extract::ws::WebSocketUpgrade;
+    use axum::extract::Extension;
+    use axum::routing::get;
+    use houseflow_types::accessory::characteristics::CharacteristicName;
+    use houseflow_types::hive::AccessoryFrameType;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::sync::Mutex;
+
+    struct Dummy;
+
+    #[async_trait]
+    impl Accessory for Dummy {
+        async fn write_characteristic(
+            &mut self,
+            _service_name: ServiceName,
+            _characteristic: Characteristic,
+        ) -> Result<(), accessory::Error> {
+            Err(accessory::Error::CharacteristicNotSupported)
+        }
 
-    async fn binary(&mut self, _bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
-        unimplemented!()
+        async fn read_characteristic(
+            &mut self,
+            _service_name: ServiceName,
+            _characteristic_name: CharacteristicName,
+        ) -> Result<Characteristic, accessory::Error> {
+            Err(accessory::Error::CharacteristicNotSupported)
+        }
+    }
+
+    /// Frames received by the hub, along with the number of the connection they were received on
+    type Received = mpsc::UnboundedSender<(usize, Message)>;
+
+    async fn websocket(
+        upgrade: WebSocketUpgrade,
+        Extension(connections): Extension<Arc<Mutex<usize>>>,
+        Extension(received): Extension<Received>,
+    ) -> axum::response::Response {
+        upgrade.on_upgrade(move |socket| async move {
+            let connection = {
+                let mut connections = connections.lock().await;
+                *connections += 1;
+                *connections
+            };
+            session(socket, connection, received).await
+        })
+    }
+
+    /// Receives the next frame, skipping the heartbeats
+    async fn recv(socket: &mut WebSocket) -> Message {
+        loop {
+            match socket.recv().await.unwrap().unwrap() {
+                Message::Ping(_) | Message::Pong(_) => continue,
+                message => return message,
+            }
+        }
+    }
+
+    async fn session(mut socket: WebSocket, connection: usize, received: Received) {
+        let hello = recv(&mut socket).await;
+        received.send((connection, hello)).unwrap();
+        if connection == 1 {
+            // negotiate MessagePack, then drop the connection
+            let mut hello = protocol::hello!(
+                hive::PROTOCOL_VERSION,
+                hive::MIN_PROTOCOL_VERSION,
+                AccessoryFrameType::VARIANTS,
+            );
+            hello.encodings = vec![protocol::Encoding::MessagePack];
+            let hello = HubFrame::Hello(hello);
+            let hello = Message::Text(serde_json::to_string(&hello).unwrap());
+            socket.send(hello).await.unwrap();
+            socket.close().await.unwrap();
+        } else {
+            // no `Hello` on this connection yet, so the accessory must respond in JSON
+            let identify = HubFrame::Identify(Identify { id: 1 });
+            let identify = Message::Text(serde_json::to_string(&identify).unwrap());
+            socket.send(identify).await.unwrap();
+            let response = recv(&mut socket).await;
+            received.send((connection, response)).unwrap();
+        }
+    }
+
+    async fn next(frames: &mut mpsc::UnboundedReceiver<(usize, Message)>) -> (usize, Message) {
+        tokio::time::timeout(Duration::from_secs(5), frames.recv())
+            .await
+            .expect("hub did not receive the frame")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn handshake_after_reconnecting() {
+        let (received, mut frames): (Received, _) = mpsc::unbounded_channel();
+        let app = axum::Router::new()
+            .route("/provider/hive/websocket", get(websocket))
+            .layer(Extension(Arc::new(Mutex::new(0_usize))))
+            .layer(Extension(received));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "ws://{}/provider/hive/websocket",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let config = ClientConfig::new(url).reconnect_interval(Duration::from_millis(10));
+        let (_client, _future) =
+            HiveClient::connect_with_config(|_| Dummy, config, protocol::Encoding::MessagePack)
+                .await;
+
+        for connection in 1..=2_usize {
+            let (received_on, frame) = next(&mut frames).await;
+            assert_eq!(received_on, connection);
+            let frame = match frame {
+                Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                frame => panic!("unexpected frame: {:?}", frame),
+            };
+            assert!(matches!(frame, AccessoryFrame::Hello(_)));
+        }
+        let (received_on, frame) = next(&mut frames).await;
+        assert_eq!(received_on, 2);
+        let frame: AccessoryFrame = match frame {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            frame => panic!("unexpected frame: {:?}", frame),
+        };
+        assert_eq!(
+            frame,
+            AccessoryFrame::IdentifyResult(IdentifyResult {
+                id: 1,
+                result: Ok(()).into(),
+            })
+        );
     }
 }
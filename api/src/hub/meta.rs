@@ -0,0 +1,82 @@
+use super::HubClient;
+use crate::read_error;
+use crate::send_request;
+use crate::Error;
+use crate::EventStream;
+use houseflow_types::accessory;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::hub;
+use houseflow_types::meta;
+use url::Url;
+
+impl HubClient {
+    fn meta_url(&self, path: &str) -> Url {
+        self.url(&format!("controller/meta/{}", path))
+    }
+
+    pub async fn read_characteristic(
+        &self,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic_name: &CharacteristicName,
+    ) -> Result<Result<Characteristic, hub::Error>, Error> {
+        let url = self.meta_url(&format!(
+            "characteristic/{}/{}/{}",
+            accessory_id, service_name, characteristic_name
+        ));
+        send_request(self.client.get(url)).await
+    }
+
+    pub async fn write_characteristic(
+        &self,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic: &Characteristic,
+    ) -> Result<Result<(), hub::Error>, Error> {
+        let url = self.meta_url(&format!("characteristic/{}/{}", accessory_id, service_name));
+        send_request(self.client.post(url).json(characteristic)).await
+    }
+
+    pub async fn batch_read_characteristics(
+        &self,
+        request: &meta::BatchReadRequest,
+    ) -> Result<Result<meta::BatchReadResponse, hub::Error>, Error> {
+        let url = self.meta_url("characteristics/batch-read");
+        send_request(self.client.post(url).json(request)).await
+    }
+
+    pub async fn batch_write_characteristics(
+        &self,
+        request: &meta::BatchWriteRequest,
+    ) -> Result<Result<meta::BatchWriteResponse, hub::Error>, Error> {
+        let url = self.meta_url("characteristics/batch-write");
+        send_request(self.client.post(url).json(request)).await
+    }
+
+    pub async fn identify(
+        &self,
+        accessory_id: &accessory::ID,
+    ) -> Result<Result<(), hub::Error>, Error> {
+        let url = self.meta_url(&format!("accessory/{}/identify", accessory_id));
+        send_request(self.client.post(url)).await
+    }
+
+    /// Subscribes to the events of the accessories connected to the hub which pass the filter
+    pub async fn watch(
+        &self,
+        filter: &meta::EventFilter,
+    ) -> Result<Result<EventStream, hub::Error>, Error> {
+        let response = self
+            .client
+            .get(self.meta_url("events"))
+            .query(filter)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return read_error(response).await;
+        }
+        Ok(Ok(EventStream::new(response)))
+    }
+}
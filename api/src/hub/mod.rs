@@ -1,2 +1,39 @@
 #[cfg(feature = "hub-hive")]
 pub mod hive;
+
+#[cfg(feature = "hub-meta")]
+pub mod meta;
+
+use crate::Error;
+use houseflow_config::client::Hub;
+use url::Url;
+
+/// Client of the HTTP API of the hub, it's reachable without the server, e.g over LAN
+#[derive(Debug, Clone)]
+pub struct HubClient {
+    client: reqwest::Client,
+    hub: Hub,
+}
+
+impl HubClient {
+    pub fn new(hub: Hub) -> Self {
+        Self {
+            hub,
+            client: Default::default(),
+        }
+    }
+
+    fn url(&self, path: &str) -> Url {
+        self.hub.url.join(path).unwrap()
+    }
+
+    /// Checks whether the hub is up, fails if it's unreachable or responds with an unsuccessful status
+    pub async fn health_check(&self) -> Result<(), Error> {
+        self.client
+            .get(self.url("health-check"))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
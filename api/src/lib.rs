@@ -4,6 +4,12 @@ pub mod server;
 #[cfg(feature = "hub")]
 pub mod hub;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+#[cfg(any(feature = "server", feature = "hub-meta"))]
+use serde::de::DeserializeOwned;
+
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum Error {
@@ -16,4 +22,103 @@ pub enum Error {
         status_code: reqwest::StatusCode,
         body: String,
     },
+
+    #[error("invalid token received: `{0}`")]
+    InvalidToken(#[from] houseflow_types::errors::TokenError),
+}
+
+#[cfg(any(feature = "server", feature = "hub-meta"))]
+pub(crate) async fn send_request<B: DeserializeOwned, E: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+) -> Result<Result<B, E>, Error> {
+    let response = request.send().await?;
+    let status_code = response.status();
+    let bytes = response.bytes().await?;
+    parse_response(status_code, &bytes)
+}
+
+#[cfg(any(feature = "server-meta", feature = "hub-meta"))]
+/// Parses the error from the body of an unsuccessful response
+pub(crate) async fn read_error<B, E: DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<Result<B, E>, Error> {
+    let status_code = response.status();
+    let bytes = response.bytes().await?;
+    parse_body(status_code, &bytes).map(Err)
+}
+
+#[cfg(any(feature = "server-meta", feature = "hub-meta"))]
+/// Events of the accessories sent as server-sent events, by either the server or the hub
+#[derive(Debug)]
+pub struct EventStream {
+    response: reqwest::Response,
+    /// Bytes received after the last complete message
+    buffer: Vec<u8>,
+}
+
+#[cfg(any(feature = "server-meta", feature = "hub-meta"))]
+impl EventStream {
+    pub(crate) fn new(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Waits for the next event, returns `None` once the stream is closed
+    pub async fn next(&mut self) -> Result<Option<houseflow_types::meta::Event>, Error> {
+        loop {
+            if let Some(end) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+                let message = self.buffer.drain(..end + 2).collect::<Vec<_>>();
+                let message = String::from_utf8_lossy(&message);
+                let data = message
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data:"))
+                    .map(str::trim_start)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                // messages without data are keep-alives
+                if data.is_empty() {
+                    continue;
+                }
+                let event =
+                    serde_json::from_str(&data).map_err(|err| Error::InvalidResponseBody {
+                        error: Box::new(err),
+                        status_code: self.response.status(),
+                        body: data,
+                    })?;
+                return Ok(Some(event));
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "server", feature = "hub-meta"))]
+/// Parses the body of a response, shared by the async and blocking clients
+pub(crate) fn parse_response<B: DeserializeOwned, E: DeserializeOwned>(
+    status_code: reqwest::StatusCode,
+    bytes: &[u8],
+) -> Result<Result<B, E>, Error> {
+    if !status_code.is_success() {
+        return parse_body(status_code, bytes).map(Err);
+    }
+    // endpoints which respond with an empty body, e.g writes, return `()`
+    let body: &[u8] = if bytes.is_empty() { b"null" } else { bytes };
+    parse_body(status_code, body).map(Ok)
+}
+
+#[cfg(any(feature = "server", feature = "hub-meta"))]
+fn parse_body<T: DeserializeOwned>(
+    status_code: reqwest::StatusCode,
+    bytes: &[u8],
+) -> Result<T, Error> {
+    serde_json::from_slice(bytes).map_err(|err| Error::InvalidResponseBody {
+        error: Box::new(err),
+        status_code,
+        body: String::from_utf8_lossy(bytes).into_owned(),
+    })
 }
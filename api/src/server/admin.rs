@@ -0,0 +1,145 @@
+use super::Client;
+#[cfg(feature = "server-auth")]
+use super::Session;
+use crate::Error;
+use houseflow_types::admin;
+use houseflow_types::errors::ServerError;
+use houseflow_types::permission::Permission;
+use houseflow_types::structure;
+use houseflow_types::structure::Structure;
+use houseflow_types::token::AccessToken;
+use houseflow_types::user;
+use houseflow_types::user::User;
+use reqwest::Url;
+
+impl Client {
+    fn admin_url(&self, path: &str) -> Url {
+        self.config
+            .server
+            .url
+            .join(&format!("admin/{}", path))
+            .unwrap()
+    }
+
+    pub async fn list_users(
+        &self,
+        access_token: &AccessToken,
+    ) -> Result<Result<admin::ListUsersResponse, ServerError>, Error> {
+        let url = self.admin_url("users");
+        self.get_with_token(url, &(), access_token).await
+    }
+
+    pub async fn add_user(
+        &self,
+        access_token: &AccessToken,
+        request: &admin::AddUserRequest,
+    ) -> Result<Result<User, ServerError>, Error> {
+        let url = self.admin_url("users");
+        self.post_with_token(url, request, access_token).await
+    }
+
+    pub async fn remove_user(
+        &self,
+        access_token: &AccessToken,
+        user_id: &user::ID,
+    ) -> Result<Result<admin::RemoveUserResponse, ServerError>, Error> {
+        let url = self.admin_url(&format!("users/{}", user_id));
+        self.delete_with_token(url, access_token).await
+    }
+
+    pub async fn add_structure(
+        &self,
+        access_token: &AccessToken,
+        request: &admin::AddStructureRequest,
+    ) -> Result<Result<Structure, ServerError>, Error> {
+        let url = self.admin_url("structures");
+        self.post_with_token(url, request, access_token).await
+    }
+
+    pub async fn grant_permission(
+        &self,
+        access_token: &AccessToken,
+        permission: &Permission,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self.admin_url("permissions");
+        self.post_with_token(url, permission, access_token).await
+    }
+
+    pub async fn revoke_permission(
+        &self,
+        access_token: &AccessToken,
+        structure_id: &structure::ID,
+        user_id: &user::ID,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self.admin_url(&format!("permissions/{}/{}", structure_id, user_id));
+        self.delete_with_token(url, access_token).await
+    }
+
+    pub async fn register_hub(
+        &self,
+        access_token: &AccessToken,
+        request: &admin::RegisterHubRequest,
+    ) -> Result<Result<admin::RegisterHubResponse, ServerError>, Error> {
+        let url = self.admin_url("hubs");
+        self.post_with_token(url, request, access_token).await
+    }
+}
+
+#[cfg(feature = "server-auth")]
+impl Session {
+    pub async fn list_users(&self) -> Result<Result<admin::ListUsersResponse, ServerError>, Error> {
+        let url = self.client().admin_url("users");
+        self.get(url, &()).await
+    }
+
+    pub async fn add_user(
+        &self,
+        request: &admin::AddUserRequest,
+    ) -> Result<Result<User, ServerError>, Error> {
+        let url = self.client().admin_url("users");
+        self.post(url, request).await
+    }
+
+    pub async fn remove_user(
+        &self,
+        user_id: &user::ID,
+    ) -> Result<Result<admin::RemoveUserResponse, ServerError>, Error> {
+        let url = self.client().admin_url(&format!("users/{}", user_id));
+        self.delete(url).await
+    }
+
+    pub async fn add_structure(
+        &self,
+        request: &admin::AddStructureRequest,
+    ) -> Result<Result<Structure, ServerError>, Error> {
+        let url = self.client().admin_url("structures");
+        self.post(url, request).await
+    }
+
+    pub async fn grant_permission(
+        &self,
+        permission: &Permission,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self.client().admin_url("permissions");
+        self.post(url, permission).await
+    }
+
+    pub async fn revoke_permission(
+        &self,
+        structure_id: &structure::ID,
+        user_id: &user::ID,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self
+            .client()
+            .admin_url(&format!("permissions/{}/{}", structure_id, user_id));
+        self.delete(url).await
+    }
+
+    pub async fn register_hub(
+        &self,
+        request: &admin::RegisterHubRequest,
+    ) -> Result<Result<admin::RegisterHubResponse, ServerError>, Error> {
+        let url = self.client().admin_url("hubs");
+        self.post(url, request).await
+    }
+}
@@ -1,4 +1,5 @@
 use super::Client;
+use super::Session;
 use crate::Error;
 use houseflow_types::auth;
 use houseflow_types::errors::ServerError;
@@ -41,3 +42,10 @@ impl Client {
             .await
     }
 }
+
+impl Session {
+    pub async fn whoami(&self) -> Result<Result<auth::whoami::Response, ServerError>, Error> {
+        let url = self.client().auth_url("whoami");
+        self.get(url, &auth::whoami::Request {}).await
+    }
+}
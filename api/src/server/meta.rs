@@ -1,10 +1,19 @@
 use super::Client;
+#[cfg(feature = "server-auth")]
+use super::Session;
+use crate::read_error;
 use crate::Error;
+pub use crate::EventStream;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+#[cfg(feature = "server-auth")]
+use houseflow_types::errors::AuthError;
 use houseflow_types::errors::ServerError;
+use houseflow_types::meta;
+use houseflow_types::token::AccessToken;
 use reqwest::Url;
 
 impl Client {
@@ -18,24 +27,156 @@ impl Client {
 
     pub async fn read_characteristics(
         &self,
+        access_token: &AccessToken,
         accessory_id: &accessory::ID,
         service_name: &ServiceName,
         characteristic_name: &CharacteristicName,
-    ) -> Result<Result<Characteristic, ServerError>, Error> {
+    ) -> Result<Result<meta::ReadResponse, ServerError>, Error> {
         let url = self.meta_url(&format!(
             "characteristic/{}/{}/{}",
             accessory_id, service_name, characteristic_name
         ));
-        self.get(url, &()).await
+        self.get_with_token(url, &(), access_token).await
     }
 
     pub async fn write_characteristics(
         &self,
+        access_token: &AccessToken,
         accessory_id: &accessory::ID,
         service_name: &ServiceName,
         characteristic: &Characteristic,
     ) -> Result<Result<(), ServerError>, Error> {
         let url = self.meta_url(&format!("characteristic/{}/{}", accessory_id, service_name));
-        self.get(url, characteristic).await
+        self.post_with_token(url, characteristic, access_token)
+            .await
+    }
+
+    pub async fn batch_read_characteristics(
+        &self,
+        request: &meta::BatchReadRequest,
+    ) -> Result<Result<meta::BatchReadResponse, ServerError>, Error> {
+        let url = self.meta_url("characteristics/batch-read");
+        self.post(url, request).await
+    }
+
+    pub async fn batch_write_characteristics(
+        &self,
+        request: &meta::BatchWriteRequest,
+    ) -> Result<Result<meta::BatchWriteResponse, ServerError>, Error> {
+        let url = self.meta_url("characteristics/batch-write");
+        self.post(url, request).await
+    }
+
+    pub async fn identify(
+        &self,
+        access_token: &AccessToken,
+        accessory_id: &accessory::ID,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self.meta_url(&format!("accessory/{}/identify", accessory_id));
+        self.post_with_token(url, &(), access_token).await
+    }
+
+    pub async fn list_accessories(
+        &self,
+        access_token: &AccessToken,
+    ) -> Result<Result<meta::ListAccessoriesResponse, ServerError>, Error> {
+        let url = self.meta_url("accessories");
+        self.get_with_token(url, &(), access_token).await
+    }
+
+    /// Subscribes to the events of the accessories which pass the filter
+    pub async fn watch(
+        &self,
+        access_token: &AccessToken,
+        filter: &meta::EventFilter,
+    ) -> Result<Result<EventStream, ServerError>, Error> {
+        let url = self.meta_url("events");
+        let response = self
+            .client
+            .get(url)
+            .query(filter)
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return read_error(response).await;
+        }
+        Ok(Ok(EventStream::new(response)))
+    }
+
+    pub async fn accessory_capabilities(
+        &self,
+        accessory_id: &accessory::ID,
+    ) -> Result<Result<Capabilities, ServerError>, Error> {
+        let url = self.meta_url(&format!("accessory/{}/capabilities", accessory_id));
+        self.get(url, &()).await
+    }
+}
+
+#[cfg(feature = "server-auth")]
+impl Session {
+    pub async fn read_characteristics(
+        &self,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic_name: &CharacteristicName,
+    ) -> Result<Result<meta::ReadResponse, ServerError>, Error> {
+        let url = self.client().meta_url(&format!(
+            "characteristic/{}/{}/{}",
+            accessory_id, service_name, characteristic_name
+        ));
+        self.get(url, &()).await
+    }
+
+    pub async fn write_characteristics(
+        &self,
+        accessory_id: &accessory::ID,
+        service_name: &ServiceName,
+        characteristic: &Characteristic,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self
+            .client()
+            .meta_url(&format!("characteristic/{}/{}", accessory_id, service_name));
+        self.post(url, characteristic).await
+    }
+
+    pub async fn identify(
+        &self,
+        accessory_id: &accessory::ID,
+    ) -> Result<Result<(), ServerError>, Error> {
+        let url = self
+            .client()
+            .meta_url(&format!("accessory/{}/identify", accessory_id));
+        self.post(url, &()).await
+    }
+
+    pub async fn list_accessories(
+        &self,
+    ) -> Result<Result<meta::ListAccessoriesResponse, ServerError>, Error> {
+        let url = self.client().meta_url("accessories");
+        self.get(url, &()).await
+    }
+
+    /// Subscribes to the events of the accessories which pass the filter.
+    ///
+    /// The stream is not resumed when it breaks, only opening it is covered by the refresh.
+    pub async fn watch(
+        &self,
+        filter: &meta::EventFilter,
+    ) -> Result<Result<EventStream, ServerError>, Error> {
+        let access_token = match self.access_token().await? {
+            Ok(access_token) => access_token,
+            Err(err) => return Ok(Err(err)),
+        };
+        match self.client().watch(&access_token, filter).await? {
+            Err(ServerError::AuthError(AuthError::InvalidToken(_))) => {
+                let access_token = match self.refresh_rejected(&access_token).await? {
+                    Ok(access_token) => access_token,
+                    Err(err) => return Ok(Err(err)),
+                };
+                self.client().watch(&access_token, filter).await
+            }
+            result => Ok(result),
+        }
     }
 }
@@ -1,9 +1,19 @@
+#[cfg(feature = "server-admin")]
+pub mod admin;
+
 #[cfg(feature = "server-auth")]
 pub mod auth;
 
 #[cfg(feature = "server-meta")]
 pub mod meta;
 
+#[cfg(feature = "server-auth")]
+pub mod session;
+
+#[cfg(feature = "server-auth")]
+pub use session::Session;
+
+use crate::send_request;
 use crate::Error;
 use houseflow_config::client::Config;
 use houseflow_types::token::Token;
@@ -26,6 +36,7 @@ impl Client {
         }
     }
 
+    #[cfg(any(feature = "server-auth", feature = "server-meta"))]
     pub(crate) async fn post<B, E>(
         &self,
         url: Url,
@@ -82,28 +93,19 @@ impl Client {
         let request = self.client.get(url).json(body).bearer_auth(token);
         send_request(request).await
     }
-}
 
-pub(crate) async fn send_request<B: DeserializeOwned, E: DeserializeOwned>(
-    request: reqwest::RequestBuilder,
-) -> Result<Result<B, E>, Error> {
-    let response = request.send().await?;
-    let status_code = response.status();
-    let bytes = response.bytes().await?;
-    let result = if status_code.is_success() {
-        let parsed = serde_json::from_slice(&bytes).map_err(|err| Error::InvalidResponseBody {
-            error: Box::new(err),
-            status_code,
-            body: String::from_utf8(bytes.to_vec()).unwrap(),
-        })?;
-        Ok(parsed)
-    } else {
-        let parsed = serde_json::from_slice(&bytes).map_err(|err| Error::InvalidResponseBody {
-            error: Box::new(err),
-            status_code,
-            body: String::from_utf8(bytes.to_vec()).unwrap(),
-        })?;
-        Err(parsed)
-    };
-    Ok(result)
+    #[allow(dead_code)]
+    pub(crate) async fn delete_with_token<TC, B, E>(
+        &self,
+        url: Url,
+        token: &Token<TC>,
+    ) -> Result<Result<B, E>, Error>
+    where
+        TC: TokenClaims,
+        B: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let request = self.client.delete(url).bearer_auth(token);
+        send_request(request).await
+    }
 }
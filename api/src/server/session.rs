@@ -0,0 +1,422 @@
+use super::Client;
+use crate::send_request;
+use crate::Error;
+use houseflow_types::errors::AuthError;
+use houseflow_types::errors::ServerError;
+use houseflow_types::token::AccessToken;
+use houseflow_types::token::RefreshToken;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use url::Url;
+
+/// Access token is refreshed when it's about to expire in less than that
+const REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// Called with the tokens after they're refreshed, so they can be persisted
+pub type OnRefresh = Arc<dyn Fn(&AccessToken, &RefreshToken) + Send + Sync>;
+
+/// How idempotent requests are retried after a connection error or `TooManyRequests`
+#[derive(Debug, Clone)]
+pub struct Retry {
+    /// Number of retries, 0 disables retrying
+    pub attempts: u32,
+    /// Delay before the first retry, doubled before each next one
+    pub delay: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            delay: Duration::from_millis(250),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Tokens {
+    access: AccessToken,
+    refresh: RefreshToken,
+}
+
+/// Authenticated client which keeps the access token valid.
+///
+/// The access token is refreshed before it expires, or when the server rejects it.
+#[derive(Clone)]
+pub struct Session {
+    client: Client,
+    tokens: Arc<Mutex<Tokens>>,
+    on_refresh: Option<OnRefresh>,
+    retry: Retry,
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Session")
+            .field("client", &self.client)
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Session {
+    pub fn new(client: Client, access_token: AccessToken, refresh_token: RefreshToken) -> Self {
+        Self {
+            client,
+            tokens: Arc::new(Mutex::new(Tokens {
+                access: access_token,
+                refresh: refresh_token,
+            })),
+            on_refresh: None,
+            retry: Retry::default(),
+        }
+    }
+
+    pub fn on_refresh(
+        mut self,
+        on_refresh: impl Fn(&AccessToken, &RefreshToken) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_refresh = Some(Arc::new(on_refresh));
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Returns the access token, refreshed if it's about to expire
+    pub async fn access_token(&self) -> Result<Result<AccessToken, ServerError>, Error> {
+        let mut tokens = self.tokens.lock().await;
+        let expires_in = (tokens.access.exp - chrono::Utc::now()).to_std();
+        if matches!(expires_in, Ok(expires_in) if expires_in > REFRESH_MARGIN) {
+            return Ok(Ok(tokens.access.clone()));
+        }
+        tracing::debug!("access token is about to expire, refreshing");
+        self.refresh_locked(&mut tokens).await
+    }
+
+    /// Refreshes the access token even if it's still valid
+    pub async fn refresh(&self) -> Result<Result<AccessToken, ServerError>, Error> {
+        let mut tokens = self.tokens.lock().await;
+        self.refresh_locked(&mut tokens).await
+    }
+
+    /// Refreshes the access token, unless it was already replaced since it has been `rejected`
+    pub(crate) async fn refresh_rejected(
+        &self,
+        rejected: &AccessToken,
+    ) -> Result<Result<AccessToken, ServerError>, Error> {
+        let mut tokens = self.tokens.lock().await;
+        if tokens.access != *rejected {
+            return Ok(Ok(tokens.access.clone()));
+        }
+        tracing::debug!("access token was rejected, refreshing");
+        self.refresh_locked(&mut tokens).await
+    }
+
+    async fn refresh_locked(
+        &self,
+        tokens: &mut Tokens,
+    ) -> Result<Result<AccessToken, ServerError>, Error> {
+        let response = match self.client.refresh_token(&tokens.refresh).await? {
+            Ok(response) => response,
+            Err(err) => return Ok(Err(err)),
+        };
+        tokens.access = AccessToken::decode_insecure_novalidate(&response.access_token)?;
+        if let Some(refresh_token) = response.refresh_token {
+            tokens.refresh = RefreshToken::decode_insecure_novalidate(&refresh_token)?;
+        }
+        if let Some(on_refresh) = &self.on_refresh {
+            on_refresh(&tokens.access, &tokens.refresh);
+        }
+        Ok(Ok(tokens.access.clone()))
+    }
+
+    /// Sends the request with the access token.
+    ///
+    /// If the server rejects the token, it's refreshed and the request is sent once again.
+    /// Idempotent requests are also retried with an exponential backoff.
+    pub(crate) async fn send<B: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&(impl Serialize + ?Sized)>,
+    ) -> Result<Result<B, ServerError>, Error> {
+        let mut refreshed = false;
+        let mut retries = 0;
+        let mut delay = self.retry.delay;
+        let mut access_token = match self.access_token().await? {
+            Ok(access_token) => access_token,
+            Err(err) => return Ok(Err(err)),
+        };
+        loop {
+            let mut request = self
+                .client
+                .client
+                .request(method.clone(), url.clone())
+                .bearer_auth(&access_token);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let response = send_request(request).await;
+            let retryable = match &response {
+                Ok(Err(ServerError::AuthError(AuthError::InvalidToken(_)))) if !refreshed => {
+                    refreshed = true;
+                    access_token = match self.refresh_rejected(&access_token).await? {
+                        Ok(access_token) => access_token,
+                        Err(err) => return Ok(Err(err)),
+                    };
+                    continue;
+                }
+                Ok(Err(ServerError::TooManyRequests)) => true,
+                Err(Error::ReqwestError(err)) => err.is_connect() || err.is_timeout(),
+                _ => false,
+            };
+            if !retryable || !method.is_idempotent() || retries >= self.retry.attempts {
+                return response;
+            }
+            tracing::debug!(%method, %url, retries, "retrying request in {:?}", delay);
+            tokio::time::sleep(delay).await;
+            retries += 1;
+            delay *= 2;
+        }
+    }
+
+    pub(crate) async fn get<B: DeserializeOwned>(
+        &self,
+        url: Url,
+        body: &(impl Serialize + ?Sized),
+    ) -> Result<Result<B, ServerError>, Error> {
+        self.send(Method::GET, url, Some(body)).await
+    }
+
+    #[cfg(any(feature = "server-admin", feature = "server-meta"))]
+    pub(crate) async fn post<B: DeserializeOwned>(
+        &self,
+        url: Url,
+        body: &(impl Serialize + ?Sized),
+    ) -> Result<Result<B, ServerError>, Error> {
+        self.send(Method::POST, url, Some(body)).await
+    }
+
+    #[cfg(feature = "server-admin")]
+    pub(crate) async fn delete<B: DeserializeOwned>(
+        &self,
+        url: Url,
+    ) -> Result<Result<B, ServerError>, Error> {
+        self.send(Method::DELETE, url, None::<&()>).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Extension;
+    use axum::http::HeaderMap;
+    use axum::routing::any;
+    use axum::routing::post;
+    use axum::Json;
+    use houseflow_config::client::Config;
+    use houseflow_types::auth;
+    use houseflow_types::errors::TokenError;
+    use houseflow_types::token::AccessTokenClaims;
+    use houseflow_types::token::RefreshTokenClaims;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::time::Instant;
+
+    /// Server which accepts only the last access token it has issued
+    #[derive(Default)]
+    struct Mock {
+        valid: std::sync::Mutex<Option<String>>,
+        refreshes: AtomicUsize,
+        /// When the requests to `/busy` were received
+        busy: std::sync::Mutex<Vec<Instant>>,
+    }
+
+    fn access_token(expires_in: chrono::Duration) -> AccessToken {
+        // every token is unique, as the expiration is in seconds
+        static ISSUED: AtomicUsize = AtomicUsize::new(0);
+        let issued = ISSUED.fetch_add(1, Ordering::SeqCst) as i64;
+        let claims = AccessTokenClaims {
+            sub: Default::default(),
+            exp: chrono::Utc::now() + expires_in + chrono::Duration::seconds(issued),
+        };
+        AccessToken::new(b"key", claims).unwrap()
+    }
+
+    fn refresh_token() -> RefreshToken {
+        let claims = RefreshTokenClaims {
+            sub: Default::default(),
+            exp: None,
+        };
+        RefreshToken::new(b"key", claims).unwrap()
+    }
+
+    async fn refresh(Extension(mock): Extension<Arc<Mock>>) -> Json<auth::token::Response> {
+        mock.refreshes.fetch_add(1, Ordering::SeqCst);
+        let access_token = access_token(chrono::Duration::hours(1)).encode();
+        *mock.valid.lock().unwrap() = Some(access_token.clone());
+        Json(auth::token::Response {
+            refresh_token: None,
+            access_token,
+        })
+    }
+
+    fn invalid_token() -> ServerError {
+        ServerError::AuthError(AuthError::InvalidToken(TokenError {
+            description: String::from("invalid token"),
+        }))
+    }
+
+    async fn protected(
+        Extension(mock): Extension<Arc<Mock>>,
+        headers: HeaderMap,
+    ) -> Result<Json<()>, ServerError> {
+        let token = headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.strip_prefix("Bearer "));
+        match (token, mock.valid.lock().unwrap().as_deref()) {
+            (Some(token), Some(valid)) if token == valid => Ok(Json(())),
+            _ => Err(invalid_token()),
+        }
+    }
+
+    async fn rejecting() -> Result<Json<()>, ServerError> {
+        Err(invalid_token())
+    }
+
+    async fn busy(Extension(mock): Extension<Arc<Mock>>) -> Result<Json<()>, ServerError> {
+        mock.busy.lock().unwrap().push(Instant::now());
+        Err(ServerError::TooManyRequests)
+    }
+
+    struct Setup {
+        mock: Arc<Mock>,
+        url: Url,
+        refreshed: Arc<std::sync::Mutex<Vec<AccessToken>>>,
+        session: Session,
+    }
+
+    async fn setup(access_token: AccessToken) -> Setup {
+        let mock = Arc::new(Mock::default());
+        let app = axum::Router::new()
+            .route("/auth/refresh", post(refresh))
+            .route("/protected", any(protected))
+            .route("/rejecting", any(rejecting))
+            .route("/busy", any(busy))
+            .layer(Extension(mock.clone()));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let client = Client::new(Config {
+            server: houseflow_config::client::Server { url: url.clone() },
+            ..Default::default()
+        });
+        let refreshed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let session = Session::new(client, access_token, refresh_token())
+            .on_refresh({
+                let refreshed = refreshed.clone();
+                move |access_token, _| refreshed.lock().unwrap().push(access_token.clone())
+            })
+            .with_retry(Retry {
+                attempts: 3,
+                delay: Duration::from_millis(20),
+            });
+        Setup {
+            mock,
+            url,
+            refreshed,
+            session,
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_before_expiration() {
+        let setup = setup(access_token(chrono::Duration::seconds(10))).await;
+        let url = setup.url.join("protected").unwrap();
+        setup.session.get::<()>(url, &()).await.unwrap().unwrap();
+        assert_eq!(setup.mock.refreshes.load(Ordering::SeqCst), 1);
+
+        let refreshed = setup.refreshed.lock().unwrap().clone();
+        assert_eq!(refreshed.len(), 1);
+        assert_eq!(
+            Some(refreshed[0].encode()),
+            setup.mock.valid.lock().unwrap().clone()
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_rejected_token() {
+        let setup = setup(access_token(chrono::Duration::hours(1))).await;
+        let url = setup.url.join("protected").unwrap();
+        setup
+            .session
+            .get::<()>(url.clone(), &())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(setup.mock.refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(setup.refreshed.lock().unwrap().len(), 1);
+
+        // the refreshed token is used from now on
+        setup.session.get::<()>(url, &()).await.unwrap().unwrap();
+        assert_eq!(setup.mock.refreshes.load(Ordering::SeqCst), 1);
+
+        // the token is refreshed only once per request
+        let url = setup.url.join("rejecting").unwrap();
+        let response = setup.session.get::<()>(url, &()).await.unwrap();
+        assert_eq!(response, Err(invalid_token()));
+        assert_eq!(setup.mock.refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn retry_with_backoff() {
+        let setup = setup(access_token(chrono::Duration::hours(1))).await;
+        let url = setup.url.join("busy").unwrap();
+        let response = setup.session.get::<()>(url, &()).await.unwrap();
+        assert_eq!(response, Err(ServerError::TooManyRequests));
+
+        let busy = setup.mock.busy.lock().unwrap().clone();
+        assert_eq!(busy.len(), 4);
+        let delays = busy
+            .windows(2)
+            .map(|requests| requests[1] - requests[0])
+            .collect::<Vec<_>>();
+        for (delay, expected) in delays.iter().zip([20, 40, 80]) {
+            assert!(
+                *delay >= Duration::from_millis(expected),
+                "delays: {:?}",
+                delays
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn no_retry_of_non_idempotent() {
+        let setup = setup(access_token(chrono::Duration::hours(1))).await;
+        let url = setup.url.join("busy").unwrap();
+        let response = setup
+            .session
+            .send::<()>(Method::POST, url, Some(&()))
+            .await
+            .unwrap();
+        assert_eq!(response, Err(ServerError::TooManyRequests));
+        assert_eq!(setup.mock.busy.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::admin::RegisterHubRequest;
+use houseflow_types::admin::RegisterHubResponse;
+use houseflow_types::structure;
+use serde::Serialize;
+
+pub struct Register {
+    pub name: String,
+    pub structure_id: structure::ID,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct RegisterOutput(RegisterHubResponse);
+
+impl crate::Output for RegisterOutput {
+    fn text(&self) -> String {
+        format!(
+            "✔ Registered the hub, the password is shown only once\n\
+             Put the credentials in the hub configuration:\n\n\
+             [hub]\nid = \"{}\"\n\n\
+             [controllers.lighthouse]\npassword = \"{}\"",
+            self.0.id, self.0.password
+        )
+    }
+}
+
+#[async_trait]
+impl crate::Command for Register {
+    type Output = RegisterOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<RegisterOutput> {
+        let request = RegisterHubRequest {
+            name: self.name,
+            structure_id: self.structure_id,
+        };
+        let response = ctx.session()?.register_hub(&request).await??;
+        Ok(RegisterOutput(response))
+    }
+}
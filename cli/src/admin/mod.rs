@@ -0,0 +1,4 @@
+pub mod hub;
+pub mod permission;
+pub mod structure;
+pub mod user;
@@ -0,0 +1,76 @@
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::permission::Permission;
+use houseflow_types::structure;
+use houseflow_types::user;
+use serde::Serialize;
+
+pub struct Grant {
+    pub user_id: user::ID,
+    pub structure_id: structure::ID,
+    pub is_manager: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct GrantOutput(Permission);
+
+impl crate::Output for GrantOutput {
+    fn text(&self) -> String {
+        format!(
+            "✔ Granted {} a permission to {}",
+            self.0.user_id, self.0.structure_id
+        )
+    }
+}
+
+#[async_trait]
+impl crate::Command for Grant {
+    type Output = GrantOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<GrantOutput> {
+        let permission = Permission {
+            structure_id: self.structure_id,
+            user_id: self.user_id,
+            is_manager: self.is_manager,
+        };
+        ctx.session()?.grant_permission(&permission).await??;
+        Ok(GrantOutput(permission))
+    }
+}
+
+pub struct Revoke {
+    pub user_id: user::ID,
+    pub structure_id: structure::ID,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RevokeOutput {
+    structure_id: structure::ID,
+    user_id: user::ID,
+}
+
+impl crate::Output for RevokeOutput {
+    fn text(&self) -> String {
+        format!(
+            "✔ Revoked the permission of {} to {}",
+            self.user_id, self.structure_id
+        )
+    }
+}
+
+#[async_trait]
+impl crate::Command for Revoke {
+    type Output = RevokeOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<RevokeOutput> {
+        ctx.session()?
+            .revoke_permission(&self.structure_id, &self.user_id)
+            .await??;
+        Ok(RevokeOutput {
+            structure_id: self.structure_id,
+            user_id: self.user_id,
+        })
+    }
+}
@@ -0,0 +1,32 @@
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::admin::AddStructureRequest;
+use houseflow_types::structure::Structure;
+use serde::Serialize;
+
+pub struct Add {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct AddOutput(Structure);
+
+impl crate::Output for AddOutput {
+    fn text(&self) -> String {
+        format!("✔ Added {} with ID {}", self.0.name, self.0.id)
+    }
+}
+
+#[async_trait]
+impl crate::Command for Add {
+    type Output = AddOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<AddOutput> {
+        let structure = ctx
+            .session()?
+            .add_structure(&AddStructureRequest { name: self.name })
+            .await??;
+        Ok(AddOutput(structure))
+    }
+}
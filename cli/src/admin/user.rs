@@ -0,0 +1,101 @@
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::admin::AddUserRequest;
+use houseflow_types::admin::ListUsersResponse;
+use houseflow_types::admin::RemoveUserResponse;
+use houseflow_types::user;
+use houseflow_types::user::User;
+use serde::Serialize;
+
+pub struct Add {
+    pub username: String,
+    pub email: lettre::Address,
+    pub admin: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct AddOutput(User);
+
+impl crate::Output for AddOutput {
+    fn text(&self) -> String {
+        format!("✔ Added {} with ID {}", self.0.username, self.0.id)
+    }
+}
+
+#[async_trait]
+impl crate::Command for Add {
+    type Output = AddOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<AddOutput> {
+        let request = AddUserRequest {
+            username: self.username,
+            email: self.email,
+            admin: self.admin,
+        };
+        let user = ctx.session()?.add_user(&request).await??;
+        Ok(AddOutput(user))
+    }
+}
+
+pub struct Remove {
+    pub user_id: user::ID,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct RemoveOutput(RemoveUserResponse);
+
+impl crate::Output for RemoveOutput {
+    fn text(&self) -> String {
+        format!(
+            "✔ Removed {} and {} of their permissions",
+            self.0.user_id, self.0.removed_permissions
+        )
+    }
+}
+
+#[async_trait]
+impl crate::Command for Remove {
+    type Output = RemoveOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<RemoveOutput> {
+        let response = ctx.session()?.remove_user(&self.user_id).await??;
+        Ok(RemoveOutput(response))
+    }
+}
+
+pub struct List {}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct ListOutput(ListUsersResponse);
+
+impl crate::Output for ListOutput {
+    fn text(&self) -> String {
+        let mut lines = vec![format!(
+            "{:<36}  {:<20}  {:<30}  ADMIN",
+            "ID", "USERNAME", "EMAIL"
+        )];
+        for user in &self.0.users {
+            lines.push(format!(
+                "{:<36}  {:<20}  {:<30}  {}",
+                user.id,
+                user.username,
+                user.email.to_string(),
+                if user.admin { "yes" } else { "no" }
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[async_trait]
+impl crate::Command for List {
+    type Output = ListOutput;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<ListOutput> {
+        let response = ctx.session()?.list_users().await??;
+        Ok(ListOutput(response))
+    }
+}
@@ -2,18 +2,47 @@ use crate::CommandContext;
 use crate::Tokens;
 use async_trait::async_trait;
 use houseflow_types::code::VerificationCode;
+use serde::Serialize;
 
 pub struct Command {
     pub email: lettre::Address,
     pub code: Option<VerificationCode>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Output {
+    LoggedIn {
+        email: lettre::Address,
+        profile: String,
+    },
+    VerificationCodeSent {
+        email: lettre::Address,
+    },
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        match self {
+            Self::LoggedIn { email, profile } => {
+                format!("✔ Logged in as {} to the {} profile", email, profile)
+            }
+            Self::VerificationCodeSent { email } => format!(
+                "✔ Verification code sent to {}. Please copy the code and re-run the command with --code <code-from-email>",
+                email
+            ),
+        }
+    }
+}
+
 #[async_trait]
 impl crate::Command for Command {
-    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<()> {
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
         use houseflow_types::auth::login;
 
-        match self.code {
+        let output = match self.code {
             Some(code) => {
                 let request = login::Request {
                     email: self.email,
@@ -25,16 +54,20 @@ impl crate::Command for Command {
                         access_token,
                         refresh_token,
                     } => {
-                        tracing::info!("✔ Logged in as {}", request.email);
                         let tokens = Tokens {
                             refresh: refresh_token,
                             access: access_token,
                         };
-                        ctx.tokens.save(&tokens)?;
-                        tracing::debug!("Saved refresh token at {:#?}", ctx.tokens.path);
+                        let store = ctx.tokens()?;
+                        store.save(&tokens)?;
+                        tracing::debug!("Saved refresh token at {}", store.location());
+                        Output::LoggedIn {
+                            email: request.email,
+                            profile: ctx.profile,
+                        }
                     }
                     _ => panic!("Expected Response::LoggedIn"),
-                };
+                }
             }
             None => {
                 let request = login::Request {
@@ -43,17 +76,14 @@ impl crate::Command for Command {
                 };
                 let response = ctx.server_client()?.login(&request).await??;
                 match response {
-                    login::Response::VerificationCodeSent => {
-                        tracing::info!(
-                            "✔ Verification code sent to {}. Please copy the code and re-run the command with --code <code-from-email>",
-                            request.email
-                        );
-                    }
+                    login::Response::VerificationCodeSent => Output::VerificationCodeSent {
+                        email: request.email,
+                    },
                     _ => panic!("Expected Response::VerificationCodeSent"),
-                };
+                }
             }
         };
 
-        Ok(())
+        Ok(output)
     }
 }
@@ -1,14 +1,34 @@
 use crate::CommandContext;
 use async_trait::async_trait;
+use serde::Serialize;
 
 pub struct Command {}
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    profile: String,
+    /// Where the removed tokens were kept
+    keystore: String,
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        format!("✔ Succesfully logged out of the {} profile", self.profile)
+    }
+}
+
 #[async_trait]
 impl crate::Command for Command {
-    async fn run(self, ctx: CommandContext) -> anyhow::Result<()> {
-        ctx.tokens.remove()?;
-        tracing::info!("✔ Succesfully logged out");
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        let store = ctx.tokens()?;
+        store.remove()?;
 
-        Ok(())
+        Ok(Output {
+            profile: ctx.profile,
+            keystore: store.location(),
+        })
     }
 }
@@ -1,22 +1,34 @@
 use crate::CommandContext;
-use crate::Tokens;
 use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Serialize;
 
 pub struct Command {}
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    /// Expiration of the new access token
+    expires_at: DateTime<Utc>,
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        String::from("✔ Succesfully refreshed token and saved to keystore")
+    }
+}
+
 #[async_trait]
 impl crate::Command for Command {
-    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<()> {
-        let tokens = ctx.tokens.get()?;
-        let refresh_token = ctx.refresh_token()?;
-        let response = ctx.server_client()?.refresh_token(&refresh_token).await??;
-        let tokens = Tokens {
-            refresh: tokens.refresh,
-            access: response.access_token,
-        };
-        ctx.tokens.save(&tokens)?;
-        tracing::info!("✔ Succesfully refreshed token and saved to keystore");
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        // the session saves the new tokens to the store
+        let access_token = ctx.session()?.refresh().await??;
 
-        Ok(())
+        Ok(Output {
+            expires_at: access_token.exp,
+        })
     }
 }
@@ -1,27 +1,28 @@
 use crate::CommandContext;
+use anyhow::Context;
 use async_trait::async_trait;
 use chrono::DateTime;
 use chrono::Utc;
 use houseflow_types::token::AccessToken;
 use houseflow_types::token::RefreshToken;
+use serde::Serialize;
 
 pub struct Command {
     pub show_token: bool,
 }
 
-#[async_trait]
-impl crate::Command for Command {
-    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<()> {
-        let access_token = ctx.access_token().await?;
-
-        let response = ctx.server_client()?.whoami(&access_token).await??;
-        let tokens = ctx.tokens.get()?;
-        let (access_token, refresh_token) = (
-            AccessToken::decode_insecure_novalidate(&tokens.access)?,
-            RefreshToken::decode_insecure_novalidate(&tokens.refresh)?,
-        );
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Token {
+    /// Censored unless `--show-token` is passed
+    token: String,
+    /// `None` if the token never expires
+    expires_at: Option<DateTime<Utc>>,
+}
 
-        let get_token_expiration = |exp_at: Option<&DateTime<Utc>>| match exp_at {
+impl Token {
+    fn expiration(&self) -> String {
+        match self.expires_at {
             Some(exp_at) => {
                 use std::cmp::Ordering;
 
@@ -36,31 +37,76 @@ impl crate::Command for Command {
                 }
             }
             None => "never".to_string(),
-        };
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    profile: String,
+    username: String,
+    email: lettre::Address,
+    /// Where the tokens are kept
+    keystore: String,
+    access_token: Token,
+    refresh_token: Token,
+}
 
-        let (access_token_expiration, refresh_token_expiration) = (
-            get_token_expiration(Some(&access_token.exp)),
-            get_token_expiration(refresh_token.exp.as_ref()),
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        [
+            format!("✔ Logged in to the {} profile", self.profile),
+            format!("  Username: {}", self.username),
+            format!("  Email: {}", self.email),
+            format!("  Keystore: {}", self.keystore),
+            format!(
+                "  Access token({}): {}",
+                self.access_token.expiration(),
+                self.access_token.token
+            ),
+            format!(
+                "  Refresh token({}): {}",
+                self.refresh_token.expiration(),
+                self.refresh_token.token
+            ),
+        ]
+        .join("\n")
+    }
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        let response = ctx.session()?.whoami().await??;
+        let store = ctx.tokens()?;
+        let tokens = store.get()?.context("tokens were removed")?;
+        let (access_token, refresh_token) = (
+            AccessToken::decode_insecure_novalidate(&tokens.access)?,
+            RefreshToken::decode_insecure_novalidate(&tokens.refresh)?,
         );
+
         let censored = "*".repeat(32);
-        let (access_token, refresh_token) = match self.show_token {
-            true => (&tokens.access, &tokens.refresh),
-            false => (&censored, &censored),
+        let (raw_access_token, raw_refresh_token) = match self.show_token {
+            true => (tokens.access, tokens.refresh),
+            false => (censored.clone(), censored),
         };
 
-        println!("✔ Logged in");
-        println!("  Username: {}", response.username);
-        println!("  Email: {}", response.email);
-        println!("  Keystore: {:#?}", ctx.tokens.path);
-        println!(
-            "  Access token({}): {}",
-            access_token_expiration, access_token
-        );
-        println!(
-            "  Refresh token({}): {}",
-            refresh_token_expiration, refresh_token
-        );
-
-        Ok(())
+        Ok(Output {
+            profile: ctx.profile,
+            username: response.username,
+            email: response.email,
+            keystore: store.location(),
+            access_token: Token {
+                token: raw_access_token,
+                expires_at: Some(access_token.exp),
+            },
+            refresh_token: Token {
+                token: raw_refresh_token,
+                expires_at: refresh_token.exp,
+            },
+        })
     }
 }
@@ -0,0 +1,133 @@
+use clap::Arg;
+use clap::Command;
+use houseflow_types::structure;
+use houseflow_types::user;
+use std::str::FromStr;
+
+fn user_id() -> Arg<'static> {
+    Arg::new("user-id")
+        .help("ID of the user")
+        .long("user")
+        .validator(|s| match user::ID::from_str(s) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        })
+        .takes_value(true)
+}
+
+fn structure_id() -> Arg<'static> {
+    Arg::new("structure-id")
+        .help("ID of the structure")
+        .long("structure")
+        .validator(|s| match structure::ID::from_str(s) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        })
+        .takes_value(true)
+}
+
+fn user() -> Command<'static> {
+    Command::new("user")
+        .about("Manage users")
+        .subcommand(
+            Command::new("add")
+                .about("Add a user")
+                .arg(
+                    Arg::new("username")
+                        .help("Name of the user")
+                        .long("username")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("email")
+                        .help("Email used by the user to log in")
+                        .long("email")
+                        .validator(|s| match lettre::Address::from_str(s) {
+                            Ok(_) => Ok(()),
+                            Err(err) => Err(err.to_string()),
+                        })
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("admin")
+                        .help("Allow the user to use the admin commands")
+                        .long("admin"),
+                ),
+        )
+        .subcommand(
+            Command::new("remove")
+                .about("Remove a user together with their permissions")
+                .arg(user_id()),
+        )
+        .subcommand(Command::new("list").about("List all users"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+}
+
+fn structure() -> Command<'static> {
+    Command::new("structure")
+        .about("Manage structures")
+        .subcommand(
+            Command::new("add").about("Add a structure").arg(
+                Arg::new("name")
+                    .help("Name of the structure")
+                    .long("name")
+                    .takes_value(true),
+            ),
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+}
+
+fn permission() -> Command<'static> {
+    Command::new("permission")
+        .about("Manage permissions of the users to the structures")
+        .subcommand(
+            Command::new("grant")
+                .about("Grant the user a permission to the structure")
+                .arg(user_id())
+                .arg(structure_id())
+                .arg(
+                    Arg::new("manager")
+                        .help("Allow the user to manage the structure")
+                        .long("manager"),
+                ),
+        )
+        .subcommand(
+            Command::new("revoke")
+                .about("Revoke the permission of the user to the structure")
+                .arg(user_id())
+                .arg(structure_id()),
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+}
+
+fn hub() -> Command<'static> {
+    Command::new("hub")
+        .about("Manage lighthouse hubs")
+        .subcommand(
+            Command::new("register")
+                .about("Register a hub, prints the generated credentials")
+                .arg(
+                    Arg::new("name")
+                        .help("Name of the hub")
+                        .long("name")
+                        .takes_value(true),
+                )
+                .arg(structure_id()),
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+}
+
+pub(super) fn subcommand() -> Command<'static> {
+    Command::new("admin")
+        .about("Manage users, structures, permissions and hubs of the server, requires an admin account")
+        .subcommand(user())
+        .subcommand(structure())
+        .subcommand(permission())
+        .subcommand(hub())
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+}
@@ -0,0 +1,47 @@
+use clap::Arg;
+use clap::Command;
+
+fn kind() -> Arg<'static> {
+    Arg::new("kind")
+        .help("Component that the configuration is for")
+        .possible_values(["server", "hub", "accessory", "client"])
+        .required(true)
+}
+
+fn path() -> Arg<'static> {
+    Arg::new("path")
+        .help("Path to the configuration file, defaults to the standard location of the component")
+        .long("path")
+        .takes_value(true)
+}
+
+pub(super) fn subcommand() -> Command<'static> {
+    Command::new("config")
+        .about("Create and check configuration files")
+        .subcommand(
+            Command::new("init")
+                .about("Generate a new configuration file")
+                .arg(kind())
+                .arg(path())
+                .arg(
+                    Arg::new("force")
+                        .help("Overwrite the configuration file if it already exists")
+                        .long("force"),
+                ),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Report all problems of the configuration file")
+                .arg(kind())
+                .arg(path())
+                .arg(
+                    Arg::new("features")
+                        .help("Cargo features that the hub is compiled with, e.g controllers-hap,providers-hive")
+                        .long("features")
+                        .use_value_delimiter(true)
+                        .multiple_values(true)
+                        .takes_value(true),
+                ),
+        )
+        .subcommand_required(true)
+}
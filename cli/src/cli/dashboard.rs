@@ -0,0 +1,18 @@
+use clap::Arg;
+use clap::Command;
+
+pub(super) fn subcommand() -> Command<'static> {
+    Command::new("dashboard")
+        .about("Show the accessories with their live values in a full-screen dashboard")
+        .arg(
+            Arg::new("history")
+                .help("Number of the sensor readings kept for the sparklines")
+                .long("history")
+                .validator(|s| {
+                    s.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .default_value("120"),
+        )
+}
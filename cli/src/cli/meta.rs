@@ -5,60 +5,97 @@ use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
 use std::str::FromStr;
 
+fn accessory_id() -> Arg<'static> {
+    Arg::new("accessory-id")
+        .help("ID of the accessory")
+        .long("accessory")
+        .validator(|s| match accessory::ID::from_str(s) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        })
+        .takes_value(true)
+}
+
+fn characteristic_name() -> Arg<'static> {
+    Arg::new("characteristic-name")
+        .help("Name of the characteristic")
+        .long("characteristic")
+        .validator(|s| {
+            match CharacteristicName::VARIANTS
+                .iter()
+                .find(|v| v.to_string() == s)
+            {
+                Some(_) => Ok(()),
+                None => Err(format!(
+                    "variant {} not found. Available variants: [{}]",
+                    s,
+                    CharacteristicName::VARIANTS.join(",")
+                )),
+            }
+        })
+        .takes_value(true)
+}
+
+fn service_name() -> Arg<'static> {
+    Arg::new("service-name")
+        .help("Name of the service")
+        .long("service")
+        .validator(
+            |s| match ServiceName::VARIANTS.iter().find(|v| v.to_string() == s) {
+                Some(_) => Ok(()),
+                None => Err(format!(
+                    "variant {} not found. Available variants: [{}]",
+                    s,
+                    ServiceName::VARIANTS.join(",")
+                )),
+            },
+        )
+        .takes_value(true)
+}
+
 fn read() -> Command<'static> {
     Command::new("read")
         .about("Read characteristic of the accessory")
+        .arg(accessory_id())
+        .arg(characteristic_name())
+        .arg(service_name())
+}
+
+fn write() -> Command<'static> {
+    Command::new("write")
+        .about("Write characteristic of the accessory")
+        .arg(accessory_id())
+        .arg(characteristic_name())
+        .arg(service_name())
         .arg(
-            Arg::new("accessory-id")
-                .help("ID of the accessory")
-                .long("accessory")
-                .validator(|s| match accessory::ID::from_str(s) {
-                    Ok(_) => Ok(()),
-                    Err(err) => Err(err.to_string()),
-                })
-                .takes_value(true),
-        )
-        .arg(
-            Arg::new("characteristic-name")
-                .help("Name of the characteristic to read")
-                .long("characteristic")
-                .validator(|s| {
-                    match CharacteristicName::VARIANTS
-                        .iter()
-                        .find(|v| v.to_string() == s)
-                    {
-                        Some(_) => Ok(()),
-                        None => Err(format!(
-                            "variant {} not found. Available variants: [{}]",
-                            s,
-                            CharacteristicName::VARIANTS.join(",")
-                        )),
-                    }
-                })
-                .takes_value(true),
-        )
-        .arg(
-            Arg::new("service-name")
-                .help("Name of the service to read")
-                .long("service")
-                .validator(
-                    |s| match ServiceName::VARIANTS.iter().find(|v| v.to_string() == s) {
-                        Some(_) => Ok(()),
-                        None => Err(format!(
-                            "variant {} not found. Available variants: [{}]",
-                            s,
-                            ServiceName::VARIANTS.join(",")
-                        )),
-                    },
-                )
-                .takes_value(true),
+            Arg::new("values")
+                .help("Values of the characteristic, e.g on=true or open-percent=50")
+                .required(true)
+                .multiple_values(true)
+                .validator(|s| match s.split_once('=') {
+                    Some(_) => Ok(()),
+                    None => Err(format!("expected `field=value`, got `{}`", s)),
+                }),
         )
 }
 
+fn list() -> Command<'static> {
+    Command::new("list").about("List connected accessories and their services")
+}
+
+fn identify() -> Command<'static> {
+    Command::new("identify")
+        .about("Ask the accessory to identify itself, e.g by blinking an LED")
+        .arg(accessory_id())
+}
+
 pub(super) fn subcommand() -> Command<'static> {
     Command::new("meta")
         .about("Read or write characteristic of the accessory")
         .subcommand(read())
+        .subcommand(write())
+        .subcommand(list())
+        .subcommand(identify())
         .subcommand_required(true)
         .arg_required_else_help(true)
 }
@@ -1,9 +1,18 @@
+mod admin;
 mod auth;
 mod completions;
+mod config;
+mod dashboard;
 mod meta;
+mod watch;
 
 use clap::Arg;
 use clap::Command;
+use houseflow_config::client::DEFAULT_PROFILE;
+use strum::VariantNames;
+
+use crate::context::Target;
+use crate::output::Format;
 
 pub(crate) fn dialoguer_theme() -> impl dialoguer::theme::Theme {
     dialoguer::theme::ColorfulTheme {
@@ -23,8 +32,38 @@ pub fn app(default_config_path: &'static std::ffi::OsStr) -> Command<'_> {
                 .help("Configuration path")
                 .default_value_os(default_config_path),
         )
+        .arg(
+            Arg::new("profile")
+                .help("Profile with its own server and account, as defined in the `profiles` of the configuration")
+                .short('p')
+                .long("profile")
+                .default_value(DEFAULT_PROFILE)
+                .global(true),
+        )
+        .arg(
+            Arg::new("output")
+                .help("Format of the results and errors")
+                .short('o')
+                .long("output")
+                .possible_values(Format::VARIANTS)
+                .default_value("text")
+                .global(true),
+        )
+        .arg(
+            Arg::new("target")
+                .help("Where the commands are sent, `hub` controls the accessories over LAN without the server")
+                .short('t')
+                .long("target")
+                .possible_values(Target::VARIANTS)
+                .default_value("server")
+                .global(true),
+        )
         .subcommand(auth::subcommand())
+        .subcommand(admin::subcommand())
         .subcommand(meta::subcommand())
+        .subcommand(watch::subcommand())
+        .subcommand(dashboard::subcommand())
+        .subcommand(config::subcommand())
         .subcommand(completions::subcommand())
         .subcommand_required(true)
         .arg_required_else_help(true)
@@ -0,0 +1,40 @@
+use clap::Arg;
+use clap::Command;
+use houseflow_types::accessory;
+use houseflow_types::accessory::services::ServiceName;
+use std::str::FromStr;
+
+pub(super) fn subcommand() -> Command<'static> {
+    Command::new("watch")
+        .about("Print events of the accessories as they happen")
+        .arg(
+            Arg::new("accessory-id")
+                .help("Show only events of the accessory")
+                .long("accessory")
+                .validator(|s| match accessory::ID::from_str(s) {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(err.to_string()),
+                })
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("room-name")
+                .help("Show only events of the accessories in the room")
+                .long("room")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("service-name")
+                .help("Show only updates of the service")
+                .long("service")
+                .possible_values(ServiceName::VARIANTS)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("format")
+                .help("Format of the events, JSON prints one event per line. Follows `--output` if not set")
+                .long("format")
+                .possible_values(["json", "yaml", "table"])
+                .takes_value(true),
+        )
+}
@@ -0,0 +1,239 @@
+use super::Kind;
+use crate::cli::dialoguer_theme;
+use crate::CommandContext;
+use anyhow::Context;
+use async_trait::async_trait;
+use dialoguer::Confirm;
+use dialoguer::Input;
+use houseflow_config::defaults;
+use houseflow_config::Config;
+use houseflow_types::accessory;
+use houseflow_types::hub;
+use houseflow_types::permission::Permission;
+use houseflow_types::structure;
+use houseflow_types::structure::Structure;
+use houseflow_types::user;
+use houseflow_types::user::User;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use url::Url;
+
+/// Length of the generated accessory passwords
+const PASSWORD_LENGTH: usize = 32;
+
+pub struct Command {
+    pub kind: Kind,
+    pub path: Option<PathBuf>,
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    kind: Kind,
+    path: PathBuf,
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        format!(
+            "✔ Written {} configuration to {}",
+            self.kind,
+            self.path.display()
+        )
+    }
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = Output;
+
+    async fn run(self, _ctx: CommandContext) -> anyhow::Result<Output> {
+        let path = self.path.unwrap_or_else(|| self.kind.default_path());
+        if path.exists() && !self.force {
+            return Err(anyhow::anyhow!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            ));
+        }
+        match self.kind {
+            Kind::Server => write(&server()?, &path),
+            Kind::Hub => write(&hub()?, &path),
+            Kind::Accessory => write(&accessory()?, &path),
+            Kind::Client => write(&client()?, &path),
+        }?;
+        Ok(Output {
+            kind: self.kind,
+            path,
+        })
+    }
+}
+
+fn write(config: &impl Config, path: &Path) -> anyhow::Result<()> {
+    config.validate().map_err(anyhow::Error::msg)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    config
+        .write(path)
+        .with_context(|| format!("write {}", path.display()))
+}
+
+fn input<T>(prompt: &str, default: T) -> anyhow::Result<T>
+where
+    T: Clone + Display + FromStr,
+    T::Err: Display + Debug,
+{
+    Ok(Input::with_theme(&dialoguer_theme())
+        .with_prompt(prompt)
+        .default(default)
+        .interact_text()?)
+}
+
+/// Asks for a value that can be skipped by leaving the answer empty.
+fn optional_input<T>(prompt: &str) -> anyhow::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    let answer: String = Input::with_theme(&dialoguer_theme())
+        .with_prompt(format!("{} (leave empty to skip)", prompt))
+        .allow_empty(true)
+        .interact_text()?;
+    if answer.is_empty() {
+        return Ok(None);
+    }
+    T::from_str(&answer)
+        .map(Some)
+        .map_err(Into::into)
+        .with_context(|| prompt.to_string())
+}
+
+fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    Ok(Confirm::with_theme(&dialoguer_theme())
+        .with_prompt(prompt)
+        .default(true)
+        .interact()?)
+}
+
+fn server() -> anyhow::Result<houseflow_config::server::Config> {
+    use houseflow_config::server::*;
+
+    let network = Network {
+        address: input("Listen address", defaults::listen_address())?,
+        port: input("Listen port", defaults::server_port())?,
+        base_url: optional_input("Public URL of the server")?,
+    };
+    let mailers = match optional_input::<Url>("SMTP URL")? {
+        Some(url) => Mailers {
+            smtp: Some(mailers::Smtp {
+                url,
+                from: crate::get_input("Sender address of the emails"),
+            }),
+            dummy: None,
+        },
+        None => Mailers {
+            smtp: None,
+            dummy: Some(mailers::Dummy {}),
+        },
+    };
+    let user = User {
+        id: user::ID::new_v4(),
+        username: input("Username of the admin", String::from("admin"))?,
+        email: lettre::Address::from_str(&crate::get_input("Email of the admin"))?,
+        admin: true,
+        preferences: Default::default(),
+    };
+    let structure = Structure {
+        id: structure::ID::new_v4(),
+        name: input("Name of the structure", String::from("Home"))?,
+    };
+    let permission = Permission {
+        structure_id: structure.id,
+        user_id: user.id,
+        is_manager: true,
+    };
+    Ok(Config {
+        network,
+        secrets: rand::random(),
+        tls: None,
+        mailers,
+        controllers: Controllers {
+            meta: confirm("Enable the meta API")?.then(Default::default),
+            google: confirm("Enable Google Home fulfillment")?.then(Default::default),
+        },
+        providers: Providers {
+            lighthouse: confirm("Accept connections from hubs")?.then(Default::default),
+        },
+        logins: Default::default(),
+        structures: vec![structure],
+        users: vec![user],
+        permissions: vec![permission],
+    })
+}
+
+fn hub() -> anyhow::Result<houseflow_config::hub::Config> {
+    use houseflow_config::hub::*;
+
+    let id = optional_input("ID of the hub, as registered on the server")?
+        .unwrap_or_else(hub::ID::new_v4);
+    let lighthouse =
+        optional_input::<Url>("URL of the server")?.map(|url| controllers::Lighthouse {
+            url,
+            password: crate::get_input("Password of the hub"),
+            encoding: Default::default(),
+        });
+    Ok(Config {
+        hub: Hub { id },
+        network: Default::default(),
+        accessories: vec![],
+        providers: Providers {
+            hive: confirm("Accept connections from accessories")?.then_some(HiveProvider {}),
+            mijia: None,
+        },
+        controllers: Controllers {
+            hap: None,
+            meta: confirm("Enable the meta API")?.then_some(controllers::Meta {}),
+            lighthouse,
+        },
+    })
+}
+
+fn accessory() -> anyhow::Result<houseflow_config::accessory::Config> {
+    use houseflow_config::accessory::*;
+
+    let password: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(PASSWORD_LENGTH)
+        .map(char::from)
+        .collect();
+    Ok(Config {
+        hub: Hub {
+            url: input("URL of the hub", defaults::hub_websocket_url())?,
+            encoding: Default::default(),
+        },
+        credentials: Credentials {
+            id: optional_input("ID of the accessory")?.unwrap_or_else(accessory::ID::new_v4),
+            password,
+        },
+        services: Default::default(),
+    })
+}
+
+fn client() -> anyhow::Result<houseflow_config::client::Config> {
+    use houseflow_config::client::*;
+
+    Ok(Config {
+        server: Server {
+            url: input("URL of the server", defaults::server_http_url())?,
+        },
+        hub: optional_input("URL of the hub in the local network")?.map(|url| Hub { url }),
+        ..Default::default()
+    })
+}
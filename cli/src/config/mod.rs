@@ -0,0 +1,30 @@
+pub mod init;
+pub mod validate;
+
+use houseflow_config::Config;
+use std::path::PathBuf;
+
+/// Component of Houseflow that has its own configuration file
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display, serde::Serialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    Server,
+    Hub,
+    Accessory,
+    Client,
+}
+
+impl Kind {
+    /// Standard location of the configuration file of the component
+    pub fn default_path(self) -> PathBuf {
+        match self {
+            Self::Server => houseflow_config::server::Config::default_path(),
+            Self::Hub => houseflow_config::hub::Config::default_path(),
+            Self::Accessory => houseflow_config::accessory::Config::default_path(),
+            Self::Client => houseflow_config::client::Config::default_path(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use super::Kind;
+use crate::CommandContext;
+use anyhow::Context;
+use async_trait::async_trait;
+use houseflow_config::validation::check;
+use houseflow_config::validation::check_with;
+use houseflow_config::validation::Diagnostic;
+use serde::Serialize;
+use std::path::PathBuf;
+
+pub struct Command {
+    pub kind: Kind,
+    pub path: Option<PathBuf>,
+    /// Cargo features of the hub, controllers and providers are not checked against them if `None`
+    pub features: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    kind: Kind,
+    path: PathBuf,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        if self.diagnostics.is_empty() {
+            return format!(
+                "✔ {} is a valid {} configuration",
+                self.path.display(),
+                self.kind
+            );
+        }
+        let mut lines = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| match diagnostic.line {
+                Some(line) => format!("{}:{}: {}", self.path.display(), line, diagnostic.message),
+                None => format!("{}: {}", self.path.display(), diagnostic.message),
+            })
+            .collect::<Vec<_>>();
+        lines.push(format!(
+            "✘ {} has {} problem(s)",
+            self.path.display(),
+            self.diagnostics.len()
+        ));
+        lines.join("\n")
+    }
+
+    fn success(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = Output;
+
+    async fn run(self, _ctx: CommandContext) -> anyhow::Result<Output> {
+        let path = self.path.unwrap_or_else(|| self.kind.default_path());
+        let source =
+            std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let diagnostics = match self.kind {
+            Kind::Server => check::<houseflow_config::server::Config>(&source),
+            Kind::Hub => check_with(
+                &source,
+                |config: &houseflow_config::hub::Config| match &self.features {
+                    Some(features) => config.unsupported_components(
+                        &features.iter().map(String::as_str).collect::<Vec<_>>(),
+                    ),
+                    None => Vec::new(),
+                },
+            ),
+            Kind::Accessory => check::<houseflow_config::accessory::Config>(&source),
+            Kind::Client => check::<houseflow_config::client::Config>(&source),
+        };
+        Ok(Output {
+            kind: self.kind,
+            path,
+            diagnostics,
+        })
+    }
+}
@@ -1,5 +1,10 @@
+use crate::output::Format;
+use crate::tokens;
+use crate::tokens::TokenStore;
 use anyhow::Context;
+use houseflow_api::hub::HubClient;
 use houseflow_api::server::Client as ServerClient;
+use houseflow_api::server::Session;
 use houseflow_config::client::Config;
 use houseflow_config::Config as _;
 use houseflow_types::accessory::Accessory;
@@ -7,6 +12,7 @@ use houseflow_types::token::AccessToken;
 use houseflow_types::token::RefreshToken;
 use serde::Deserialize;
 use serde::Serialize;
+use std::sync::Arc;
 use szafka::Szafka;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -15,23 +21,48 @@ pub struct Tokens {
     pub(crate) refresh: String,
 }
 
+/// Where the commands are sent, chosen with `--target`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::EnumVariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Target {
+    Server,
+    /// Hub from the configuration, reachable without the internet. Supports only reading and writing characteristics, identifying accessories and watching their events.
+    Hub,
+}
+
 #[derive(Debug, Clone)]
 pub struct CommandContext {
     config_path: std::path::PathBuf,
     config: Option<Config>,
     server_client: Option<ServerClient>,
-    pub tokens: Szafka<Tokens>,
+    hub_client: Option<HubClient>,
+    tokens: Option<Arc<dyn TokenStore>>,
     pub devices: Szafka<Vec<Accessory>>,
+    /// Format of the results, chosen with `--output`
+    pub output: Format,
+    /// Profile chosen with `--profile`
+    pub profile: String,
+    /// Target chosen with `--target`
+    pub target: Target,
 }
 
 impl CommandContext {
-    pub fn new(config_path: std::path::PathBuf) -> anyhow::Result<Self> {
+    pub fn new(
+        config_path: std::path::PathBuf,
+        output: Format,
+        profile: String,
+        target: Target,
+    ) -> anyhow::Result<Self> {
         let ctx = CommandContext {
             config_path,
             config: None,
             server_client: None,
-            tokens: Szafka::new(houseflow_config::defaults::data_home().join("tokens")),
+            hub_client: None,
+            tokens: None,
             devices: Szafka::new(houseflow_config::defaults::data_home().join("devices")),
+            output,
+            profile,
+            target,
         };
         Ok::<_, anyhow::Error>(ctx)
     }
@@ -53,62 +84,79 @@ impl CommandContext {
     }
 
     pub fn server_client(&mut self) -> anyhow::Result<&ServerClient> {
+        if self.target == Target::Hub {
+            anyhow::bail!("the command is not supported by the hub, use `--target server`");
+        }
         match self.server_client {
             Some(ref api) => Ok(api),
             None => {
+                let profile = self.profile.clone();
                 let config = self.config()?;
-                let client = ServerClient::new(config.clone());
+                let client = ServerClient::new(Config {
+                    server: config.server(&profile).clone(),
+                    ..config.clone()
+                });
                 self.server_client = Some(client);
                 Ok(self.server_client.as_ref().unwrap())
             }
         }
     }
 
-    pub async fn access_token(&mut self) -> anyhow::Result<AccessToken> {
-        let tokens = match self.tokens.get() {
-            Ok(tokens) => tokens,
-            Err(szafka::Error::OpenFileError(err)) => match err.kind() {
-                std::io::ErrorKind::NotFound => {
-                    return Err(anyhow::anyhow!(
-                        "Tokens not found on disk. You need to log in."
-                    ))
-                }
-                _ => return Err(err.into()),
-            },
-            Err(err) => return Err(err).context("Get tokens error"),
-        };
-        let refresh_token = RefreshToken::decode_insecure(&tokens.refresh)
-            .with_context(|| "you may need to log in again using `houseflow auth login`")?;
-
-        let access_token = AccessToken::decode_insecure(&tokens.access);
-        match access_token {
-            Ok(token) => {
-                tracing::debug!("cached access token is valid");
-                Ok(token)
+    pub fn hub_client(&mut self) -> anyhow::Result<&HubClient> {
+        match self.hub_client {
+            Some(ref client) => Ok(client),
+            None => {
+                let profile = self.profile.clone();
+                let hub = self.config()?.hub(&profile).cloned().with_context(|| {
+                    format!(
+                        "hub of the {} profile is not configured, set its `hub.url` in the configuration",
+                        profile
+                    )
+                })?;
+                self.hub_client = Some(HubClient::new(hub));
+                Ok(self.hub_client.as_ref().unwrap())
             }
-            Err(err) => {
-                tracing::debug!("token verify returned error: {}", err);
-                tracing::debug!("cached access token is expired, fetching new one");
-                let raw_fetched_access_token = self
-                    .server_client()?
-                    .refresh_token(&refresh_token)
-                    .await??
-                    .access_token;
-                let fetched_access_token = AccessToken::decode_insecure(&raw_fetched_access_token)?;
-                let tokens = Tokens {
-                    refresh: tokens.refresh,
-                    access: raw_fetched_access_token,
-                };
+        }
+    }
 
-                self.tokens.save(&tokens)?;
-                Ok(fetched_access_token)
+    /// Store of the tokens of the profile, as configured in `tokens.store`
+    pub fn tokens(&mut self) -> anyhow::Result<Arc<dyn TokenStore>> {
+        match self.tokens {
+            Some(ref tokens) => Ok(tokens.clone()),
+            None => {
+                let kind = self.config()?.tokens.store;
+                let tokens: Arc<dyn TokenStore> = tokens::open(kind, &self.profile)?.into();
+                self.tokens = Some(tokens.clone());
+                Ok(tokens)
             }
         }
     }
 
-    pub fn refresh_token(&mut self) -> anyhow::Result<RefreshToken> {
-        let tokens = self.tokens.get().with_context(|| "get tokens")?;
-        RefreshToken::decode_insecure(&tokens.refresh)
-            .with_context(|| "you may need to log in again using `houseflow auth login`")
+    /// Authenticated session of the profile, tokens refreshed by it are saved to the store
+    pub fn session(&mut self) -> anyhow::Result<Session> {
+        let client = self.server_client()?.clone();
+        let store = self.tokens()?;
+        let tokens = store.get()?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Tokens of the {} profile not found. You need to log in.",
+                self.profile
+            )
+        })?;
+        let refresh_token = RefreshToken::decode_insecure(&tokens.refresh)
+            .with_context(|| "you may need to log in again using `houseflow auth login`")?;
+        let access_token = AccessToken::decode_insecure_novalidate(&tokens.access)?;
+        let session = Session::new(client, access_token, refresh_token).on_refresh(
+            move |access_token, refresh_token| {
+                tracing::debug!("access token refreshed, saving to {}", store.location());
+                let tokens = Tokens {
+                    access: access_token.encode(),
+                    refresh: refresh_token.encode(),
+                };
+                if let Err(err) = store.save(&tokens) {
+                    tracing::warn!("failed to save the refreshed tokens: {:#}", err);
+                }
+            },
+        );
+        Ok(session)
     }
 }
@@ -0,0 +1,515 @@
+use crate::watch::describe;
+use crate::CommandContext;
+use async_trait::async_trait;
+use crossterm::event::Event as TerminalEvent;
+use crossterm::event::EventStream as TerminalEvents;
+use crossterm::event::KeyCode;
+use crossterm::terminal::EnterAlternateScreen;
+use crossterm::terminal::LeaveAlternateScreen;
+use futures::StreamExt;
+use houseflow_api::server::Session;
+use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::characteristics::On;
+use houseflow_types::accessory::characteristics::TargetDoorState;
+use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::accessory::Accessory;
+use houseflow_types::accessory::CharacteristicRead;
+use houseflow_types::meta;
+use houseflow_types::meta::Event;
+use houseflow_types::meta::EventFilter;
+use houseflow_types::structure::Structure;
+use std::collections::VecDeque;
+use std::io::Stdout;
+use tui::backend::CrosstermBackend;
+use tui::layout::Constraint;
+use tui::layout::Direction;
+use tui::layout::Layout;
+use tui::layout::Rect;
+use tui::style::Color;
+use tui::style::Modifier;
+use tui::style::Style;
+use tui::widgets::Block;
+use tui::widgets::Borders;
+use tui::widgets::Paragraph;
+use tui::widgets::Row;
+use tui::widgets::Sparkline;
+use tui::widgets::Table;
+use tui::widgets::TableState;
+use tui::Frame;
+use tui::Terminal;
+
+type Backend = CrosstermBackend<Stdout>;
+
+const HELP: &str = "↑/↓ select  t toggle light  o open/close garage  q quit";
+
+pub struct Command {
+    /// Number of the sensor readings kept for the sparklines
+    pub history: usize,
+}
+
+/// Last value of a characteristic, with the previous values of the numeric sensors
+struct Reading {
+    service_name: ServiceName,
+    characteristic: Characteristic,
+    history: VecDeque<f32>,
+}
+
+struct Entry {
+    id: accessory::ID,
+    /// Known once the `Connected` event of the accessory is received
+    accessory: Option<Accessory>,
+    capabilities: Capabilities,
+    structure: Option<Structure>,
+    readings: Vec<Reading>,
+}
+
+impl Entry {
+    fn name(&self) -> String {
+        self.accessory
+            .as_ref()
+            .map(|accessory| accessory.name.clone())
+            .unwrap_or_else(|| self.id.to_string())
+    }
+
+    fn room_name(&self) -> &str {
+        self.accessory
+            .as_ref()
+            .map(|accessory| accessory.room_name.as_str())
+            .unwrap_or_default()
+    }
+
+    fn structure_name(&self) -> &str {
+        self.structure
+            .as_ref()
+            .map(|structure| structure.name.as_str())
+            .unwrap_or_default()
+    }
+
+    fn characteristic(
+        &self,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+    ) -> Option<&Characteristic> {
+        self.readings
+            .iter()
+            .find(|reading| {
+                reading.service_name == service_name
+                    && CharacteristicName::from(&reading.characteristic) == characteristic_name
+            })
+            .map(|reading| &reading.characteristic)
+    }
+
+    /// Short summary of the state, e.g `on` for lights or `open 100%` for garages
+    fn state(&self) -> String {
+        if let Some(Characteristic::On(On { on })) =
+            self.characteristic(ServiceName::Light, CharacteristicName::On)
+        {
+            return String::from(if *on { "on" } else { "off" });
+        }
+        if let Some(Characteristic::CurrentDoorState(state)) = self.characteristic(
+            ServiceName::GarageDoorOpener,
+            CharacteristicName::CurrentDoorState,
+        ) {
+            return match state.open_percent {
+                0 => String::from("closed"),
+                open_percent => format!("open {}%", open_percent),
+            };
+        }
+        self.readings
+            .iter()
+            .find(|reading| reading.characteristic.value().is_some())
+            .map(|reading| describe(&reading.characteristic))
+            .unwrap_or_default()
+    }
+
+    fn update(
+        &mut self,
+        service_name: ServiceName,
+        characteristic: Characteristic,
+        history: usize,
+    ) {
+        let characteristic_name = CharacteristicName::from(&characteristic);
+        let reading = match self.readings.iter_mut().find(|reading| {
+            reading.service_name == service_name
+                && CharacteristicName::from(&reading.characteristic) == characteristic_name
+        }) {
+            Some(reading) => reading,
+            None => {
+                self.readings.push(Reading {
+                    service_name,
+                    characteristic: characteristic.clone(),
+                    history: VecDeque::with_capacity(history),
+                });
+                self.readings.last_mut().unwrap()
+            }
+        };
+        // only sensors get a sparkline, history of writable characteristics isn't interesting
+        let capability = self
+            .capabilities
+            .service(service_name)
+            .and_then(|service| service.characteristic(characteristic_name));
+        let is_sensor = matches!(capability, Some(capability) if !capability.permissions.write);
+        if let Some(value) = characteristic.value().filter(|_| is_sensor) {
+            if reading.history.len() == history {
+                reading.history.pop_front();
+            }
+            reading.history.push_back(value);
+        }
+        reading.characteristic = characteristic;
+    }
+}
+
+struct Dashboard {
+    session: Session,
+    history: usize,
+    /// Sorted by structure, room and accessory name
+    entries: Vec<Entry>,
+    state: TableState,
+    /// Result of the last action
+    status: String,
+}
+
+impl Dashboard {
+    /// Fetches the connected accessories with the current values of their readable characteristics.
+    async fn refresh(&mut self) -> anyhow::Result<()> {
+        let response = self.session.list_accessories().await??;
+        for connected in response.accessories {
+            if self.entries.iter().any(|entry| entry.id == connected.id) {
+                continue;
+            }
+            let reads = connected
+                .capabilities
+                .services
+                .iter()
+                .flat_map(|service| {
+                    service
+                        .characteristics
+                        .iter()
+                        .filter(|characteristic| characteristic.permissions.read)
+                        .map(|characteristic| CharacteristicRead {
+                            accessory_id: connected.id,
+                            service_name: service.name,
+                            characteristic_name: characteristic.name,
+                        })
+                })
+                .collect::<Vec<_>>();
+            let results = self
+                .session
+                .client()
+                .batch_read_characteristics(&meta::BatchReadRequest {
+                    reads: reads.clone(),
+                })
+                .await??
+                .results;
+            let mut entry = Entry {
+                id: connected.id,
+                accessory: None,
+                capabilities: connected.capabilities,
+                structure: connected.structure,
+                readings: Vec::new(),
+            };
+            for (read, result) in reads.into_iter().zip(results) {
+                if let accessory::Result::Ok(body) = result {
+                    entry.update(read.service_name, body, self.history);
+                }
+            }
+            self.entries.push(entry);
+        }
+        self.sort();
+        Ok(())
+    }
+
+    fn sort(&mut self) {
+        let selected = self.selected().map(|entry| entry.id);
+        self.entries.sort_by(|a, b| {
+            (a.structure_name(), a.room_name(), a.name()).cmp(&(
+                b.structure_name(),
+                b.room_name(),
+                b.name(),
+            ))
+        });
+        let index = selected.and_then(|id| self.entries.iter().position(|entry| entry.id == id));
+        if index.is_some() || self.entries.is_empty() {
+            self.state.select(index);
+        } else {
+            self.state.select(Some(0));
+        }
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.state.selected()?)
+    }
+
+    fn select(&mut self, offset: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let index = self.state.selected().unwrap_or_default() as isize + offset;
+        let index = index.rem_euclid(self.entries.len() as isize) as usize;
+        self.state.select(Some(index));
+    }
+
+    async fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
+        match event {
+            Event::Connected { accessory } => {
+                if !self.entries.iter().any(|entry| entry.id == accessory.id) {
+                    self.refresh().await?;
+                }
+                if let Some(entry) = self
+                    .entries
+                    .iter_mut()
+                    .find(|entry| entry.id == accessory.id)
+                {
+                    entry.accessory = Some(accessory);
+                }
+                self.sort();
+            }
+            Event::Disconnected { accessory_id } => {
+                self.entries.retain(|entry| entry.id != accessory_id);
+                self.sort();
+            }
+            Event::Updated {
+                accessory_id,
+                service_name,
+                characteristic,
+            } => {
+                let history = self.history;
+                if let Some(entry) = self
+                    .entries
+                    .iter_mut()
+                    .find(|entry| entry.id == accessory_id)
+                {
+                    entry.update(service_name, characteristic, history);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, service_name: ServiceName, characteristic: Characteristic) {
+        let entry = match self.selected() {
+            Some(entry) => entry,
+            None => return,
+        };
+        let name = entry.name();
+        let result = self
+            .session
+            .write_characteristics(&entry.id, &service_name, &characteristic)
+            .await;
+        self.status = match result {
+            Ok(Ok(())) => format!("✔ {}: {}", name, describe(&characteristic)),
+            Ok(Err(err)) => format!("✘ {}: {}", name, err),
+            Err(err) => format!("✘ {}: {}", name, err),
+        };
+    }
+
+    async fn toggle_light(&mut self) {
+        let on = match self.selected() {
+            Some(entry) if entry.capabilities.service(ServiceName::Light).is_some() => {
+                matches!(
+                    entry.characteristic(ServiceName::Light, CharacteristicName::On),
+                    Some(Characteristic::On(On { on: true }))
+                )
+            }
+            Some(_) => {
+                self.status = String::from("Selected accessory is not a light");
+                return;
+            }
+            None => return,
+        };
+        self.write(ServiceName::Light, Characteristic::On(On { on: !on }))
+            .await;
+    }
+
+    async fn toggle_garage(&mut self) {
+        let open_percent = match self.selected() {
+            Some(entry)
+                if entry
+                    .capabilities
+                    .service(ServiceName::GarageDoorOpener)
+                    .is_some() =>
+            {
+                let is_closed = matches!(
+                    entry.characteristic(
+                        ServiceName::GarageDoorOpener,
+                        CharacteristicName::CurrentDoorState
+                    ),
+                    Some(Characteristic::CurrentDoorState(state)) if state.open_percent == 0
+                );
+                if is_closed {
+                    100
+                } else {
+                    0
+                }
+            }
+            Some(_) => {
+                self.status = String::from("Selected accessory is not a garage");
+                return;
+            }
+            None => return,
+        };
+        self.write(
+            ServiceName::GarageDoorOpener,
+            Characteristic::TargetDoorState(TargetDoorState { open_percent }),
+        )
+        .await;
+    }
+
+    fn draw(&mut self, frame: &mut Frame<Backend>) {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(frame.size());
+        let (main, status) = (areas[0], areas[1]);
+        let areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(main);
+        let (list, details) = (areas[0], areas[1]);
+        let rows = self.entries.iter().map(|entry| {
+            Row::new(vec![
+                entry.structure_name().to_string(),
+                entry.room_name().to_string(),
+                entry.name(),
+                entry.state(),
+            ])
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["STRUCTURE", "ROOM", "ACCESSORY", "STATE"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::default().borders(Borders::ALL).title("Accessories"))
+            .widths(&[
+                Constraint::Percentage(20),
+                Constraint::Percentage(20),
+                Constraint::Percentage(30),
+                Constraint::Percentage(30),
+            ])
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, list, &mut self.state);
+        if let Some(entry) = self.selected() {
+            draw_details(frame, details, entry);
+        }
+        let text = if self.status.is_empty() {
+            HELP
+        } else {
+            self.status.as_str()
+        };
+        frame.render_widget(Paragraph::new(text), status);
+    }
+}
+
+/// Draws the values of the accessory characteristics, with a sparkline below every sensor that has a history.
+fn draw_details(frame: &mut Frame<Backend>, area: Rect, entry: &Entry) {
+    let block = Block::default().borders(Borders::ALL).title(entry.name());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let constraints = entry
+        .readings
+        .iter()
+        .map(|reading| match reading.history.len() {
+            0 | 1 => Constraint::Length(1),
+            _ => Constraint::Length(4),
+        })
+        .chain(std::iter::once(Constraint::Min(0)))
+        .collect::<Vec<_>>();
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+    for (reading, area) in entry.readings.iter().zip(areas) {
+        let text = format!(
+            "{}: {}",
+            reading.service_name,
+            describe(&reading.characteristic)
+        );
+        if reading.history.len() < 2 {
+            frame.render_widget(Paragraph::new(text), area);
+            continue;
+        }
+        // sparkline draws unsigned values, so they're shifted by the minimum and scaled to keep the fractions
+        let min = reading
+            .history
+            .iter()
+            .copied()
+            .fold(f32::INFINITY, f32::min);
+        let data = reading
+            .history
+            .iter()
+            .map(|value| ((value - min) * 100.0) as u64)
+            .collect::<Vec<_>>();
+        let sparkline = Sparkline::default()
+            .block(Block::default().title(text))
+            .data(&data)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(sparkline, area);
+    }
+}
+
+/// Restores the terminal once dropped, also when the dashboard fails.
+struct TerminalGuard(Terminal<Backend>);
+
+impl TerminalGuard {
+    fn new() -> anyhow::Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        let mut stdout = std::io::stdout();
+        crossterm::execute!(stdout, EnterAlternateScreen)?;
+        Ok(Self(Terminal::new(CrosstermBackend::new(stdout))?))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+        let _ = crossterm::execute!(self.0.backend_mut(), LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+    }
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = ();
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<()> {
+        let session = ctx.session()?;
+        let mut events = session.watch(&EventFilter::default()).await??;
+        let mut dashboard = Dashboard {
+            session,
+            history: self.history,
+            entries: Vec::new(),
+            state: TableState::default(),
+            status: String::new(),
+        };
+        dashboard.refresh().await?;
+        let mut terminal = TerminalGuard::new()?;
+        let mut terminal_events = TerminalEvents::new();
+        loop {
+            terminal.0.draw(|frame| dashboard.draw(frame))?;
+            tokio::select! {
+                event = events.next() => match event? {
+                    Some(event) => dashboard.handle_event(event).await?,
+                    None => anyhow::bail!("server closed the event stream"),
+                },
+                event = terminal_events.next() => {
+                    let key = match event.transpose()? {
+                        Some(TerminalEvent::Key(key)) => key,
+                        Some(_) => continue,
+                        None => return Ok(()),
+                    };
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Up | KeyCode::Char('k') => dashboard.select(-1),
+                        KeyCode::Down | KeyCode::Char('j') => dashboard.select(1),
+                        KeyCode::Char('t') | KeyCode::Enter => dashboard.toggle_light().await,
+                        KeyCode::Char('o') => dashboard.toggle_garage().await,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
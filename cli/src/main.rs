@@ -1,18 +1,29 @@
+mod admin;
 mod auth;
 mod cli;
+mod config;
 mod context;
+mod dashboard;
 mod meta;
+mod output;
+mod tokens;
+mod watch;
 
 use anyhow::Context;
 use async_trait::async_trait;
 use cli::get_input;
 use context::CommandContext;
+use context::Target;
 use context::Tokens;
 use houseflow_config::client::Config;
 use houseflow_config::Config as _;
 use houseflow_types::code::VerificationCode;
+use houseflow_types::meta::EventFilter;
 use lazy_static::lazy_static;
+use output::Format;
+use output::Output;
 use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 lazy_static! {
@@ -20,8 +31,21 @@ lazy_static! {
 }
 
 #[async_trait]
-pub trait Command {
-    async fn run(self, ctx: CommandContext) -> anyhow::Result<()>;
+pub trait Command: Sized + Send {
+    type Output: Output;
+
+    async fn run(self, ctx: CommandContext) -> anyhow::Result<Self::Output>;
+
+    /// Runs the command and prints its result in the format chosen with `--output`
+    async fn execute(self, ctx: CommandContext) -> anyhow::Result<()> {
+        let format = ctx.output;
+        let output = self.run(ctx).await?;
+        output.print(format)?;
+        if !output.success() {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
 }
 
 #[tokio::main]
@@ -30,9 +54,12 @@ async fn main() -> anyhow::Result<()> {
     let matches = cli::app(DEFAULT_CONFIG_PATH.as_os_str()).get_matches();
     let subcommand = matches.subcommand().unwrap();
     let config_path = Path::new(matches.value_of("config").unwrap());
-    let ctx = CommandContext::new(config_path.to_path_buf())?;
+    let output = Format::from_str(matches.value_of("output").unwrap())?;
+    let profile = matches.value_of("profile").unwrap().to_string();
+    let target = Target::from_str(matches.value_of("target").unwrap())?;
+    let ctx = CommandContext::new(config_path.to_path_buf(), output, profile, target)?;
 
-    match subcommand {
+    let result = match subcommand {
         ("auth", matches) => match matches.subcommand().unwrap() {
             ("login", matches) => {
                 auth::login::Command {
@@ -41,20 +68,84 @@ async fn main() -> anyhow::Result<()> {
                         .value_of("code")
                         .map(|str| VerificationCode::from_str(str).unwrap()),
                 }
-                .run(ctx)
+                .execute(ctx)
                 .await
             }
-            ("logout", _) => auth::logout::Command {}.run(ctx).await,
-            ("refresh", _) => auth::refresh::Command {}.run(ctx).await,
+            ("logout", _) => auth::logout::Command {}.execute(ctx).await,
+            ("refresh", _) => auth::refresh::Command {}.execute(ctx).await,
             ("status", matches) => {
                 auth::status::Command {
                     show_token: matches.is_present("show-token"),
                 }
-                .run(ctx)
+                .execute(ctx)
                 .await
             }
             _ => unreachable!(),
         },
+        ("admin", matches) => match matches.subcommand().unwrap() {
+            ("user", matches) => match matches.subcommand().unwrap() {
+                ("add", matches) => {
+                    admin::user::Add {
+                        username: get_value(matches, get_input, "username")?,
+                        email: get_value(matches, get_input, "email")?,
+                        admin: matches.is_present("admin"),
+                    }
+                    .execute(ctx)
+                    .await
+                }
+                ("remove", matches) => {
+                    admin::user::Remove {
+                        user_id: get_value(matches, get_input, "user-id")?,
+                    }
+                    .execute(ctx)
+                    .await
+                }
+                ("list", _) => admin::user::List {}.execute(ctx).await,
+                _ => unreachable!(),
+            },
+            ("structure", matches) => match matches.subcommand().unwrap() {
+                ("add", matches) => {
+                    admin::structure::Add {
+                        name: get_value(matches, get_input, "name")?,
+                    }
+                    .execute(ctx)
+                    .await
+                }
+                _ => unreachable!(),
+            },
+            ("permission", matches) => match matches.subcommand().unwrap() {
+                ("grant", matches) => {
+                    admin::permission::Grant {
+                        user_id: get_value(matches, get_input, "user-id")?,
+                        structure_id: get_value(matches, get_input, "structure-id")?,
+                        is_manager: matches.is_present("manager"),
+                    }
+                    .execute(ctx)
+                    .await
+                }
+                ("revoke", matches) => {
+                    admin::permission::Revoke {
+                        user_id: get_value(matches, get_input, "user-id")?,
+                        structure_id: get_value(matches, get_input, "structure-id")?,
+                    }
+                    .execute(ctx)
+                    .await
+                }
+                _ => unreachable!(),
+            },
+            ("hub", matches) => match matches.subcommand().unwrap() {
+                ("register", matches) => {
+                    admin::hub::Register {
+                        name: get_value(matches, get_input, "name")?,
+                        structure_id: get_value(matches, get_input, "structure-id")?,
+                    }
+                    .execute(ctx)
+                    .await
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        },
         ("meta", matches) => match matches.subcommand().unwrap() {
             ("read", matches) => {
                 meta::read::Command {
@@ -62,7 +153,80 @@ async fn main() -> anyhow::Result<()> {
                     service_name: get_value(matches, get_input, "service-name")?,
                     characteristic_name: get_value(matches, get_input, "characteristic-name")?,
                 }
-                .run(ctx)
+                .execute(ctx)
+                .await
+            }
+            ("write", matches) => {
+                meta::write::Command {
+                    accessory_id: get_value(matches, get_input, "accessory-id")?,
+                    service_name: get_value(matches, get_input, "service-name")?,
+                    characteristic_name: get_value(matches, get_input, "characteristic-name")?,
+                    values: matches
+                        .values_of("values")
+                        .unwrap()
+                        .map(ToString::to_string)
+                        .collect(),
+                }
+                .execute(ctx)
+                .await
+            }
+            ("list", _) => meta::list::Command {}.execute(ctx).await,
+            ("identify", matches) => {
+                meta::identify::Command {
+                    accessory_id: get_value(matches, get_input, "accessory-id")?,
+                }
+                .execute(ctx)
+                .await
+            }
+            _ => unreachable!(),
+        },
+        ("watch", matches) => {
+            watch::Command {
+                filter: EventFilter {
+                    accessory_id: matches
+                        .value_of("accessory-id")
+                        .map(FromStr::from_str)
+                        .transpose()?,
+                    room_name: matches.value_of("room-name").map(ToString::to_string),
+                    service_name: matches
+                        .value_of("service-name")
+                        .map(FromStr::from_str)
+                        .transpose()?,
+                },
+                format: match matches.value_of("format") {
+                    Some(format) => FromStr::from_str(format)?,
+                    None => output.into(),
+                },
+            }
+            .execute(ctx)
+            .await
+        }
+        ("dashboard", matches) => {
+            dashboard::Command {
+                history: matches.value_of("history").unwrap().parse()?,
+            }
+            .execute(ctx)
+            .await
+        }
+        ("config", matches) => match matches.subcommand().unwrap() {
+            ("init", matches) => {
+                config::init::Command {
+                    kind: FromStr::from_str(matches.value_of("kind").unwrap())?,
+                    path: matches.value_of("path").map(PathBuf::from),
+                    force: matches.is_present("force"),
+                }
+                .execute(ctx)
+                .await
+            }
+            ("validate", matches) => {
+                config::validate::Command {
+                    kind: FromStr::from_str(matches.value_of("kind").unwrap())?,
+                    path: matches.value_of("path").map(PathBuf::from),
+                    features: matches
+                        .values_of("features")
+                        .map(|values| values.map(ToString::to_string).collect()),
+                }
+                .execute(ctx)
                 .await
             }
             _ => unreachable!(),
@@ -77,8 +241,14 @@ async fn main() -> anyhow::Result<()> {
             Ok(())
         }
         _ => unreachable!(),
-    }?;
-    Ok::<(), anyhow::Error>(())
+    };
+    match result {
+        Err(err) if output != Format::Text => {
+            output::print_error(&err, output)?;
+            std::process::exit(1);
+        }
+        result => result,
+    }
 }
 
 fn get_value<T, TE, AF>(
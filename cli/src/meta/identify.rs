@@ -0,0 +1,36 @@
+use crate::context::Target;
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::accessory;
+use serde::Serialize;
+
+pub struct Command {
+    pub accessory_id: accessory::ID,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    accessory_id: accessory::ID,
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        format!("✔ Asked {} to identify itself", self.accessory_id)
+    }
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        match ctx.target {
+            Target::Server => ctx.session()?.identify(&self.accessory_id).await??,
+            Target::Hub => ctx.hub_client()?.identify(&self.accessory_id).await??,
+        };
+        Ok(Output {
+            accessory_id: self.accessory_id,
+        })
+    }
+}
@@ -0,0 +1,62 @@
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::accessory::capabilities::CharacteristicCapability;
+use houseflow_types::meta::ListAccessoriesResponse;
+use serde::Serialize;
+
+pub struct Command {}
+
+/// Describes the characteristic, e.g `current-temperature (read, notify) -20..60 °C`
+fn describe(characteristic: &CharacteristicCapability) -> String {
+    let permissions = [
+        ("read", characteristic.permissions.read),
+        ("write", characteristic.permissions.write),
+        ("notify", characteristic.permissions.notify),
+    ]
+    .into_iter()
+    .filter(|(_, permitted)| *permitted)
+    .map(|(name, _)| name)
+    .collect::<Vec<_>>()
+    .join(", ");
+    let mut description = format!("{} ({})", characteristic.name, permissions);
+    if let Some(range) = &characteristic.range {
+        description.push_str(&format!(" {}..{}", range.min, range.max));
+    }
+    if let Some(unit) = &characteristic.unit {
+        description.push_str(&format!(" {}", unit.symbol()));
+    }
+    description
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Output(ListAccessoriesResponse);
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        if self.0.accessories.is_empty() {
+            return String::from("No accessories are connected");
+        }
+        let mut lines = vec![];
+        for accessory in &self.0.accessories {
+            lines.push(accessory.id.to_string());
+            for service in &accessory.capabilities.services {
+                lines.push(format!("  {}", service.name));
+                for characteristic in &service.characteristics {
+                    lines.push(format!("    {}", describe(characteristic)));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        let response = ctx.session()?.list_accessories().await??;
+        Ok(Output(response))
+    }
+}
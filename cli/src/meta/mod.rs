@@ -1 +1,4 @@
+pub mod identify;
+pub mod list;
 pub mod read;
+pub mod write;
@@ -1,8 +1,11 @@
+use crate::context::Target;
 use crate::CommandContext;
 use async_trait::async_trait;
 use houseflow_types::accessory;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::meta::ReadResponse;
+use serde::Serialize;
 
 pub struct Command {
     pub accessory_id: accessory::ID,
@@ -10,18 +13,48 @@ pub struct Command {
     pub characteristic_name: CharacteristicName,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Output(ReadResponse);
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        match &self.0.display {
+            Some(display) => display.to_string(),
+            None => crate::watch::describe(&self.0.characteristic),
+        }
+    }
+}
+
 #[async_trait]
 impl crate::Command for Command {
-    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<()> {
-        let characteristic = ctx
-            .server_client()?
-            .read_characteristics(
-                &self.accessory_id,
-                &self.service_name,
-                &self.characteristic_name,
-            )
-            .await??;
-        tracing::info!("Characteristic: {:?}", characteristic);
-        Ok(())
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        let response = match ctx.target {
+            Target::Server => {
+                ctx.session()?
+                    .read_characteristics(
+                        &self.accessory_id,
+                        &self.service_name,
+                        &self.characteristic_name,
+                    )
+                    .await??
+            }
+            // the hub doesn't know the preferred units of the user, so there is no display value
+            Target::Hub => ReadResponse {
+                characteristic: ctx
+                    .hub_client()?
+                    .read_characteristic(
+                        &self.accessory_id,
+                        &self.service_name,
+                        &self.characteristic_name,
+                    )
+                    .await??,
+                display: None,
+            },
+        };
+        tracing::info!("Characteristic: {:?}", response.characteristic);
+        Ok(Output(response))
     }
 }
@@ -0,0 +1,145 @@
+use crate::context::Target;
+use crate::CommandContext;
+use anyhow::Context;
+use async_trait::async_trait;
+use houseflow_types::accessory;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::services::ServiceName;
+use serde::Serialize;
+use serde_json::Value;
+
+pub struct Command {
+    pub accessory_id: accessory::ID,
+    pub service_name: ServiceName,
+    pub characteristic_name: CharacteristicName,
+    /// Fields of the characteristic in `field=value` form
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    accessory_id: accessory::ID,
+    service_name: ServiceName,
+    characteristic: Characteristic,
+}
+
+impl crate::Output for Output {
+    fn text(&self) -> String {
+        format!("✔ Written {:?}", self.characteristic)
+    }
+}
+
+/// Builds the characteristic from `field=value` pairs, values which aren't valid JSON are treated as strings, e.g `mode=heat`.
+fn characteristic(
+    characteristic_name: CharacteristicName,
+    values: &[String],
+) -> anyhow::Result<Characteristic> {
+    let mut fields = serde_json::Map::new();
+    fields.insert(
+        String::from("name"),
+        Value::String(characteristic_name.to_string()),
+    );
+    for value in values {
+        let (field, value) = value
+            .split_once('=')
+            .with_context(|| format!("expected `field=value`, got `{}`", value))?;
+        let value =
+            serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+        fields.insert(field.to_string(), value);
+    }
+    serde_json::from_value(Value::Object(fields))
+        .with_context(|| format!("invalid value of {}", characteristic_name))
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = Output;
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<Output> {
+        let characteristic = characteristic(self.characteristic_name, &self.values)?;
+        match ctx.target {
+            Target::Server => {
+                ctx.session()?
+                    .write_characteristics(&self.accessory_id, &self.service_name, &characteristic)
+                    .await??
+            }
+            Target::Hub => {
+                ctx.hub_client()?
+                    .write_characteristic(&self.accessory_id, &self.service_name, &characteristic)
+                    .await??
+            }
+        };
+        Ok(Output {
+            accessory_id: self.accessory_id,
+            service_name: self.service_name,
+            characteristic,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::characteristic;
+    use houseflow_types::accessory::characteristics;
+    use houseflow_types::accessory::characteristics::Characteristic;
+    use houseflow_types::accessory::characteristics::CharacteristicName;
+    use houseflow_types::accessory::characteristics::HeatingCoolingMode;
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn boolean() {
+        assert_eq!(
+            characteristic(CharacteristicName::On, &values(&["on=true"])).unwrap(),
+            Characteristic::On(characteristics::On { on: true })
+        );
+    }
+
+    #[test]
+    fn number() {
+        assert_eq!(
+            characteristic(
+                CharacteristicName::TargetDoorState,
+                &values(&["open-percent=50"])
+            )
+            .unwrap(),
+            Characteristic::TargetDoorState(characteristics::TargetDoorState { open_percent: 50 })
+        );
+    }
+
+    #[test]
+    fn string_fallback() {
+        assert_eq!(
+            characteristic(
+                CharacteristicName::TargetHeatingCoolingState,
+                &values(&["mode=heat"])
+            )
+            .unwrap(),
+            Characteristic::TargetHeatingCoolingState(characteristics::TargetHeatingCoolingState {
+                mode: HeatingCoolingMode::Heat
+            })
+        );
+    }
+
+    #[test]
+    fn bad_field() {
+        assert!(characteristic(CharacteristicName::On, &values(&["on"])).is_err());
+        assert!(characteristic(CharacteristicName::On, &values(&["brightness=true"])).is_err());
+    }
+
+    #[test]
+    fn wrong_type() {
+        assert!(characteristic(CharacteristicName::On, &values(&["on=50"])).is_err());
+        // not valid JSON, so it's passed as a string which isn't a boolean
+        assert!(characteristic(CharacteristicName::On, &values(&["on=yes"])).is_err());
+        assert!(characteristic(
+            CharacteristicName::TargetDoorState,
+            &values(&["open-percent=300"])
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use houseflow_types::errors::ServerError;
+use houseflow_types::hub;
+use serde::Serialize;
+
+/// Format of the results and errors printed by the commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::EnumVariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Format {
+    Text,
+    Json,
+    Yaml,
+}
+
+/// Result of a command, printed in the format chosen with `--output`
+pub trait Output: Serialize + Send {
+    /// Human-readable form of the result
+    fn text(&self) -> String;
+
+    /// `false` if the command completed, but the result should make the process exit with a failure, e.g invalid configuration
+    fn success(&self) -> bool {
+        true
+    }
+
+    fn print(&self, format: Format) -> anyhow::Result<()> {
+        match format {
+            Format::Text => {
+                println!("{}", self.text());
+                Ok(())
+            }
+            _ => print_serialized(self, format),
+        }
+    }
+}
+
+fn print_serialized(value: &(impl Serialize + ?Sized), format: Format) -> anyhow::Result<()> {
+    match format {
+        Format::Text => unreachable!("text is not a serialization format"),
+        Format::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        Format::Yaml => print!("{}", serde_yaml::to_string(value)?),
+    };
+    Ok(())
+}
+
+/// Commands which print their results as they happen have nothing left to print when they finish, e.g `watch`
+impl Output for () {
+    fn text(&self) -> String {
+        String::new()
+    }
+
+    fn print(&self, _format: Format) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Error in the same form as the server responds with, so scripts can handle both the same way
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "error",
+    content = "error-description",
+    rename_all = "kebab-case"
+)]
+enum ClientError {
+    ClientError(String),
+}
+
+/// Prints the error as JSON or YAML, errors of the text format are left to `anyhow`.
+pub fn print_error(error: &anyhow::Error, format: Format) -> anyhow::Result<()> {
+    if let Some(error) = error.downcast_ref::<ServerError>() {
+        return print_serialized(error, format);
+    }
+    if let Some(error) = error.downcast_ref::<hub::Error>() {
+        return print_serialized(error, format);
+    }
+    print_serialized(&ClientError::ClientError(format!("{:#}", error)), format)
+}
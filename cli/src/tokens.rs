@@ -0,0 +1,352 @@
+use crate::Tokens;
+use anyhow::Context;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::KeyInit;
+use chacha20poly1305::Nonce;
+use houseflow_config::client::TokenStore as Kind;
+use houseflow_config::client::DEFAULT_PROFILE;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use szafka::Szafka;
+
+/// Name of the keyring service that the tokens are kept under, with profile as the user
+const KEYRING_SERVICE: &str = "houseflow";
+
+/// Environment variable with the password of the encrypted file, it's prompted for if not set
+const PASSWORD_ENV: &str = "HOUSEFLOW_TOKENS_PASSWORD";
+
+/// Storage of the tokens of a single profile
+pub trait TokenStore: std::fmt::Debug + Send + Sync {
+    /// Returns `None` if the profile is not logged in
+    fn get(&self) -> anyhow::Result<Option<Tokens>>;
+
+    fn save(&self, tokens: &Tokens) -> anyhow::Result<()>;
+
+    fn remove(&self) -> anyhow::Result<()>;
+
+    /// Where the tokens are kept, e.g path of the file
+    fn location(&self) -> String;
+}
+
+/// Opens the store of the profile, the keyring falls back to the encrypted file if it's not available.
+pub fn open(kind: Kind, profile: &str) -> anyhow::Result<Box<dyn TokenStore>> {
+    let store: Box<dyn TokenStore> = match kind {
+        Kind::Keyring => match Keyring::new(profile) {
+            Ok(keyring) => Box::new(keyring),
+            Err(err) => {
+                tracing::warn!(
+                    "keyring is not available, falling back to the encrypted file: {:#}",
+                    err
+                );
+                Box::new(EncryptedFile::new(profile))
+            }
+        },
+        Kind::EncryptedFile => Box::new(EncryptedFile::new(profile)),
+        Kind::File => Box::new(File::new(profile)),
+    };
+    tracing::debug!("using {:?} for the tokens", store);
+    if kind != Kind::File {
+        migrate(&File::new(profile), store.as_ref())?;
+    }
+    Ok(store)
+}
+
+/// Moves the tokens from the plain-text file, which was the only store before it became configurable.
+///
+/// Tokens already in the store are kept, the plain-text file is removed either way.
+fn migrate(legacy: &File, store: &dyn TokenStore) -> anyhow::Result<()> {
+    let tokens = match legacy.get()? {
+        Some(tokens) => tokens,
+        None => return Ok(()),
+    };
+    if store.get()?.is_none() {
+        store.save(&tokens)?;
+    }
+    legacy.remove()?;
+    tracing::info!(
+        "moved the tokens from {} to {}",
+        legacy.location(),
+        store.location()
+    );
+    Ok(())
+}
+
+/// Path of the file in the data directory, the default profile uses paths from before profiles were introduced
+fn path(profile: &str, extension: &str) -> PathBuf {
+    let name = match profile {
+        DEFAULT_PROFILE => String::from("tokens"),
+        profile => format!("tokens-{}", profile),
+    };
+    houseflow_config::defaults::data_home().join(name + extension)
+}
+
+#[derive(Debug)]
+pub struct Keyring {
+    entry: keyring::Entry,
+    profile: String,
+}
+
+impl Keyring {
+    /// Fails if the keyring of the operating system can't be accessed
+    fn new(profile: &str) -> anyhow::Result<Self> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, profile)?;
+        match entry.get_password() {
+            Ok(_) | Err(keyring::Error::NoEntry) => Ok(Self {
+                entry,
+                profile: profile.to_string(),
+            }),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl TokenStore for Keyring {
+    fn get(&self) -> anyhow::Result<Option<Tokens>> {
+        match self.entry.get_password() {
+            Ok(tokens) => Ok(Some(serde_json::from_str(&tokens)?)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(err).context("read tokens from the keyring"),
+        }
+    }
+
+    fn save(&self, tokens: &Tokens) -> anyhow::Result<()> {
+        self.entry
+            .set_password(&serde_json::to_string(tokens)?)
+            .context("write tokens to the keyring")
+    }
+
+    fn remove(&self) -> anyhow::Result<()> {
+        self.entry
+            .delete_password()
+            .context("remove tokens from the keyring")
+    }
+
+    fn location(&self) -> String {
+        format!("keyring ({}/{})", KEYRING_SERVICE, self.profile)
+    }
+}
+
+/// Contents of the encrypted file, all fields are hex-encoded
+#[derive(Debug, Serialize, Deserialize)]
+struct Encrypted {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// File encrypted with ChaCha20-Poly1305, using a key derived from the password with Argon2
+#[derive(Debug)]
+pub struct EncryptedFile {
+    path: PathBuf,
+    /// Password is asked for once per command
+    password: Mutex<Option<String>>,
+}
+
+impl EncryptedFile {
+    fn new(profile: &str) -> Self {
+        Self::with_path(path(profile, ".enc"))
+    }
+
+    fn with_path(path: PathBuf) -> Self {
+        Self {
+            path,
+            password: Mutex::new(None),
+        }
+    }
+
+    /// Asks for the password, with a confirmation if the file is about to be created
+    fn password(&self, confirm: bool) -> anyhow::Result<String> {
+        let mut password = self.password.lock().unwrap();
+        if let Some(password) = password.as_ref() {
+            return Ok(password.clone());
+        }
+        let entered = match std::env::var(PASSWORD_ENV) {
+            Ok(entered) => entered,
+            Err(_) => {
+                let theme = crate::cli::dialoguer_theme();
+                let mut prompt = dialoguer::Password::with_theme(&theme);
+                prompt.with_prompt("Password of the tokens file");
+                if confirm {
+                    prompt.with_confirmation("Repeat the password", "Passwords don't match");
+                }
+                prompt.interact()?
+            }
+        };
+        *password = Some(entered.clone());
+        Ok(entered)
+    }
+
+    fn cipher(password: &str, salt: &[u8]) -> anyhow::Result<ChaCha20Poly1305> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(password.as_bytes(), salt, &mut key)
+            .map_err(|err| anyhow::anyhow!("derive key: {}", err))?;
+        Ok(ChaCha20Poly1305::new(&key.into()))
+    }
+}
+
+impl TokenStore for EncryptedFile {
+    fn get(&self) -> anyhow::Result<Option<Tokens>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context("read tokens file"),
+        };
+        let encrypted: Encrypted = serde_json::from_str(&content)?;
+        let cipher = Self::cipher(&self.password(false)?, &hex::decode(encrypted.salt)?)?;
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&hex::decode(encrypted.nonce)?),
+                hex::decode(encrypted.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| anyhow::anyhow!("decrypt tokens, the password may be invalid"))?;
+        Ok(Some(serde_json::from_slice(&plaintext)?))
+    }
+
+    fn save(&self, tokens: &Tokens) -> anyhow::Result<()> {
+        let salt = rand::random::<[u8; 16]>();
+        let nonce = rand::random::<[u8; 12]>();
+        let cipher = Self::cipher(&self.password(!self.path.exists())?, &salt)?;
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                serde_json::to_vec(tokens)?.as_slice(),
+            )
+            .map_err(|_| anyhow::anyhow!("encrypt tokens"))?;
+        let encrypted = Encrypted {
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&encrypted)?).context("write tokens file")
+    }
+
+    fn remove(&self) -> anyhow::Result<()> {
+        std::fs::remove_file(&self.path).context("remove tokens file")
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+/// Plain-text file, the tokens can be read by anyone with access to it
+#[derive(Debug)]
+pub struct File {
+    szafka: Szafka<Tokens>,
+}
+
+impl File {
+    fn new(profile: &str) -> Self {
+        Self::with_path(path(profile, ""))
+    }
+
+    fn with_path(path: PathBuf) -> Self {
+        Self {
+            szafka: Szafka::new(path),
+        }
+    }
+}
+
+impl TokenStore for File {
+    fn get(&self) -> anyhow::Result<Option<Tokens>> {
+        match self.szafka.get() {
+            Ok(tokens) => Ok(Some(tokens)),
+            Err(szafka::Error::OpenFileError(err))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err).context("read tokens file"),
+        }
+    }
+
+    fn save(&self, tokens: &Tokens) -> anyhow::Result<()> {
+        Ok(self.szafka.save(tokens)?)
+    }
+
+    fn remove(&self) -> anyhow::Result<()> {
+        Ok(self.szafka.remove()?)
+    }
+
+    fn location(&self) -> String {
+        self.szafka.path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("houseflow-{}-test-{}", name, rand::random::<u32>()))
+    }
+
+    fn tokens(suffix: &str) -> Tokens {
+        Tokens {
+            access: format!("access-{}", suffix),
+            refresh: format!("refresh-{}", suffix),
+        }
+    }
+
+    #[test]
+    fn encrypted_file() {
+        std::env::set_var(PASSWORD_ENV, "correct horse battery staple");
+        let path = temp_path("tokens");
+        let store = EncryptedFile::with_path(path.clone());
+        assert_eq!(store.get().unwrap(), None);
+
+        store.save(&tokens("first")).unwrap();
+        let first: Encrypted =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(hex::decode(&first.salt).unwrap().len(), 16);
+        assert_eq!(hex::decode(&first.nonce).unwrap().len(), 12);
+        assert!(!first.ciphertext.contains(&hex::encode("access-first")));
+        assert_eq!(store.get().unwrap(), Some(tokens("first")));
+
+        // every save uses a fresh salt and nonce
+        store.save(&tokens("second")).unwrap();
+        let second: Encrypted =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_ne!(first.salt, second.salt);
+        assert_ne!(first.nonce, second.nonce);
+        assert_eq!(store.get().unwrap(), Some(tokens("second")));
+
+        // the file is read again with a new store, which takes the password from the environment
+        assert_eq!(
+            EncryptedFile::with_path(path.clone()).get().unwrap(),
+            Some(tokens("second"))
+        );
+        std::env::set_var(PASSWORD_ENV, "wrong password");
+        assert!(EncryptedFile::with_path(path.clone()).get().is_err());
+
+        store.remove().unwrap();
+        assert_eq!(store.get().unwrap(), None);
+    }
+
+    #[test]
+    fn migrate_legacy_file() {
+        let legacy = File::with_path(temp_path("legacy-tokens"));
+        let store = File::with_path(temp_path("migrated-tokens"));
+        migrate(&legacy, &store).unwrap();
+        assert_eq!(store.get().unwrap(), None);
+
+        legacy.save(&tokens("legacy")).unwrap();
+        migrate(&legacy, &store).unwrap();
+        assert_eq!(store.get().unwrap(), Some(tokens("legacy")));
+        assert_eq!(legacy.get().unwrap(), None);
+
+        // tokens already in the store are newer than the legacy ones
+        legacy.save(&tokens("stale")).unwrap();
+        migrate(&legacy, &store).unwrap();
+        assert_eq!(store.get().unwrap(), Some(tokens("legacy")));
+        assert_eq!(legacy.get().unwrap(), None);
+        store.remove().unwrap();
+    }
+}
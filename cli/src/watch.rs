@@ -0,0 +1,121 @@
+use crate::context::Target;
+use crate::CommandContext;
+use async_trait::async_trait;
+use houseflow_types::accessory;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::Accessory;
+use houseflow_types::meta::Event;
+use houseflow_types::meta::EventFilter;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Format {
+    Json,
+    Yaml,
+    Table,
+}
+
+impl From<crate::output::Format> for Format {
+    fn from(format: crate::output::Format) -> Self {
+        match format {
+            crate::output::Format::Text => Self::Table,
+            crate::output::Format::Json => Self::Json,
+            crate::output::Format::Yaml => Self::Yaml,
+        }
+    }
+}
+
+pub struct Command {
+    pub filter: EventFilter,
+    pub format: Format,
+}
+
+/// Describes the characteristic in the same form as `meta write` takes it, e.g `current-temperature temperature=21.5`
+pub(crate) fn describe(characteristic: &Characteristic) -> String {
+    let mut fields = match serde_json::to_value(characteristic) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return format!("{:?}", characteristic),
+    };
+    let name = fields.remove("name").unwrap_or_default();
+    let values = fields
+        .into_iter()
+        .map(|(field, value)| match value {
+            serde_json::Value::String(value) => format!("{}={}", field, value),
+            value => format!("{}={}", field, value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{} {}", name.as_str().unwrap_or_default(), values)
+}
+
+fn print_row(time: &str, event: &str, accessory: &str, service: &str, value: &str) {
+    println!(
+        "{:<8}  {:<12}  {:<36}  {:<20}  {}",
+        time, event, accessory, service, value
+    );
+}
+
+#[async_trait]
+impl crate::Command for Command {
+    type Output = ();
+
+    async fn run(self, mut ctx: CommandContext) -> anyhow::Result<()> {
+        let mut events = match ctx.target {
+            Target::Server => ctx.session()?.watch(&self.filter).await??,
+            Target::Hub => ctx.hub_client()?.watch(&self.filter).await??,
+        };
+        // names of the accessories, known from their `Connected` events
+        let mut accessories: HashMap<accessory::ID, Accessory> = HashMap::new();
+        if self.format == Format::Table {
+            print_row("TIME", "EVENT", "ACCESSORY", "SERVICE", "VALUE");
+        }
+        while let Some(event) = events.next().await? {
+            match self.format {
+                Format::Json => {
+                    println!("{}", serde_json::to_string(&event)?);
+                    continue;
+                }
+                // every event is a separate YAML document, serde_yaml starts them with `---`
+                Format::Yaml => {
+                    print!("{}", serde_yaml::to_string(&event)?);
+                    continue;
+                }
+                Format::Table => {}
+            }
+            let time = chrono::Local::now().format("%H:%M:%S").to_string();
+            let name = |accessories: &HashMap<accessory::ID, Accessory>, id: &accessory::ID| {
+                accessories
+                    .get(id)
+                    .map(|accessory| format!("{} ({})", accessory.name, accessory.room_name))
+                    .unwrap_or_else(|| id.to_string())
+            };
+            match event {
+                Event::Connected { accessory } => {
+                    accessories.insert(accessory.id, accessory.clone());
+                    let name = name(&accessories, &accessory.id);
+                    print_row(&time, "connected", &name, "", "");
+                }
+                Event::Disconnected { accessory_id } => {
+                    let name = name(&accessories, &accessory_id);
+                    print_row(&time, "disconnected", &name, "", "");
+                }
+                Event::Updated {
+                    accessory_id,
+                    service_name,
+                    characteristic,
+                } => {
+                    let name = name(&accessories, &accessory_id);
+                    print_row(
+                        &time,
+                        "updated",
+                        &name,
+                        &service_name.to_string(),
+                        &describe(&characteristic),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
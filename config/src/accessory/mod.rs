@@ -1,6 +1,13 @@
 use crate::defaults;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
+use houseflow_types::accessory::capabilities::CharacteristicCapability;
+use houseflow_types::accessory::capabilities::Permissions;
+use houseflow_types::accessory::capabilities::ServiceCapability;
+use houseflow_types::accessory::capabilities::Unit;
+use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::protocol::Encoding;
 use serde::Deserialize;
 use serde::Serialize;
 use url::Url;
@@ -20,6 +27,9 @@ pub struct Config {
 pub struct Hub {
     #[serde(default = "defaults::hub_websocket_url")]
     pub url: Url,
+    /// Preferred encoding of the hive frames, falls back to JSON if the hub doesn't support it
+    #[serde(default)]
+    pub encoding: Encoding,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -35,17 +45,121 @@ pub struct Credentials {
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Services {
     pub temperature_sensor: Option<services::TemperatureSensor>,
+    pub thermostat: Option<services::Thermostat>,
+    pub air_quality_sensor: Option<services::AirQualitySensor>,
 }
 
 impl Services {
     pub fn names(&self) -> Vec<ServiceName> {
-        let Self { temperature_sensor } = &self;
+        let Self {
+            temperature_sensor,
+            thermostat,
+            air_quality_sensor,
+        } = &self;
         let mut names = vec![];
         if temperature_sensor.is_some() {
             names.push(ServiceName::TemperatureSensor);
         }
+        if thermostat.is_some() {
+            names.push(ServiceName::Thermostat);
+        }
+        if air_quality_sensor.is_some() {
+            names.push(ServiceName::AirQualitySensor);
+        }
         names
     }
+
+    /// Capabilities of the declared services, announced to the hub instead of the defaults of the accessory type.
+    pub fn capabilities(&self) -> Capabilities {
+        let Self {
+            temperature_sensor,
+            thermostat,
+            air_quality_sensor,
+        } = &self;
+        let mut services = vec![];
+        if temperature_sensor.is_some() {
+            services.push(ServiceCapability::new(
+                ServiceName::TemperatureSensor,
+                vec![CharacteristicCapability::new(
+                    CharacteristicName::CurrentTemperature,
+                    Permissions::READ_NOTIFY,
+                )
+                .with_unit(Unit::Celsius)],
+            ));
+        }
+        if thermostat.is_some() {
+            services.push(ServiceCapability::new(
+                ServiceName::Thermostat,
+                vec![
+                    CharacteristicCapability::new(
+                        CharacteristicName::CurrentTemperature,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_unit(Unit::Celsius),
+                    CharacteristicCapability::new(
+                        CharacteristicName::TargetTemperature,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(10.0, 38.0)
+                    .with_unit(Unit::Celsius),
+                    CharacteristicCapability::new(
+                        CharacteristicName::CurrentHeatingCoolingState,
+                        Permissions::READ_NOTIFY,
+                    ),
+                    CharacteristicCapability::new(
+                        CharacteristicName::TargetHeatingCoolingState,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                    CharacteristicCapability::new(
+                        CharacteristicName::TemperatureDisplayUnits,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                ],
+            ));
+        }
+        if let Some(air_quality_sensor) = air_quality_sensor {
+            let mut characteristics = vec![CharacteristicCapability::new(
+                CharacteristicName::AirQuality,
+                Permissions::READ_NOTIFY,
+            )];
+            if air_quality_sensor.carbon_dioxide_level.is_some() {
+                characteristics.push(
+                    CharacteristicCapability::new(
+                        CharacteristicName::CarbonDioxideLevel,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_unit(Unit::PartsPerMillion),
+                );
+            }
+            let densities = [
+                (
+                    CharacteristicName::Pm25Density,
+                    &air_quality_sensor.pm25_density,
+                ),
+                (
+                    CharacteristicName::Pm10Density,
+                    &air_quality_sensor.pm10_density,
+                ),
+                (
+                    CharacteristicName::VocDensity,
+                    &air_quality_sensor.voc_density,
+                ),
+            ];
+            for (name, density) in densities {
+                if density.is_some() {
+                    characteristics.push(
+                        CharacteristicCapability::new(name, Permissions::READ_NOTIFY)
+                            .with_unit(Unit::MicrogramsPerCubicMeter),
+                    );
+                }
+            }
+            services.push(ServiceCapability::new(
+                ServiceName::AirQualitySensor,
+                characteristics,
+            ));
+        }
+        Capabilities { services }
+    }
 }
 
 pub mod services {
@@ -58,6 +172,24 @@ pub mod services {
     pub struct TemperatureSensor {
         pub current_temperature: characteristics::CurrentTemperature,
     }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct Thermostat {
+        pub current_temperature: characteristics::CurrentTemperature,
+        pub target_temperature: characteristics::TargetTemperature,
+        pub target_heating_cooling_state: characteristics::TargetHeatingCoolingState,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct AirQualitySensor {
+        pub air_quality: characteristics::AirQuality,
+        pub carbon_dioxide_level: Option<characteristics::CarbonDioxideLevel>,
+        pub pm25_density: Option<characteristics::Density>,
+        pub pm10_density: Option<characteristics::Density>,
+        pub voc_density: Option<characteristics::Density>,
+    }
 }
 
 pub mod characteristics {
@@ -75,6 +207,56 @@ pub mod characteristics {
         #[serde_as(as = "DurationSeconds<u64>")]
         pub interval: Duration,
     }
+
+    /// Overall air quality, one of `unknown`, `excellent`, `good`, `fair`, `inferior` or `poor`.
+    #[serde_with::serde_as]
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct AirQuality {
+        pub command: Command,
+        #[serde_as(as = "DurationSeconds<u64>")]
+        pub interval: Duration,
+    }
+
+    /// Carbon dioxide concentration in parts per million.
+    #[serde_with::serde_as]
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct CarbonDioxideLevel {
+        pub command: Command,
+        #[serde_as(as = "DurationSeconds<u64>")]
+        pub interval: Duration,
+    }
+
+    /// Density of particulate matter or volatile organic compounds in µg/m³.
+    #[serde_with::serde_as]
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct Density {
+        pub command: Command,
+        #[serde_as(as = "DurationSeconds<u64>")]
+        pub interval: Duration,
+    }
+
+    /// Target temperature in degrees Celsius.
+    ///
+    /// `write` receives the new temperature in the `$VALUE` environment variable, avoid `${VALUE}` as it is substituted when the configuration is parsed.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct TargetTemperature {
+        pub read: Command,
+        pub write: Command,
+    }
+
+    /// Mode of the thermostat, one of `off`, `heat`, `cool` or `auto`.
+    ///
+    /// `write` receives the new mode in the `$VALUE` environment variable.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct TargetHeatingCoolingState {
+        pub read: Command,
+        pub write: Command,
+    }
 }
 
 impl crate::Config for Config {
@@ -100,6 +282,7 @@ impl Default for Hub {
     fn default() -> Self {
         Self {
             url: defaults::hub_websocket_url(),
+            encoding: Encoding::default(),
         }
     }
 }
@@ -107,8 +290,10 @@ impl Default for Hub {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Command;
     use crate::Config as _;
     use houseflow_types::accessory;
+    use std::time::Duration;
     use url::Url;
 
     #[test]
@@ -120,8 +305,41 @@ mod tests {
             },
             hub: Hub {
                 url: Url::parse("wss://example.com:1234/hello/world").unwrap(),
+                encoding: Encoding::MessagePack,
+            },
+            services: Services {
+                temperature_sensor: None,
+                thermostat: Some(services::Thermostat {
+                    current_temperature: characteristics::CurrentTemperature {
+                        command: Command::from("cat /tmp/thermostat/current"),
+                        interval: Duration::from_secs(10),
+                    },
+                    target_temperature: characteristics::TargetTemperature {
+                        read: Command::from("cat /tmp/thermostat/target"),
+                        write: Command::from("echo $VALUE > /tmp/thermostat/target"),
+                    },
+                    target_heating_cooling_state: characteristics::TargetHeatingCoolingState {
+                        read: Command::from("cat /tmp/thermostat/mode"),
+                        write: Command::from("echo $VALUE > /tmp/thermostat/mode"),
+                    },
+                }),
+                air_quality_sensor: Some(services::AirQualitySensor {
+                    air_quality: characteristics::AirQuality {
+                        command: Command::from("cat /tmp/air/quality"),
+                        interval: Duration::from_secs(60),
+                    },
+                    carbon_dioxide_level: Some(characteristics::CarbonDioxideLevel {
+                        command: Command::from("cat /tmp/air/co2"),
+                        interval: Duration::from_secs(30),
+                    }),
+                    pm25_density: Some(characteristics::Density {
+                        command: Command::from("cat /tmp/air/pm25"),
+                        interval: Duration::from_secs(60),
+                    }),
+                    pm10_density: None,
+                    voc_density: None,
+                }),
             },
-            services: Services::default(),
         };
 
         std::env::set_var("HUB_PORT", expected.hub.url.port().unwrap().to_string());
@@ -1,13 +1,56 @@
 use crate::defaults;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use url::Url;
 
+/// Name of the profile used when `--profile` is not passed
+pub const DEFAULT_PROFILE: &str = "default";
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
     pub server: Server,
+    /// Hub used with `--target hub`, e.g to control the accessories over LAN while the server is unreachable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hub: Option<Hub>,
+    #[serde(default)]
+    pub tokens: Tokens,
+    /// Profiles selected with `--profile`, each of them is logged in separately
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Tokens {
+    #[serde(default)]
+    pub store: TokenStore,
+}
+
+/// Where the access and refresh tokens are kept
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenStore {
+    /// Keyring of the operating system, e.g Secret Service on Linux. Falls back to `encrypted-file` if it's not available.
+    #[default]
+    Keyring,
+    /// File encrypted with a password
+    EncryptedFile,
+    /// Plain-text file
+    File,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Profile {
+    /// Server of the profile, defaults to the top-level one
+    #[serde(default)]
+    pub server: Option<Server>,
+    /// Hub of the profile, defaults to the top-level one
+    #[serde(default)]
+    pub hub: Option<Hub>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -25,6 +68,30 @@ impl Default for Server {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Hub {
+    pub url: Url,
+}
+
+impl Config {
+    /// Server used by the profile, profiles which are not defined use the top-level server
+    pub fn server(&self, profile: &str) -> &Server {
+        self.profiles
+            .get(profile)
+            .and_then(|profile| profile.server.as_ref())
+            .unwrap_or(&self.server)
+    }
+
+    /// Hub used by the profile, profiles without a hub use the top-level one
+    pub fn hub(&self, profile: &str) -> Option<&Hub> {
+        self.profiles
+            .get(profile)
+            .and_then(|profile| profile.hub.as_ref())
+            .or(self.hub.as_ref())
+    }
+}
+
 impl crate::Config for Config {
     const DEFAULT_TOML: &'static str = include_str!("default.toml");
 
@@ -46,17 +113,44 @@ impl crate::Config for Config {
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
-    use super::Server;
+    use super::*;
     use crate::Config as _;
     use url::Url;
 
     #[test]
     fn test_example() {
+        let staging = Server {
+            url: Url::parse("https://staging.example.com:1234").unwrap(),
+        };
+        let work_hub = Hub {
+            url: Url::parse("http://10.0.0.5:5001").unwrap(),
+        };
         let expected = Config {
             server: Server {
                 url: Url::parse("https://example.com:1234/hello/world").unwrap(),
             },
+            hub: Some(Hub {
+                url: Url::parse("http://192.168.1.10:5001").unwrap(),
+            }),
+            tokens: Tokens {
+                store: TokenStore::EncryptedFile,
+            },
+            profiles: BTreeMap::from([
+                (
+                    String::from("staging"),
+                    Profile {
+                        server: Some(staging.clone()),
+                        hub: None,
+                    },
+                ),
+                (
+                    String::from("work"),
+                    Profile {
+                        server: None,
+                        hub: Some(work_hub.clone()),
+                    },
+                ),
+            ]),
         };
         std::env::set_var(
             "SERVER_PORT",
@@ -64,5 +158,10 @@ mod tests {
         );
         let config = Config::parse(include_str!("example.toml")).unwrap();
         assert_eq!(config, expected);
+        assert_eq!(config.server("staging"), &staging);
+        assert_eq!(config.server("work"), &expected.server);
+        assert_eq!(config.server(DEFAULT_PROFILE), &expected.server);
+        assert_eq!(config.hub("staging"), expected.hub.as_ref());
+        assert_eq!(config.hub("work"), Some(&work_hub));
     }
 }
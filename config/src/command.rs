@@ -9,6 +9,14 @@ impl Command {
         self.command().output().map(|v| v.stdout)
     }
 
+    /// Executes the command with `value` passed in the `VALUE` environment variable.
+    pub fn execute_with_value(&self, value: impl ToString) -> Result<Vec<u8>, std::io::Error> {
+        self.command()
+            .env("VALUE", value.to_string())
+            .output()
+            .map(|v| v.stdout)
+    }
+
     pub fn command(&self) -> std::process::Command {
         let mut command = std::process::Command::new("bash");
         command.arg("-c");
@@ -16,3 +24,9 @@ impl Command {
         command
     }
 }
+
+impl From<&str> for Command {
+    fn from(command: &str) -> Self {
+        Self(command.to_string())
+    }
+}
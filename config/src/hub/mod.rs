@@ -1,4 +1,5 @@
 use crate::defaults;
+use crate::ValidationError;
 use houseflow_types::accessory;
 use houseflow_types::hub;
 use serde::Deserialize;
@@ -91,6 +92,7 @@ pub struct Controllers {
 }
 
 pub mod controllers {
+    use houseflow_types::protocol::Encoding;
     use serde::Deserialize;
     use serde::Serialize;
     use url::Url;
@@ -109,6 +111,9 @@ pub mod controllers {
     pub struct Lighthouse {
         pub password: String,
         pub url: Url,
+        /// Preferred encoding of the lighthouse frames, falls back to JSON if the server doesn't support it
+        #[serde(default)]
+        pub encoding: Encoding,
     }
 
     #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -124,6 +129,73 @@ impl crate::Config for Config {
     fn preprocess(&mut self) -> Result<(), String> {
         Ok(())
     }
+
+    fn errors(&self) -> Vec<ValidationError> {
+        let mac_address = regex::Regex::new(r"^([0-9A-Fa-f]{2}:){5}[0-9A-Fa-f]{2}$").unwrap();
+        let mut errors = Vec::new();
+        for (index, accessory) in self.accessories.iter().enumerate() {
+            if self.accessories[..index]
+                .iter()
+                .any(|other| other.id == accessory.id)
+            {
+                errors.push(ValidationError::new(
+                    format!("accessories[{}].id", index),
+                    format!("accessory with id {} is already defined", accessory.id),
+                ));
+            }
+            match &accessory.mac_address {
+                Some(address) if !mac_address.is_match(address) => {
+                    errors.push(ValidationError::new(
+                        format!("accessories[{}].mac-address", index),
+                        format!(
+                            "malformed MAC address {}, expected XX:XX:XX:XX:XX:XX",
+                            address
+                        ),
+                    ))
+                }
+                None if matches!(accessory.r#type, accessory::Type::XiaomiMijia(_)) => {
+                    errors.push(ValidationError::new(
+                        format!("accessories[{}]", index),
+                        "Xiaomi Mijia accessories require `mac-address`",
+                    ))
+                }
+                _ => {}
+            }
+        }
+        errors
+    }
+}
+
+impl Config {
+    /// Reports controllers and providers that are configured, but the hub is not compiled with their cargo features, e.g `controllers-hap`.
+    pub fn unsupported_components(&self, features: &[&str]) -> Vec<ValidationError> {
+        let components = [
+            ("controllers", "hap", self.controllers.hap.is_some()),
+            ("controllers", "meta", self.controllers.meta.is_some()),
+            (
+                "controllers",
+                "lighthouse",
+                self.controllers.lighthouse.is_some(),
+            ),
+            ("providers", "hive", self.providers.hive.is_some()),
+            ("providers", "mijia", self.providers.mijia.is_some()),
+        ];
+        components
+            .into_iter()
+            .filter(|(prefix, name, configured)| {
+                *configured && !features.contains(&format!("{}-{}", prefix, name).as_str())
+            })
+            .map(|(prefix, name, _)| {
+                ValidationError::new(
+                    format!("{}.{}", prefix, name),
+                    format!(
+                        "hub is not compiled with `{}-{}` feature enabled",
+                        prefix, name
+                    ),
+                )
+            })
+            .collect()
+    }
 }
 
 impl Default for Network {
@@ -142,6 +214,7 @@ mod tests {
     use super::*;
     use crate::Config as _;
     use houseflow_types::accessory;
+    use houseflow_types::protocol::Encoding;
     use url::Url;
 
     #[test]
@@ -175,6 +248,7 @@ mod tests {
                 lighthouse: Some(controllers::Lighthouse {
                     url: Url::parse("http://lighthouse").unwrap(),
                     password: String::from("hard-password"),
+                    encoding: Encoding::MessagePack,
                 }),
                 meta: Some(controllers::Meta {}),
             },
@@ -186,5 +260,62 @@ mod tests {
         );
         let config = Config::parse(include_str!("example.toml")).unwrap();
         assert_eq!(config, expected);
+        assert_eq!(config.errors(), vec![]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let source = r#"
+[hub]
+id = "2adc257a-394c-49bd-ae97-4c5a98b49d84"
+
+[[accessories]]
+id = "37c6a8bd-264c-4653-a641-c9b574207be5"
+name = "Thermometer"
+room-name = "Bedroom"
+manufacturer = "xiaomi-mijia"
+model = "hygro-thermometer"
+
+[[accessories]]
+id = "37c6a8bd-264c-4653-a641-c9b574207be5"
+name = "Thermometer"
+room-name = "Kitchen"
+manufacturer = "xiaomi-mijia"
+model = "hygro-thermometer"
+mac-address = "A4:C1:38:EF:77"
+
+[controllers.hap]
+pin = "12345678"
+name = "Awesome Hub"
+
+[controllers.meta]
+"#;
+        let config: Config = toml::from_str(source).unwrap();
+        let paths = config
+            .errors()
+            .into_iter()
+            .map(|err| err.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "accessories[0]",
+                "accessories[1].id",
+                "accessories[1].mac-address"
+            ]
+        );
+        let unsupported = config.unsupported_components(&["controllers-meta"]);
+        assert_eq!(
+            unsupported,
+            vec![ValidationError::new(
+                "controllers.hap",
+                "hub is not compiled with `controllers-hap` feature enabled"
+            )]
+        );
+        let lines = crate::validation::check::<Config>(source)
+            .into_iter()
+            .map(|diagnostic| diagnostic.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![Some(5), Some(13), Some(18)]);
     }
 }
@@ -1,7 +1,9 @@
 pub mod command;
 pub mod defaults;
+pub mod validation;
 
 pub use command::Command;
+pub use validation::ValidationError;
 
 #[cfg(feature = "log")]
 pub mod log;
@@ -29,8 +31,21 @@ pub trait Config: serde::de::DeserializeOwned + serde::ser::Serialize {
         Ok(())
     }
 
+    /// Reports all the problems of the configuration, which can't be caught while deserializing
+    fn errors(&self) -> Vec<ValidationError> {
+        Vec::new()
+    }
+
     fn validate(&self) -> Result<(), String> {
-        Ok(())
+        let errors = self.errors();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "))
     }
 
     #[cfg(feature = "fs")]
@@ -86,6 +101,14 @@ pub trait Config: serde::de::DeserializeOwned + serde::ser::Serialize {
         Self::parse(&content)
     }
 
+    /// Writes the whole configuration to the file, replacing its content.
+    #[cfg(feature = "fs")]
+    fn write(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        let content = toml::to_string(self)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
     fn default_path() -> std::path::PathBuf {
         xdg::BaseDirectories::with_prefix("houseflow")
             .unwrap()
@@ -104,6 +127,9 @@ pub enum Error {
     TomlSerialize(#[from] toml::ser::Error),
     #[error("validation: {0}")]
     Validation(String),
+    #[cfg(any(test, feature = "server"))]
+    #[error("toml edit: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
 }
 
 #[allow(dead_code)]
@@ -1,4 +1,5 @@
 use crate::defaults;
+use crate::ValidationError;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -112,15 +113,41 @@ pub mod mailers {
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Controllers {
     pub meta: Option<controllers::Meta>,
+    pub google: Option<controllers::Google>,
 }
 
 pub mod controllers {
+    use houseflow_types::structure;
     use serde::Deserialize;
     use serde::Serialize;
 
     #[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
     #[serde(rename_all = "kebab-case", deny_unknown_fields)]
     pub struct Meta {}
+
+    #[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct Google {
+        /// PINs which have to be entered to unlock the locks of the structures, structures without one only ask to confirm unlocking
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub pins: Vec<StructurePin>,
+    }
+
+    impl Google {
+        pub fn get_pin(&self, structure_id: &structure::ID) -> Option<&str> {
+            self.pins
+                .iter()
+                .find(|pin| pin.structure_id == *structure_id)
+                .map(|pin| pin.pin.as_str())
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    pub struct StructurePin {
+        pub structure_id: structure::ID,
+        pub pin: String,
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -148,6 +175,9 @@ pub mod providers {
     pub struct LighthouseHub {
         pub id: hub::ID,
         pub name: String,
+        /// Argon2 hash of the hub password, in the PHC string format.
+        ///
+        /// Hex-encoded SHA-256 hashes used by older versions are still accepted, and replaced with Argon2 ones when the hub connects.
         pub password_hash: String,
         pub structure_id: structure::ID,
     }
@@ -173,27 +203,52 @@ impl crate::Config for Config {
 
     const DEFAULT_FILE: &'static str = "server.toml";
 
-    fn validate(&self) -> Result<(), String> {
-        for permission in &self.permissions {
-            if !self
-                .structures
-                .iter()
-                .any(|structure| structure.id == permission.structure_id)
-            {
-                return Err(format!(
-                    "Couldn't find structure with id: {} for permission: {:?}",
-                    permission.structure_id, permission
+    fn errors(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        for (index, permission) in self.permissions.iter().enumerate() {
+            if self.get_structure(&permission.structure_id).is_none() {
+                errors.push(ValidationError::new(
+                    format!("permissions[{}].structure-id", index),
+                    format!(
+                        "couldn't find structure with id {}",
+                        permission.structure_id
+                    ),
                 ));
             }
-            if !self.users.iter().any(|user| user.id == permission.user_id) {
-                return Err(format!(
-                    "Couldn't find user with id: {} for permission: {:?}",
-                    permission.user_id, permission
+            if self.get_user(&permission.user_id).is_none() {
+                errors.push(ValidationError::new(
+                    format!("permissions[{}].user-id", index),
+                    format!("couldn't find user with id {}", permission.user_id),
                 ));
             }
         }
-
-        Ok(())
+        let hubs = self
+            .providers
+            .lighthouse
+            .iter()
+            .flat_map(|lighthouse| &lighthouse.hubs);
+        for (index, hub) in hubs.enumerate() {
+            if self.get_structure(&hub.structure_id).is_none() {
+                errors.push(ValidationError::new(
+                    format!("providers.lighthouse.hubs[{}].structure-id", index),
+                    format!("couldn't find structure with id {}", hub.structure_id),
+                ));
+            }
+        }
+        let pins = self
+            .controllers
+            .google
+            .iter()
+            .flat_map(|google| &google.pins);
+        for (index, pin) in pins.enumerate() {
+            if self.get_structure(&pin.structure_id).is_none() {
+                errors.push(ValidationError::new(
+                    format!("controllers.google.pins[{}].structure-id", index),
+                    format!("couldn't find structure with id {}", pin.structure_id),
+                ));
+            }
+        }
+        errors
     }
 
     fn preprocess(&mut self) -> Result<(), String> {
@@ -281,6 +336,60 @@ impl Config {
             }).collect()
     }
 
+    /// Replaces the sections managed with the admin API in the content of the configuration file,
+    /// those are the structures, users, permissions and the hubs of the lighthouse provider.
+    ///
+    /// Rest of the content is kept as it is, including the comments and the references to environment variables.
+    pub fn update_managed(&self, content: &str) -> Result<String, crate::Error> {
+        /// Sections managed with the admin API, hubs are moved to the lighthouse provider afterwards
+        #[derive(Serialize)]
+        struct Managed<'a> {
+            structures: &'a [Structure],
+            users: &'a [User],
+            permissions: &'a [Permission],
+            hubs: &'a [providers::LighthouseHub],
+        }
+
+        let hubs = self
+            .providers
+            .lighthouse
+            .as_ref()
+            .map(|lighthouse| lighthouse.hubs.as_slice())
+            .unwrap_or_default();
+        // converted to a value first, which orders the empty arrays before the tables
+        let managed = toml::Value::try_from(Managed {
+            structures: &self.structures,
+            users: &self.users,
+            permissions: &self.permissions,
+            hubs,
+        })?;
+        let managed = toml::to_string(&managed)?.parse::<toml_edit::Document>()?;
+        let mut document = content.parse::<toml_edit::Document>()?;
+        for key in ["structures", "users", "permissions"] {
+            replace(document.as_table_mut(), key, managed.get(key), usize::MAX);
+        }
+        if let Some(lighthouse) = document
+            .get_mut("providers")
+            .and_then(|providers| providers.get_mut("lighthouse"))
+            .and_then(toml_edit::Item::as_table_mut)
+        {
+            let position = lighthouse.position().unwrap_or(usize::MAX);
+            replace(lighthouse, "hubs", managed.get("hubs"), position);
+        }
+        Ok(document.to_string())
+    }
+
+    /// Writes the sections managed with the admin API to the configuration file, see `update_managed`.
+    ///
+    /// Unlike `crate::Config::write`, secrets referenced with environment variables are never written expanded.
+    #[cfg(feature = "fs")]
+    pub fn write_managed(&self, path: impl AsRef<std::path::Path>) -> Result<(), crate::Error> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        std::fs::write(path, self.update_managed(&content)?)?;
+        Ok(())
+    }
+
     pub fn get_base_url(&self) -> Url {
         self.network.base_url.clone().unwrap_or_else(|| {
             let (scheme, address, port) = if let Some(tls) = &self.tls {
@@ -293,6 +402,58 @@ impl Config {
     }
 }
 
+/// Replaces the item of the table, new tables take the position of the replaced ones in the document, or the given one if there were none
+fn replace(
+    table: &mut toml_edit::Table,
+    key: &str,
+    item: Option<&toml_edit::Item>,
+    position: usize,
+) {
+    let item = match item.filter(|item| !is_empty(item)) {
+        Some(item) => item,
+        None => {
+            table.remove(key);
+            return;
+        }
+    };
+    let position = table.get(key).and_then(position_of).unwrap_or(position);
+    let mut item = item.clone();
+    place(&mut item, position);
+    table.insert(key, item);
+}
+
+fn is_empty(item: &toml_edit::Item) -> bool {
+    match item {
+        toml_edit::Item::ArrayOfTables(tables) => tables.is_empty(),
+        toml_edit::Item::Value(toml_edit::Value::Array(array)) => array.is_empty(),
+        _ => false,
+    }
+}
+
+fn position_of(item: &toml_edit::Item) -> Option<usize> {
+    match item {
+        toml_edit::Item::Table(table) => table.position(),
+        toml_edit::Item::ArrayOfTables(tables) => tables.iter().next()?.position(),
+        _ => None,
+    }
+}
+
+/// Moves the tables of the item to the position in the document, tables at the same position keep their order
+fn place(item: &mut toml_edit::Item, position: usize) {
+    let tables = match item {
+        toml_edit::Item::Table(table) => vec![table],
+        toml_edit::Item::ArrayOfTables(tables) => tables.iter_mut().collect(),
+        _ => return,
+    };
+    for table in tables {
+        table.set_position(position);
+        table.decor_mut().set_prefix("\n");
+        for (_, item) in table.iter_mut() {
+            place(item, position);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,13 +502,22 @@ mod tests {
             },
             controllers: Controllers {
                 meta: Some(controllers::Meta {}),
+                google: Some(controllers::Google {
+                    pins: vec![controllers::StructurePin {
+                        structure_id: structure::ID::from_str("bd7feab5033940e296ed7fcdc700ba65")
+                            .unwrap(),
+                        pin: String::from("1234"),
+                    }],
+                }),
             },
             providers: Providers {
                 lighthouse: Some(providers::Lighthouse {
                     hubs: [providers::LighthouseHub {
                         id: hub::ID::from_str("c3b846ed-74f1-4fd9-90d2-e6c2669dfaa6").unwrap(),
                         name: String::from("Simple Hub"),
-                        password_hash: String::from("some-password-hash"),
+                        password_hash: String::from(
+                            "$argon2id$v=19$m=19456,t=2,p=1$0OHNZcH+taYiA/rKj1PkEQ$q44ACL2cRjLNRDdSPFxUxQHfosNfYhOuEG+crs4FeJY",
+                        ),
                         structure_id: structure::ID::from_str("bd7feab5033940e296ed7fcdc700ba65")
                             .unwrap(),
                     }]
@@ -369,6 +539,10 @@ mod tests {
                 username: String::from("gbaranski"),
                 email: lettre::Address::from_str("root@gbaranski.com").unwrap(),
                 admin: false,
+                preferences: user::Preferences {
+                    measurement_system: user::MeasurementSystem::Imperial,
+                    temperature_units: None,
+                },
             }]
             .to_vec(),
             permissions: [Permission {
@@ -394,5 +568,87 @@ mod tests {
         let config = Config::parse(include_str!("example.toml")).unwrap();
         assert_eq!(config, expected);
         crate::Config::validate(&config).unwrap();
+        let serialized = toml::to_string(&config).unwrap();
+        assert_eq!(Config::parse(&serialized).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_update_managed() {
+        let content = r#"
+# keys are kept in the environment
+[secrets]
+refresh-key = "${REFRESH_KEY}"
+access-key = "${ACCESS_KEY}"
+authorization-code-key = "${AUTHORIZATION_CODE_KEY}"
+
+[mailers.dummy]
+
+[providers.lighthouse]
+
+[[structures]]
+id = "bd7feab5033940e296ed7fcdc700ba65"
+name = "Zukago"
+"#;
+        let structure = Structure {
+            id: structure::ID::from_str("bd7feab5033940e296ed7fcdc700ba65").unwrap(),
+            name: String::from("Zukago"),
+        };
+        let user = User {
+            id: user::ID::from_str("861ccceaa3e349138ce2498768dbfe09").unwrap(),
+            username: String::from("gbaranski"),
+            email: lettre::Address::from_str("root@gbaranski.com").unwrap(),
+            admin: false,
+            preferences: Default::default(),
+        };
+        let config = Config {
+            network: Default::default(),
+            secrets: Secrets {
+                refresh_key: String::from("expanded-refresh-key"),
+                access_key: String::from("expanded-access-key"),
+                authorization_code_key: String::from("expanded-authorization-code-key"),
+            },
+            tls: None,
+            mailers: Mailers {
+                smtp: None,
+                dummy: Some(mailers::Dummy {}),
+            },
+            controllers: Default::default(),
+            providers: Providers {
+                lighthouse: Some(providers::Lighthouse {
+                    hubs: vec![providers::LighthouseHub {
+                        id: hub::ID::from_str("c3b846ed74e0413288e8d59d90d3ab48").unwrap(),
+                        name: String::from("Simple Hub"),
+                        password_hash: String::from("hash"),
+                        structure_id: structure.id,
+                    }],
+                }),
+            },
+            logins: Default::default(),
+            structures: vec![structure.clone()],
+            users: vec![user.clone()],
+            permissions: vec![Permission {
+                structure_id: structure.id,
+                user_id: user.id,
+                is_manager: true,
+            }],
+        };
+
+        let updated = config.update_managed(content).unwrap();
+        assert!(updated.contains("# keys are kept in the environment"));
+        assert!(updated.contains(r#"refresh-key = "${REFRESH_KEY}""#));
+        assert!(!updated.contains("expanded"));
+        let mut parsed: Config = toml::from_str(&updated).unwrap();
+        parsed.secrets = config.secrets.clone();
+        assert_eq!(parsed, config);
+
+        let removed = Config {
+            users: Vec::new(),
+            permissions: Vec::new(),
+            ..config.clone()
+        };
+        let updated = removed.update_managed(&updated).unwrap();
+        let mut parsed: Config = toml::from_str(&updated).unwrap();
+        parsed.secrets = config.secrets.clone();
+        assert_eq!(parsed, removed);
     }
 }
@@ -0,0 +1,165 @@
+use regex::Regex;
+
+/// Invalid field of a configuration
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{path}: {message}")]
+pub struct ValidationError {
+    /// Path to the field in TOML notation, e.g `accessories[1].mac-address`
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Problem found in a configuration file
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Diagnostic {
+    /// Line of the problem starting from 1, `None` if it couldn't be located
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Parses and validates the configuration, reporting every problem instead of stopping at the first one.
+pub fn check<T: crate::Config>(source: &str) -> Vec<Diagnostic> {
+    check_with::<T>(source, |_| Vec::new())
+}
+
+/// Same as [`check`], but also reports the errors of the additional checks.
+pub fn check_with<T: crate::Config>(
+    source: &str,
+    additional: impl FnOnce(&T) -> Vec<ValidationError>,
+) -> Vec<Diagnostic> {
+    let re = Regex::new(r"\$\{([a-zA-Z_]+)}").unwrap();
+    let undefined = re
+        .captures_iter(source)
+        .map(|caps| caps.get(1).unwrap())
+        .filter(|name| std::env::var(name.as_str()).is_err())
+        .map(|name| Diagnostic {
+            line: Some(line_of(source, name.start())),
+            message: format!(
+                "environment variable {} is not defined or not valid unicode",
+                name.as_str()
+            ),
+        })
+        .collect::<Vec<_>>();
+    if !undefined.is_empty() {
+        return undefined;
+    }
+    let source = re.replace_all(source, |caps: &regex::Captures| {
+        std::env::var(&caps[1]).unwrap()
+    });
+    let config: T = match toml::from_str(&source) {
+        Ok(config) => config,
+        Err(err) => {
+            return vec![Diagnostic {
+                line: err.line_col().map(|(line, _)| line + 1),
+                message: err.to_string(),
+            }]
+        }
+    };
+    let mut errors = config.errors();
+    errors.extend(additional(&config));
+    errors
+        .into_iter()
+        .map(|err| Diagnostic {
+            line: locate(&source, &err.path),
+            message: err.to_string(),
+        })
+        .collect()
+}
+
+fn line_of(source: &str, position: usize) -> usize {
+    source[..position].matches('\n').count() + 1
+}
+
+/// Finds the line of the field, falls back to the header of its table if the field is not set explicitly.
+///
+/// Only standard tables, arrays of tables and plain keys are supported, inline tables and dotted keys are not.
+pub fn locate(source: &str, path: &str) -> Option<usize> {
+    let (table, key) = match path.rsplit_once('.') {
+        Some((table, key)) if !key.contains('[') => (table, Some(key)),
+        _ => (path, None),
+    };
+    let mut current = String::new();
+    let mut counters = std::collections::HashMap::<String, usize>::new();
+    let mut header = None;
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if let Some(name) = line
+            .strip_prefix("[[")
+            .and_then(|line| line.split_once("]]"))
+            .map(|(name, _)| name.trim())
+        {
+            let counter = counters.entry(name.to_string()).or_default();
+            current = format!("{}[{}]", name, counter);
+            *counter += 1;
+        } else if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.split_once(']'))
+            .map(|(name, _)| name.trim())
+        {
+            current = name.to_string();
+        } else if let Some(key) = key.filter(|_| current == table) {
+            if matches!(line.strip_prefix(key), Some(rest) if rest.trim_start().starts_with('=')) {
+                return Some(index + 1);
+            }
+            continue;
+        } else {
+            continue;
+        }
+        if current == path {
+            return Some(index + 1);
+        }
+        if current == table && header.is_none() {
+            header = Some(index + 1);
+        }
+    }
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+[hub]
+id = "2adc257a-394c-49bd-ae97-4c5a98b49d84"
+
+[[accessories]]
+id = "37c6a8bd-264c-4653-a641-c9b574207be5"
+
+[[accessories]]
+id = "37c6a8bd-264c-4653-a641-c9b574207be5"
+mac-address = "A4:C1:38"
+
+[controllers.hap]
+pin = "12345678"
+"#;
+
+    #[test]
+    fn locate_fields() {
+        assert_eq!(locate(SOURCE, "hub.id"), Some(3));
+        assert_eq!(locate(SOURCE, "accessories[0].id"), Some(6));
+        assert_eq!(locate(SOURCE, "accessories[1].mac-address"), Some(10));
+        assert_eq!(locate(SOURCE, "accessories[1]"), Some(8));
+        assert_eq!(locate(SOURCE, "accessories[0].mac-address"), Some(5));
+        assert_eq!(locate(SOURCE, "controllers.hap"), Some(12));
+        assert_eq!(locate(SOURCE, "controllers.meta"), None);
+    }
+}
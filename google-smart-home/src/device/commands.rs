@@ -55,6 +55,16 @@ pub struct Hsv {
     pub value: f64,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockUnlock {
+    /// True when command is to lock, false to unlock.
+    pub lock: bool,
+    /// Google-provided token for follow-up response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up_token: Option<String>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OnOff {
@@ -65,4 +75,53 @@ pub struct OnOff {
 #[serde(rename_all = "camelCase")]
 pub struct OpenClose {
     pub open_percent: u8,
+    /// Direction in which to open, only for devices supporting multiple directions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_direction: Option<OpenDirection>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OpenDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+    In,
+    Out,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SetFanSpeed {
+    /// Speed as a percentage of the maximum speed.
+    #[serde(rename_all = "camelCase")]
+    Percent { fan_speed_percent: u8 },
+    /// Name of one of the speeds listed in the `availableFanSpeeds` attribute.
+    #[serde(rename_all = "camelCase")]
+    Setting { fan_speed: String },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartStop {
+    /// True to start device operation, false to stop.
+    pub start: bool,
+    /// Zone in which to start running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThermostatTemperatureSetpoint {
+    /// Target temperature setpoint in degrees Celsius.
+    pub thermostat_temperature_setpoint: f64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThermostatSetMode {
+    /// Target mode, one of the modes listed in the `availableThermostatModes` attribute.
+    pub thermostat_mode: String,
 }
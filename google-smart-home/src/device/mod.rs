@@ -17,8 +17,18 @@ pub enum Command {
     BrightnessRelative(commands::BrightnessRelative),
     #[serde(rename = "action.devices.commands.ColorAbsolute")]
     ColorAbsolute(commands::ColorAbsolute),
+    #[serde(rename = "action.devices.commands.LockUnlock")]
+    LockUnlock(commands::LockUnlock),
     #[serde(rename = "action.devices.commands.OnOff")]
     OnOff(commands::OnOff),
     #[serde(rename = "action.devices.commands.OpenClose")]
     OpenClose(commands::OpenClose),
+    #[serde(rename = "action.devices.commands.SetFanSpeed")]
+    SetFanSpeed(commands::SetFanSpeed),
+    #[serde(rename = "action.devices.commands.StartStop")]
+    StartStop(commands::StartStop),
+    #[serde(rename = "action.devices.commands.ThermostatTemperatureSetpoint")]
+    ThermostatTemperatureSetpoint(commands::ThermostatTemperatureSetpoint),
+    #[serde(rename = "action.devices.commands.ThermostatSetMode")]
+    ThermostatSetMode(commands::ThermostatSetMode),
 }
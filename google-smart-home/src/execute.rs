@@ -42,6 +42,21 @@ pub mod request {
         /// The command to execute, usually with accompanying parameters.
         #[serde(flatten)]
         pub command: Command,
+        /// Response to a two-factor challenge requested in a previous EXECUTE response.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub challenge: Option<Challenge>,
+    }
+
+    /// Two-factor authentication provided by the user.
+    #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Challenge {
+        /// Whether the user acknowledged the command.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub ack: Option<bool>,
+        /// PIN entered by the user.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub pin: Option<String>,
     }
 }
 
@@ -88,6 +103,27 @@ pub mod response {
         /// Expanding ERROR state if needed from the preset error codes, which will map to the errors presented to users.
         #[serde(skip_serializing_if = "Option::is_none")]
         pub error_code: Option<String>,
+        /// Two-factor challenge that the user has to pass, used together with the `challengeNeeded` error code.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub challenge_needed: Option<ChallengeNeeded>,
+    }
+
+    /// Two-factor challenge requested from the user.
+    #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ChallengeNeeded {
+        pub r#type: ChallengeType,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub enum ChallengeType {
+        /// User has to confirm the command.
+        AckNeeded,
+        /// User has to enter the PIN.
+        PinNeeded,
+        /// User entered an incorrect PIN and has to enter it again.
+        ChallengeFailedPinNeeded,
     }
 
     /// Result of the execute operation.
@@ -86,6 +86,36 @@ pub mod response {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub color: Option<Color>,
 
+        // States for EnergyStorage trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub descriptive_capacity_remaining: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub capacity_remaining: Vec<CapacityValue>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub is_plugged_in: Option<bool>,
+
+        // States for FanSpeed trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub current_fan_speed_percent: Option<u8>,
+
+        // States for LockUnlock trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub is_locked: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub is_jammed: Option<bool>,
+
+        // States for OpenClose trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub open_percent: Option<u8>,
+
+        // States for SensorState trait.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub current_sensor_state_data: Vec<CurrentSensorState>,
+
+        // States for StartStop trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub is_running: Option<bool>,
+
         // States for TemperatureSetting trait.
         #[serde(skip_serializing_if = "Option::is_none")]
         pub active_thermostat_mode: Option<String>,
@@ -105,6 +135,36 @@ pub mod response {
         pub thermostat_temperature_setpoint_low: Option<f64>,
     }
 
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CurrentSensorState {
+        /// Sensor type, one of the names from the `sensorStatesSupported` attribute.
+        pub name: String,
+        /// Current descriptive state of the sensor.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub current_sensor_state: Option<String>,
+        /// Current numeric value of the sensor, in the unit from `numericCapabilities`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub raw_value: Option<f64>,
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CapacityValue {
+        pub raw_value: f64,
+        pub unit: CapacityUnit,
+    }
+
+    #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum CapacityUnit {
+        Seconds,
+        Miles,
+        Kilometers,
+        Percentage,
+        KilowattHours,
+    }
+
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub enum Color {
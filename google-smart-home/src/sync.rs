@@ -91,6 +91,32 @@ pub mod response {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub command_only_color_setting: Option<bool>,
 
+        // Attributes for EnergyStorage trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub query_only_energy_storage: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub is_rechargeable: Option<bool>,
+
+        // Attributes for FanSpeed trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub supports_fan_speed_percent: Option<bool>,
+
+        // Attributes for OpenClose trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub discrete_only_open_close: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub query_only_open_close: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub open_direction: Option<Vec<crate::device::commands::OpenDirection>>,
+
+        // Attributes for SensorState trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub sensor_states_supported: Option<Vec<SensorStateSupported>>,
+
+        // Attributes for StartStop trait.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub pausable: Option<bool>,
+
         // Attributes for TemperatureSetting trait.
         #[serde(skip_serializing_if = "Option::is_none")]
         pub available_thermostat_modes: Option<Vec<String>>,
@@ -120,11 +146,36 @@ pub mod response {
         Hsv,
     }
 
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SensorStateSupported {
+        /// Supported sensor type, e.g. `SmokeLevel`.
+        pub name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub descriptive_capabilities: Option<SensorDescriptiveCapabilities>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub numeric_capabilities: Option<SensorNumericCapabilities>,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SensorNumericCapabilities {
+        /// Unit of the raw sensor value, e.g. `PARTS_PER_MILLION`.
+        pub raw_value_unit: String,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SensorDescriptiveCapabilities {
+        /// List of the possible descriptive states for the sensor.
+        pub available_states: Vec<String>,
+    }
+
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ThermostatTemperatureRange {
         pub min_threshold_celsius: f64,
-        pub max_threshold_celcius: f64,
+        pub max_threshold_celsius: f64,
     }
 
     #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -45,6 +45,7 @@ fn execute_request() {
                     execution: [
                         request::PayloadCommandExecution {
                             command: Command::OnOff(commands::OnOff { on: true }),
+                            challenge: None,
                         },
                         request::PayloadCommandExecution {
                             command: Command::ColorAbsolute(commands::ColorAbsolute {
@@ -59,6 +60,7 @@ fn execute_request() {
                                     },
                                 },
                             }),
+                            challenge: None,
                         },
                     ]
                     .to_vec(),
@@ -91,12 +93,14 @@ fn execute_response() {
                         .unwrap()
                         .to_owned(),
                         error_code: None,
+                        challenge_needed: None,
                     },
                     response::PayloadCommand {
                         ids: [String::from("456")].to_vec(),
                         status: response::PayloadCommandStatus::Error,
                         states: Default::default(),
                         error_code: Some(String::from("deviceTurnedOff")),
+                        challenge_needed: None,
                     },
                 ]
                 .to_vec(),
@@ -104,3 +108,82 @@ fn execute_response() {
         },
     );
 }
+
+#[test]
+fn thermostat_commands() {
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.ThermostatTemperatureSetpoint", "params": {"thermostatTemperatureSetpoint": 22.5}}"#,
+        Command::ThermostatTemperatureSetpoint(commands::ThermostatTemperatureSetpoint {
+            thermostat_temperature_setpoint: 22.5,
+        }),
+    );
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.ThermostatSetMode", "params": {"thermostatMode": "heat"}}"#,
+        Command::ThermostatSetMode(commands::ThermostatSetMode {
+            thermostat_mode: String::from("heat"),
+        }),
+    );
+}
+
+#[test]
+fn lock_unlock_challenge() {
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.LockUnlock", "params": {"lock": false}, "challenge": {"pin": "333222"}}"#,
+        request::PayloadCommandExecution {
+            command: Command::LockUnlock(commands::LockUnlock {
+                lock: false,
+                follow_up_token: None,
+            }),
+            challenge: Some(request::Challenge {
+                ack: None,
+                pin: Some(String::from("333222")),
+            }),
+        },
+    );
+    common::two_way_serde(
+        r#"{"ids": ["123"], "status": "ERROR", "errorCode": "challengeNeeded", "challengeNeeded": {"type": "pinNeeded"}}"#,
+        response::PayloadCommand {
+            ids: vec![String::from("123")],
+            status: response::PayloadCommandStatus::Error,
+            states: Default::default(),
+            error_code: Some(String::from("challengeNeeded")),
+            challenge_needed: Some(response::ChallengeNeeded {
+                r#type: response::ChallengeType::PinNeeded,
+            }),
+        },
+    );
+}
+
+#[test]
+fn window_covering_commands() {
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.OpenClose", "params": {"openPercent": 50, "openDirection": "UP"}}"#,
+        Command::OpenClose(commands::OpenClose {
+            open_percent: 50,
+            open_direction: Some(commands::OpenDirection::Up),
+        }),
+    );
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.StartStop", "params": {"start": false}}"#,
+        Command::StartStop(commands::StartStop {
+            start: false,
+            zone: None,
+        }),
+    );
+}
+
+#[test]
+fn fan_speed_commands() {
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.SetFanSpeed", "params": {"fanSpeedPercent": 40}}"#,
+        Command::SetFanSpeed(commands::SetFanSpeed::Percent {
+            fan_speed_percent: 40,
+        }),
+    );
+    common::two_way_serde(
+        r#"{"command": "action.devices.commands.SetFanSpeed", "params": {"fanSpeed": "low"}}"#,
+        Command::SetFanSpeed(commands::SetFanSpeed::Setting {
+            fan_speed: String::from("low"),
+        }),
+    );
+}
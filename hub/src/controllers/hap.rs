@@ -6,8 +6,18 @@ use crate::providers;
 use crate::providers::ProviderExt;
 use futures::lock::Mutex;
 use futures::FutureExt;
+use hap::accessory::contact_sensor::ContactSensorAccessory;
+use hap::accessory::fan_v2::FanV2Accessory;
 use hap::accessory::garage_door_opener::GarageDoorOpenerAccessory;
+use hap::accessory::leak_sensor::LeakSensorAccessory;
 use hap::accessory::lightbulb::LightbulbAccessory;
+use hap::accessory::motion_sensor::MotionSensorAccessory;
+use hap::accessory::occupancy_sensor::OccupancySensorAccessory;
+use hap::accessory::outlet::OutletAccessory;
+use hap::accessory::smoke_sensor::SmokeSensorAccessory;
+use hap::accessory::switch::SwitchAccessory;
+use hap::accessory::thermostat::ThermostatAccessory;
+use hap::accessory::window_covering::WindowCoveringAccessory;
 use hap::accessory::AccessoryCategory;
 use hap::accessory::AccessoryInformation;
 use hap::accessory::HapAccessory;
@@ -15,8 +25,13 @@ use hap::characteristic::AsyncCharacteristicCallbacks;
 use hap::characteristic::CharacteristicCallbacks;
 use hap::server::IpServer;
 use hap::server::Server;
+use hap::service::air_purifier::AirPurifierService;
+use hap::service::air_quality_sensor::AirQualitySensorService;
 use hap::service::battery::BatteryService;
+use hap::service::carbon_dioxide_sensor::CarbonDioxideSensorService;
+use hap::service::filter_maintenance::FilterMaintenanceService;
 use hap::service::humidity_sensor::HumiditySensorService;
+use hap::service::lock_mechanism::LockMechanismService;
 use hap::service::temperature_sensor::TemperatureSensorService;
 use hap::storage::FileStorage;
 use hap::storage::Storage;
@@ -26,7 +41,15 @@ use hap::Pin;
 use houseflow_config::hub::controllers::Hap as HapConfig;
 use houseflow_types::accessory;
 use houseflow_types::accessory::characteristics;
+use houseflow_types::accessory::characteristics::AirQualityLevel;
 use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::HeatingCoolingMode;
+use houseflow_types::accessory::characteristics::HeatingCoolingState;
+use houseflow_types::accessory::characteristics::LockState;
+use houseflow_types::accessory::characteristics::LockTargetState;
+use houseflow_types::accessory::characteristics::Movement;
+use houseflow_types::accessory::characteristics::Rotation;
+use houseflow_types::accessory::characteristics::TemperatureUnits;
 use houseflow_types::accessory::services::ServiceName;
 use mac_address::get_mac_address;
 use serde::ser::SerializeStruct;
@@ -103,6 +126,63 @@ impl<P: ProviderExt + Clone + Send + Sync + 'static> HapController<P> {
     }
 
     async fn handle_message(&mut self, message: Message) -> Result<(), anyhow::Error> {
+        // forwards identify requests of the Home app to the accessory and registers it
+        macro_rules! add_accessory {
+            ($accessory:ident, $accessory_id:expr) => {{
+                let provider = self.provider.clone();
+                let accessory_id = $accessory_id;
+                $accessory
+                    .accessory_information
+                    .identify
+                    .on_update_async(Some(move |_current: bool, new: bool| {
+                        let provider = provider.clone();
+
+                        async move {
+                            if new {
+                                if let Err(err) = provider.identify(accessory_id).await {
+                                    tracing::warn!(%accessory_id, "accessory failed to identify: {}", err);
+                                }
+                            }
+                            Ok(())
+                        }
+                        .boxed()
+                    }));
+                self.ip_server.add_accessory($accessory).await?
+            }};
+        }
+
+        // read-only sensor whose state is only ever pushed by the accessory
+        macro_rules! binary_sensor {
+            ($accessory_id:expr, $accessory:ident, $service:ident, $characteristic:ident, $name:literal, $model:literal) => {{
+                let mut sensor = $accessory::new(
+                    self.accessory_instance_id,
+                    AccessoryInformation {
+                        manufacturer: "Houseflow".to_string(),
+                        model: $model.to_string(),
+                        name: $name.to_string(),
+                        serial_number: $accessory_id.to_string(),
+                        ..Default::default()
+                    },
+                )?;
+                sensor.$service.$characteristic.on_read(Some(|| Ok(None)));
+                sensor
+                    .$service
+                    .status_tampered
+                    .as_mut()
+                    .unwrap()
+                    .on_read(Some(|| Ok(None)));
+                sensor
+                    .$service
+                    .status_low_battery
+                    .as_mut()
+                    .unwrap()
+                    .on_read(Some(|| Ok(None)));
+
+                tracing::info!(concat!("registering new ", $name, " accessory"));
+                add_accessory!(sensor, $accessory_id)
+            }};
+        }
+
         match message {
             Message::Connected { accessory } => {
                 let accessory_ptr = match &accessory.r#type {
@@ -173,9 +253,8 @@ impl<P: ProviderExt + Clone + Send + Sync + 'static> HapController<P> {
                                     .status_low_battery
                                     .on_read(Some(|| Ok(None)));
 
-                                self.ip_server.add_accessory(hygro_thermometer).await?
+                                add_accessory!(hygro_thermometer, accessory.id)
                             }
-                            _ => unimplemented!(),
                         }
                     }
                     accessory::Type::Houseflow(accessory_type) => {
@@ -229,17 +308,20 @@ impl<P: ProviderExt + Clone + Send + Sync + 'static> HapController<P> {
                                                             },
                                             });
 
-                                            provider.write_characteristic(accessory_id, service_name, characteristic).await.unwrap();
-                                            Ok(())
+                                            provider.write_characteristic(accessory_id, service_name, characteristic).await.map_err(write_failed)
                                         }
                                         .boxed()
                                     }));
 
                                 tracing::info!("registering new garage door opener accessory");
-                                self.ip_server.add_accessory(garage_door_opener).await?
+                                add_accessory!(garage_door_opener, accessory.id)
                             }
                             Manufacturer::Gate => todo!(),
-                            Manufacturer::Lightbulb => {
+                            Manufacturer::Lightbulb {
+                                brightness,
+                                color,
+                                color_temperature,
+                            } => {
                                 let mut lightbulb = LightbulbAccessory::new(
                                     self.accessory_instance_id,
                                     AccessoryInformation {
@@ -258,201 +340,1431 @@ impl<P: ProviderExt + Clone + Send + Sync + 'static> HapController<P> {
                                         software_revision: None,
                                     },
                                 )?;
-                                let brightness = lightbulb.lightbulb.brightness.as_mut().unwrap();
-                                brightness.on_read(Some(|| Ok(None)));
+                                lightbulb.lightbulb.power_state.on_read(Some(|| Ok(None)));
+
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                lightbulb
+                                    .lightbulb
+                                    .power_state
+                                    .on_update_async(Some(move |current: bool, new: bool| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("lightbulb power state characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::On(characteristics::On { on: new });
+                                            provider.write_characteristic(accessory_id, ServiceName::Light, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                // characteristics that the light doesn't support are not exposed to the Home app
+                                if !*brightness {
+                                    lightbulb.lightbulb.brightness = None;
+                                }
+                                if !*color {
+                                    lightbulb.lightbulb.hue = None;
+                                    lightbulb.lightbulb.saturation = None;
+                                }
+                                if !*color_temperature {
+                                    lightbulb.lightbulb.color_temperature = None;
+                                }
+
+                                let provider = self.provider.clone();
+                                if let Some(brightness) = lightbulb.lightbulb.brightness.as_mut() {
+                                    brightness.on_read(Some(|| Ok(None)));
+                                    brightness
+                                        .on_update_async(Some(move |current: i32, new: i32| {
+                                            let provider = provider.clone();
+
+                                            async move {
+                                                tracing::debug!("lightbulb brightness characteristic updated from {} to {}", current, new);
+                                                let characteristic = Characteristic::Brightness(characteristics::Brightness {
+                                                    percentage: new as u8,
+                                                });
+                                                provider.write_characteristic(accessory_id, ServiceName::Light, characteristic).await.map_err(write_failed)
+                                            }
+                                            .boxed()
+                                        }));
+                                }
+
+                                let provider = self.provider.clone();
+                                if let Some(hue) = lightbulb.lightbulb.hue.as_mut() {
+                                    hue.on_read(Some(|| Ok(None)));
+                                    hue.on_update_async(Some(move |current: f32, new: f32| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!(
+                                                "lightbulb hue characteristic updated from {} to {}",
+                                                current,
+                                                new
+                                            );
+                                            let characteristic =
+                                                Characteristic::Hue(characteristics::Hue {
+                                                    degrees: new,
+                                                });
+                                            provider
+                                                .write_characteristic(
+                                                    accessory_id,
+                                                    ServiceName::Light,
+                                                    characteristic,
+                                                )
+                                                .await
+                                                .map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+                                }
+
+                                let provider = self.provider.clone();
+                                if let Some(saturation) = lightbulb.lightbulb.saturation.as_mut() {
+                                    saturation.on_read(Some(|| Ok(None)));
+                                    saturation
+                                        .on_update_async(Some(move |current: f32, new: f32| {
+                                            let provider = provider.clone();
+
+                                            async move {
+                                                tracing::debug!("lightbulb saturation characteristic updated from {} to {}", current, new);
+                                                let characteristic = Characteristic::Saturation(characteristics::Saturation {
+                                                    percentage: new,
+                                                });
+                                                provider.write_characteristic(accessory_id, ServiceName::Light, characteristic).await.map_err(write_failed)
+                                            }
+                                            .boxed()
+                                        }));
+                                }
 
                                 let provider = self.provider.clone();
+                                if let Some(color_temperature) = lightbulb.lightbulb.color_temperature.as_mut() {
+                                    color_temperature.on_read(Some(|| Ok(None)));
+                                    color_temperature
+                                        .on_update_async(Some(move |current: i32, new: i32| {
+                                            let provider = provider.clone();
 
+                                            async move {
+                                                tracing::debug!("lightbulb color temperature characteristic updated from {} to {}", current, new);
+                                                let characteristic = Characteristic::ColorTemperature(characteristics::ColorTemperature {
+                                                    mireds: new as u16,
+                                                });
+                                                provider.write_characteristic(accessory_id, ServiceName::Light, characteristic).await.map_err(write_failed)
+                                            }
+                                            .boxed()
+                                        }));
+                                }
+
+                                tracing::info!("registering new lightbulb accessory");
+                                add_accessory!(lightbulb, accessory.id)
+                            }
+                            Manufacturer::Thermostat => {
+                                let mut thermostat = ThermostatAccessory::new(
+                                    self.accessory_instance_id,
+                                    AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-thermostat".to_string(),
+                                        name: "Thermostat".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        accessory_flags: None,
+                                        application_matching_identifier: None,
+                                        // configured_name: Some(configured_accessory.name.clone()), For some reason it causes the Home app to break
+                                        configured_name: None,
+                                        firmware_revision: None,
+                                        hardware_finish: None,
+                                        hardware_revision: None,
+                                        product_data: None,
+                                        software_revision: None,
+                                    },
+                                )?;
+                                thermostat
+                                    .thermostat
+                                    .current_temperature
+                                    .on_read(Some(|| Ok(None)));
+                                thermostat
+                                    .thermostat
+                                    .current_heating_cooling_state
+                                    .on_read(Some(|| Ok(None)));
+
+                                let provider = self.provider.clone();
                                 let accessory_id = accessory.id;
-                                brightness
-                                    .on_update_async(Some(move |current: i32, new: i32| {
+                                thermostat
+                                    .thermostat
+                                    .target_temperature
+                                    .on_update_async(Some(move |current: f32, new: f32| {
                                         let provider = provider.clone();
 
                                         async move {
-                                            println!("lightbulb target door state characteristic updated from {} to {}", current, new);
-                                            let service_name = ServiceName::GarageDoorOpener;
-                                            let characteristic = Characteristic::TargetDoorState(characteristics::TargetDoorState {
-                                                open_percent: if new == 1 {
-                                                                100
-                                                            } else if new == 0 {
-                                                                0
-                                                            } else {
-                                                                unreachable!()
-                                                            },
+                                            tracing::debug!("thermostat target temperature characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::TargetTemperature(characteristics::TargetTemperature {
+                                                temperature: new,
                                             });
+                                            provider.write_characteristic(accessory_id, ServiceName::Thermostat, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                let provider = self.provider.clone();
+                                thermostat
+                                    .thermostat
+                                    .target_heating_cooling_state
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
 
-                                            provider.write_characteristic(accessory_id, service_name, characteristic).await.unwrap();
-                                            Ok(())
+                                        async move {
+                                            tracing::debug!("thermostat target heating cooling state characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::TargetHeatingCoolingState(characteristics::TargetHeatingCoolingState {
+                                                mode: heating_cooling_mode_from_hap(new),
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::Thermostat, characteristic).await.map_err(write_failed)
                                         }
                                         .boxed()
                                     }));
 
-                                tracing::info!("registering new lightbulb accessory");
-                                self.ip_server.add_accessory(lightbulb).await?
+                                let provider = self.provider.clone();
+                                thermostat
+                                    .thermostat
+                                    .temperature_display_units
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("thermostat temperature display units characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::TemperatureDisplayUnits(characteristics::TemperatureDisplayUnits {
+                                                units: temperature_units_from_hap(new),
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::Thermostat, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                tracing::info!("registering new thermostat accessory");
+                                add_accessory!(thermostat, accessory.id)
                             }
-                            _ => unimplemented!(),
-                        }
-                    }
-                    _ => unimplemented!(),
-                };
-                self.accessory_instance_id += 1;
-                self.accessory_pointers.insert(accessory.id, accessory_ptr);
-            }
-            Message::Disconnected { accessory_id } => {
-                let accessory_pointer = self.accessory_pointers.remove(&accessory_id).unwrap();
-                self.ip_server.remove_accessory(&accessory_pointer).await?;
-            }
-            Message::Updated {
-                accessory_id,
-                service_name,
-                characteristic,
-            } => {
-                let accessory = self.accessory_pointers.get(&accessory_id).unwrap();
-                let mut accessory = accessory.lock().await;
-                let service_hap_type = match service_name {
-                    ServiceName::TemperatureSensor => HapType::TemperatureSensor,
-                    ServiceName::HumiditySensor => HapType::HumiditySensor,
-                    ServiceName::GarageDoorOpener => HapType::GarageDoorOpener,
-                    ServiceName::Battery => HapType::Battery,
-                    ServiceName::Light => HapType::Lightbulb,
-                };
-                let service = accessory.get_mut_service(service_hap_type).unwrap();
-                match characteristic {
-                    Characteristic::CurrentTemperature(current_temperature) => {
-                        service
-                            .get_mut_characteristic(HapType::CurrentTemperature)
-                            .unwrap()
-                            .set_value(JsonValue::Number(
-                                serde_json::Number::from_f64(
-                                    current_temperature.temperature as f64,
-                                )
-                                .unwrap(),
-                            ))
-                            .await?
-                    }
-                    Characteristic::CurrentHumidity(current_humidity) => {
-                        service
-                            .get_mut_characteristic(HapType::CurrentRelativeHumidity)
-                            .unwrap()
-                            .set_value(JsonValue::Number(
-                                serde_json::Number::from_f64(current_humidity.humidity as f64)
-                                    .unwrap(),
-                            ))
-                            .await?
-                    }
-                    Characteristic::CurrentDoorState(current_door_state) => {
-                        service
-                            .get_mut_characteristic(HapType::CurrentDoorState)
-                            .unwrap()
-                            .set_value(JsonValue::Number(serde_json::Number::from(
-                                if current_door_state.open_percent == 100 {
-                                    1
-                                } else if current_door_state.open_percent == 0 {
-                                    0
-                                } else {
-                                    unimplemented!()
-                                },
-                            )))
-                            .await?
-                    }
-                    Characteristic::TargetDoorState(_) => unimplemented!(),
-                    Characteristic::BatteryLevel(characteristics::BatteryLevel {
-                        battery_level_percent,
-                    }) => {
-                        service
-                            .get_mut_characteristic(HapType::BatteryLevel)
-                            .unwrap()
-                            .set_value(JsonValue::Number(serde_json::Number::from(
-                                battery_level_percent,
-                            )))
-                            .await?;
-                        service
-                            .get_mut_characteristic(HapType::StatusLowBattery)
-                            .unwrap()
-                            .set_value(JsonValue::Number(serde_json::Number::from(
-                                if battery_level_percent > 20 { 0 } else { 1 },
-                            )))
-                            .await?;
-                    }
-                    Characteristic::ChargingState(_) => todo!(),
-                    Characteristic::On(characteristics::On { on }) => {
-                        service
-                            .get_mut_characteristic(HapType::PowerState)
-                            .unwrap()
-                            .set_value(JsonValue::Bool(on))
-                            .await?;
-                    }
-                };
-            }
-        };
-        Ok(())
-    }
-}
+                            Manufacturer::ContactSensor => binary_sensor!(
+                                accessory.id,
+                                ContactSensorAccessory,
+                                contact_sensor,
+                                contact_sensor_state,
+                                "Contact Sensor",
+                                "houseflow-contact-sensor"
+                            ),
+                            Manufacturer::MotionSensor => binary_sensor!(
+                                accessory.id,
+                                MotionSensorAccessory,
+                                motion_sensor,
+                                motion_detected,
+                                "Motion Sensor",
+                                "houseflow-motion-sensor"
+                            ),
+                            Manufacturer::OccupancySensor => binary_sensor!(
+                                accessory.id,
+                                OccupancySensorAccessory,
+                                occupancy_sensor,
+                                occupancy_detected,
+                                "Occupancy Sensor",
+                                "houseflow-occupancy-sensor"
+                            ),
+                            Manufacturer::LeakSensor => binary_sensor!(
+                                accessory.id,
+                                LeakSensorAccessory,
+                                leak_sensor,
+                                leak_detected,
+                                "Leak Sensor",
+                                "houseflow-leak-sensor"
+                            ),
+                            Manufacturer::SmokeSensor => binary_sensor!(
+                                accessory.id,
+                                SmokeSensorAccessory,
+                                smoke_sensor,
+                                smoke_detected,
+                                "Smoke Sensor",
+                                "houseflow-smoke-sensor"
+                            ),
+                            Manufacturer::Blinds => {
+                                let mut blinds = WindowCoveringAccessory::new(
+                                    self.accessory_instance_id,
+                                    AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-blinds".to_string(),
+                                        name: "Blinds".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    },
+                                )?;
+                                let window_covering = &mut blinds.window_covering;
+                                window_covering.current_position.on_read(Some(|| Ok(None)));
+                                window_covering.position_state.on_read(Some(|| Ok(None)));
+                                window_covering
+                                    .current_horizontal_tilt_angle
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
 
-#[derive(Debug, Default)]
-struct HygroThermometerAccessory {
-    id: u64,
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                window_covering
+                                    .target_position
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
 
-    pub accessory_information: hap::service::accessory_information::AccessoryInformationService,
-    pub temperature_sensor: hap::service::temperature_sensor::TemperatureSensorService,
-    pub humidity_sensor: hap::service::humidity_sensor::HumiditySensorService,
-    pub battery: hap::service::battery::BatteryService,
-}
+                                        async move {
+                                            tracing::debug!("window covering target position characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::TargetPosition(characteristics::TargetPosition {
+                                                position: new,
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::WindowCovering, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
 
-impl hap::accessory::HapAccessory for HygroThermometerAccessory {
-    fn get_id(&self) -> u64 {
-        self.id
-    }
+                                let provider = self.provider.clone();
+                                window_covering
+                                    .target_horizontal_tilt_angle
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |current: i32, new: i32| {
+                                        let provider = provider.clone();
 
-    fn set_id(&mut self, id: u64) {
-        self.id = id
-    }
+                                        async move {
+                                            tracing::debug!("window covering target tilt angle characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::TargetTiltAngle(characteristics::TargetTiltAngle {
+                                                degrees: new as i8,
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::WindowCovering, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
 
-    fn get_service(&self, hap_type: HapType) -> Option<&dyn hap::service::HapService> {
-        for service in self.get_services() {
-            if service.get_type() == hap_type {
-                return Some(service);
-            }
-        }
-        None
-    }
+                                let provider = self.provider.clone();
+                                window_covering
+                                    .hold_position
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |_: bool, new: bool| {
+                                        let provider = provider.clone();
 
-    fn get_mut_service(&mut self, hap_type: HapType) -> Option<&mut dyn hap::service::HapService> {
-        for service in self.get_mut_services() {
-            if service.get_type() == hap_type {
-                return Some(service);
-            }
-        }
-        None
-    }
+                                        async move {
+                                            tracing::debug!("window covering hold position characteristic set to {}", new);
+                                            let characteristic = Characteristic::HoldPosition(characteristics::HoldPosition {
+                                                hold: new,
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::WindowCovering, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
 
-    fn get_services(&self) -> Vec<&dyn hap::service::HapService> {
-        vec![
-            &self.accessory_information,
-            &self.temperature_sensor,
-            &self.humidity_sensor,
-            &self.battery,
-        ]
-    }
+                                tracing::info!("registering new blinds accessory");
+                                add_accessory!(blinds, accessory.id)
+                            }
+                            Manufacturer::Switch => {
+                                let mut switch = SwitchAccessory::new(
+                                    self.accessory_instance_id,
+                                    AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-switch".to_string(),
+                                        name: "Switch".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    },
+                                )?;
+                                switch.switch.power_state.on_read(Some(|| Ok(None)));
 
-    fn get_mut_services(&mut self) -> Vec<&mut dyn hap::service::HapService> {
-        vec![
-            &mut self.accessory_information,
-            &mut self.temperature_sensor,
-            &mut self.humidity_sensor,
-            &mut self.battery,
-        ]
-    }
-}
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                switch
+                                    .switch
+                                    .power_state
+                                    .on_update_async(Some(move |current: bool, new: bool| {
+                                        let provider = provider.clone();
 
-impl Serialize for HygroThermometerAccessory {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let mut state = serializer.serialize_struct("HapAccessory", 2)?;
-        state.serialize_field("aid", &self.get_id())?;
-        state.serialize_field("services", &self.get_services())?;
-        state.end()
-    }
-}
+                                        async move {
+                                            tracing::debug!("switch power state characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::On(characteristics::On { on: new });
+                                            provider.write_characteristic(accessory_id, ServiceName::Switch, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                tracing::info!("registering new switch accessory");
+                                add_accessory!(switch, accessory.id)
+                            }
+                            Manufacturer::Outlet => {
+                                let mut outlet = OutletAccessory::new(
+                                    self.accessory_instance_id,
+                                    AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-outlet".to_string(),
+                                        name: "Outlet".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    },
+                                )?;
+                                outlet.outlet.power_state.on_read(Some(|| Ok(None)));
+                                outlet
+                                    .outlet
+                                    .outlet_in_use
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                outlet
+                                    .outlet
+                                    .power_state
+                                    .on_update_async(Some(move |current: bool, new: bool| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("outlet power state characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::On(characteristics::On { on: new });
+                                            provider.write_characteristic(accessory_id, ServiceName::Outlet, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                tracing::info!("registering new outlet accessory");
+                                add_accessory!(outlet, accessory.id)
+                            }
+                            Manufacturer::AirQualitySensor => {
+                                let mut sensor = AirQualitySensorAccessory {
+                                    id: self.accessory_instance_id,
+                                    accessory_information: AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-air-quality-sensor".to_string(),
+                                        name: "Air Quality Sensor".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    }
+                                    .to_service(1, self.accessory_instance_id)
+                                    .unwrap(),
+                                    // accessory information service ends at IID 6, so we start counting at 7
+                                    air_quality_sensor: AirQualitySensorService::new(
+                                        7,
+                                        self.accessory_instance_id,
+                                    ),
+                                    // air quality sensor service ends at IID 19, so we start counting at 20
+                                    carbon_dioxide_sensor: CarbonDioxideSensorService::new(
+                                        20,
+                                        self.accessory_instance_id,
+                                    ),
+                                };
+                                let air_quality_sensor = &mut sensor.air_quality_sensor;
+                                // gases which Houseflow doesn't measure
+                                air_quality_sensor.nitrogen_dioxide_density = None;
+                                air_quality_sensor.ozone_density = None;
+                                air_quality_sensor.sulphur_dioxide_density = None;
+                                air_quality_sensor
+                                    .current_air_quality
+                                    .on_read(Some(|| Ok(None)));
+                                air_quality_sensor
+                                    .pm2_5_density
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                air_quality_sensor
+                                    .pm10_density
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                air_quality_sensor
+                                    .volatile_organic_compound_density
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                let carbon_dioxide_sensor = &mut sensor.carbon_dioxide_sensor;
+                                carbon_dioxide_sensor
+                                    .carbon_dioxide_detected
+                                    .on_read(Some(|| Ok(None)));
+                                carbon_dioxide_sensor
+                                    .carbon_dioxide_level
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+
+                                tracing::info!("registering new air quality sensor accessory");
+                                add_accessory!(sensor, accessory.id)
+                            }
+                            Manufacturer::Fan => {
+                                let mut fan = FanV2Accessory::new(
+                                    self.accessory_instance_id,
+                                    AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-fan".to_string(),
+                                        name: "Fan".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    },
+                                )?;
+                                let fan_v2 = &mut fan.fan_v2;
+                                // automatic mode and child lock aren't supported
+                                fan_v2.current_fan_state = None;
+                                fan_v2.target_fan_state = None;
+                                fan_v2.lock_physical_controls = None;
+                                fan_v2.active.on_read(Some(|| Ok(None)));
+                                fan_v2
+                                    .rotation_speed
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                fan_v2
+                                    .rotation_direction
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                fan_v2
+                                    .swing_mode
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                fan_v2
+                                    .active
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!(
+                                                "fan active characteristic updated from {} to {}",
+                                                current,
+                                                new
+                                            );
+                                            let characteristic =
+                                                Characteristic::Active(characteristics::Active {
+                                                    active: new == 1,
+                                                });
+                                            provider
+                                                .write_characteristic(
+                                                    accessory_id,
+                                                    ServiceName::Fan,
+                                                    characteristic,
+                                                )
+                                                .await
+                                                .map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                let provider = self.provider.clone();
+                                fan_v2
+                                    .rotation_speed
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |current: f32, new: f32| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("fan rotation speed characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::RotationSpeed(characteristics::RotationSpeed {
+                                                percentage: new as u8,
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::Fan, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                let provider = self.provider.clone();
+                                fan_v2
+                                    .rotation_direction
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |current: i32, new: i32| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("fan rotation direction characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::RotationDirection(characteristics::RotationDirection {
+                                                direction: rotation_from_hap(new),
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::Fan, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                let provider = self.provider.clone();
+                                fan_v2
+                                    .swing_mode
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("fan swing mode characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::SwingMode(characteristics::SwingMode { swing: new == 1 });
+                                            provider.write_characteristic(accessory_id, ServiceName::Fan, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                tracing::info!("registering new fan accessory");
+                                add_accessory!(fan, accessory.id)
+                            }
+                            Manufacturer::AirPurifier => {
+                                let mut purifier = AirPurifierAccessory {
+                                    id: self.accessory_instance_id,
+                                    accessory_information: AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-air-purifier".to_string(),
+                                        name: "Air Purifier".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    }
+                                    .to_service(1, self.accessory_instance_id)
+                                    .unwrap(),
+                                    // accessory information service ends at IID 6, so we start counting at 7
+                                    air_purifier: AirPurifierService::new(
+                                        7,
+                                        self.accessory_instance_id,
+                                    ),
+                                    // air purifier service ends at IID 14, so we start counting at 15
+                                    filter_maintenance: FilterMaintenanceService::new(
+                                        15,
+                                        self.accessory_instance_id,
+                                    ),
+                                };
+                                let air_purifier = &mut purifier.air_purifier;
+                                air_purifier.lock_physical_controls = None;
+                                air_purifier.active.on_read(Some(|| Ok(None)));
+                                air_purifier
+                                    .current_air_purifier_state
+                                    .on_read(Some(|| Ok(None)));
+                                air_purifier
+                                    .rotation_speed
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                air_purifier
+                                    .swing_mode
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+                                let filter_maintenance = &mut purifier.filter_maintenance;
+                                filter_maintenance.filter_reset_change_indication = None;
+                                filter_maintenance
+                                    .filter_change_indication
+                                    .on_read(Some(|| Ok(None)));
+                                filter_maintenance
+                                    .filter_life_level
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_read(Some(|| Ok(None)));
+
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                purifier
+                                    .air_purifier
+                                    .active
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("air purifier active characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::Active(characteristics::Active { active: new == 1 });
+                                            provider.write_characteristic(accessory_id, ServiceName::AirPurifier, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                let provider = self.provider.clone();
+                                purifier
+                                    .air_purifier
+                                    .rotation_speed
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |current: f32, new: f32| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("air purifier rotation speed characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::RotationSpeed(characteristics::RotationSpeed {
+                                                percentage: new as u8,
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::AirPurifier, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                let provider = self.provider.clone();
+                                purifier
+                                    .air_purifier
+                                    .swing_mode
+                                    .as_mut()
+                                    .unwrap()
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("air purifier swing mode characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::SwingMode(characteristics::SwingMode { swing: new == 1 });
+                                            provider.write_characteristic(accessory_id, ServiceName::AirPurifier, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                tracing::info!("registering new air purifier accessory");
+                                add_accessory!(purifier, accessory.id)
+                            }
+                            Manufacturer::Lock => {
+                                let mut lock = LockAccessory {
+                                    id: self.accessory_instance_id,
+                                    accessory_information: AccessoryInformation {
+                                        manufacturer,
+                                        model: "houseflow-lock".to_string(),
+                                        name: "Lock".to_string(),
+                                        serial_number: accessory.id.to_string(),
+                                        ..Default::default()
+                                    }
+                                    .to_service(1, self.accessory_instance_id)
+                                    .unwrap(),
+                                    // accessory information service ends at IID 6, so we start counting at 7
+                                    lock_mechanism: LockMechanismService::new(
+                                        7,
+                                        self.accessory_instance_id,
+                                    ),
+                                };
+                                lock.lock_mechanism
+                                    .lock_current_state
+                                    .on_read(Some(|| Ok(None)));
+
+                                let provider = self.provider.clone();
+                                let accessory_id = accessory.id;
+                                lock.lock_mechanism
+                                    .lock_target_state
+                                    .on_update_async(Some(move |current: u8, new: u8| {
+                                        let provider = provider.clone();
+
+                                        async move {
+                                            tracing::debug!("lock target state characteristic updated from {} to {}", current, new);
+                                            let characteristic = Characteristic::TargetLockState(characteristics::TargetLockState {
+                                                state: lock_target_state_from_hap(new),
+                                            });
+                                            provider.write_characteristic(accessory_id, ServiceName::LockMechanism, characteristic).await.map_err(write_failed)
+                                        }
+                                        .boxed()
+                                    }));
+
+                                tracing::info!("registering new lock accessory");
+                                add_accessory!(lock, accessory.id)
+                            }
+                        }
+                    }
+                };
+                self.accessory_instance_id += 1;
+                self.accessory_pointers.insert(accessory.id, accessory_ptr);
+            }
+            Message::Disconnected { accessory_id } => {
+                // accessories which disconnect before being announced were never added
+                if let Some(accessory_pointer) = self.accessory_pointers.remove(&accessory_id) {
+                    self.ip_server.remove_accessory(&accessory_pointer).await?;
+                }
+            }
+            Message::Updated {
+                accessory_id,
+                service_name,
+                characteristic,
+            } => {
+                let accessory = self.accessory_pointers.get(&accessory_id).unwrap();
+                let mut accessory = accessory.lock().await;
+                let service_hap_type = match service_name {
+                    ServiceName::TemperatureSensor => HapType::TemperatureSensor,
+                    ServiceName::HumiditySensor => HapType::HumiditySensor,
+                    ServiceName::GarageDoorOpener => HapType::GarageDoorOpener,
+                    ServiceName::Battery => HapType::Battery,
+                    ServiceName::Light => HapType::Lightbulb,
+                    ServiceName::Thermostat => HapType::Thermostat,
+                    ServiceName::LockMechanism => HapType::LockMechanism,
+                    ServiceName::ContactSensor => HapType::ContactSensor,
+                    ServiceName::MotionSensor => HapType::MotionSensor,
+                    ServiceName::OccupancySensor => HapType::OccupancySensor,
+                    ServiceName::LeakSensor => HapType::LeakSensor,
+                    ServiceName::SmokeSensor => HapType::SmokeSensor,
+                    ServiceName::WindowCovering => HapType::WindowCovering,
+                    ServiceName::Switch => HapType::Switch,
+                    ServiceName::Outlet => HapType::Outlet,
+                    // carbon dioxide is reported by a separate service of the same accessory
+                    ServiceName::AirQualitySensor
+                        if matches!(characteristic, Characteristic::CarbonDioxideLevel(_)) =>
+                    {
+                        HapType::CarbonDioxideSensor
+                    }
+                    ServiceName::AirQualitySensor => HapType::AirQualitySensor,
+                    ServiceName::Fan => HapType::FanV2,
+                    // filter is reported by a separate service of the same accessory
+                    ServiceName::AirPurifier
+                        if matches!(
+                            characteristic,
+                            Characteristic::FilterLifeLevel(_)
+                                | Characteristic::FilterChangeIndication(_)
+                        ) =>
+                    {
+                        HapType::FilterMaintenance
+                    }
+                    ServiceName::AirPurifier => HapType::AirPurifier,
+                };
+                let service = accessory.get_mut_service(service_hap_type).unwrap();
+                match characteristic {
+                    Characteristic::CurrentTemperature(current_temperature) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentTemperature)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(
+                                    current_temperature.temperature as f64,
+                                )
+                                .unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::CurrentHumidity(current_humidity) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentRelativeHumidity)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(current_humidity.humidity as f64)
+                                    .unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::CurrentDoorState(current_door_state) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentDoorState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                if current_door_state.open_percent == 100 {
+                                    1
+                                } else if current_door_state.open_percent == 0 {
+                                    0
+                                } else {
+                                    unimplemented!()
+                                },
+                            )))
+                            .await?
+                    }
+                    Characteristic::TargetDoorState(_) => unimplemented!(),
+                    Characteristic::BatteryLevel(characteristics::BatteryLevel {
+                        battery_level_percent,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::BatteryLevel)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                battery_level_percent,
+                            )))
+                            .await?;
+                        service
+                            .get_mut_characteristic(HapType::StatusLowBattery)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                if battery_level_percent > 20 { 0 } else { 1 },
+                            )))
+                            .await?;
+                    }
+                    Characteristic::ChargingState(_) => todo!(),
+                    Characteristic::On(characteristics::On { on }) => {
+                        service
+                            .get_mut_characteristic(HapType::PowerState)
+                            .unwrap()
+                            .set_value(JsonValue::Bool(on))
+                            .await?;
+                    }
+                    Characteristic::TargetTemperature(target_temperature) => {
+                        service
+                            .get_mut_characteristic(HapType::TargetTemperature)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(target_temperature.temperature as f64)
+                                    .unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::CurrentHeatingCoolingState(
+                        characteristics::CurrentHeatingCoolingState { state },
+                    ) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentHeatingCoolingState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                heating_cooling_state_to_hap(state),
+                            )))
+                            .await?
+                    }
+                    Characteristic::TargetHeatingCoolingState(
+                        characteristics::TargetHeatingCoolingState { mode },
+                    ) => {
+                        service
+                            .get_mut_characteristic(HapType::TargetHeatingCoolingState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                heating_cooling_mode_to_hap(mode),
+                            )))
+                            .await?
+                    }
+                    Characteristic::TemperatureDisplayUnits(
+                        characteristics::TemperatureDisplayUnits { units },
+                    ) => {
+                        service
+                            .get_mut_characteristic(HapType::TemperatureDisplayUnits)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                temperature_units_to_hap(units),
+                            )))
+                            .await?
+                    }
+                    Characteristic::CurrentLockState(characteristics::CurrentLockState {
+                        state,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::LockCurrentState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                lock_state_to_hap(state),
+                            )))
+                            .await?
+                    }
+                    Characteristic::TargetLockState(characteristics::TargetLockState { state }) => {
+                        service
+                            .get_mut_characteristic(HapType::LockTargetState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                lock_target_state_to_hap(state),
+                            )))
+                            .await?
+                    }
+                    Characteristic::ContactSensorState(characteristics::ContactSensorState {
+                        contact_detected,
+                    }) => {
+                        // HAP reports 0 when the contact is detected
+                        service
+                            .get_mut_characteristic(HapType::ContactSensorState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(u8::from(
+                                !contact_detected,
+                            ))))
+                            .await?
+                    }
+                    Characteristic::MotionDetected(characteristics::MotionDetected {
+                        motion_detected,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::MotionDetected)
+                            .unwrap()
+                            .set_value(JsonValue::Bool(motion_detected))
+                            .await?
+                    }
+                    Characteristic::OccupancyDetected(characteristics::OccupancyDetected {
+                        occupancy_detected,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::OccupancyDetected)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(u8::from(
+                                occupancy_detected,
+                            ))))
+                            .await?
+                    }
+                    Characteristic::LeakDetected(characteristics::LeakDetected {
+                        leak_detected,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::LeakDetected)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(u8::from(
+                                leak_detected,
+                            ))))
+                            .await?
+                    }
+                    Characteristic::SmokeDetected(characteristics::SmokeDetected {
+                        smoke_detected,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::SmokeDetected)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(u8::from(
+                                smoke_detected,
+                            ))))
+                            .await?
+                    }
+                    Characteristic::StatusTampered(characteristics::StatusTampered {
+                        tampered,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::StatusTampered)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(u8::from(
+                                tampered,
+                            ))))
+                            .await?
+                    }
+                    Characteristic::StatusLowBattery(characteristics::StatusLowBattery {
+                        low_battery,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::StatusLowBattery)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(u8::from(
+                                low_battery,
+                            ))))
+                            .await?
+                    }
+                    Characteristic::CurrentPosition(characteristics::CurrentPosition {
+                        position,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentPosition)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(position)))
+                            .await?
+                    }
+                    Characteristic::TargetPosition(characteristics::TargetPosition {
+                        position,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::TargetPosition)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(position)))
+                            .await?
+                    }
+                    Characteristic::PositionState(characteristics::PositionState { movement }) => {
+                        service
+                            .get_mut_characteristic(HapType::PositionState)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                movement_to_hap(movement),
+                            )))
+                            .await?
+                    }
+                    Characteristic::CurrentTiltAngle(characteristics::CurrentTiltAngle {
+                        degrees,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentHorizontalTiltAngle)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(degrees)))
+                            .await?
+                    }
+                    Characteristic::TargetTiltAngle(characteristics::TargetTiltAngle {
+                        degrees,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::TargetHorizontalTiltAngle)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(degrees)))
+                            .await?
+                    }
+                    // write-only, accessories have nothing to report
+                    Characteristic::HoldPosition(_) => {}
+                    Characteristic::Brightness(characteristics::Brightness { percentage }) => {
+                        service
+                            .get_mut_characteristic(HapType::Brightness)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(percentage)))
+                            .await?
+                    }
+                    Characteristic::Hue(characteristics::Hue { degrees }) => {
+                        service
+                            .get_mut_characteristic(HapType::Hue)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(degrees as f64).unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::Saturation(characteristics::Saturation { percentage }) => {
+                        service
+                            .get_mut_characteristic(HapType::Saturation)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(percentage as f64).unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::ColorTemperature(characteristics::ColorTemperature {
+                        mireds,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::ColorTemperature)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(mireds)))
+                            .await?
+                    }
+                    Characteristic::OutletInUse(characteristics::OutletInUse { in_use }) => {
+                        service
+                            .get_mut_characteristic(HapType::OutletInUse)
+                            .unwrap()
+                            .set_value(JsonValue::Bool(in_use))
+                            .await?
+                    }
+                    Characteristic::AirQuality(characteristics::AirQuality { quality }) => {
+                        service
+                            .get_mut_characteristic(HapType::CurrentAirQuality)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                air_quality_to_hap(quality),
+                            )))
+                            .await?
+                    }
+                    Characteristic::CarbonDioxideLevel(characteristics::CarbonDioxideLevel {
+                        ppm,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::CarbonDioxideLevel)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(ppm as f64).unwrap(),
+                            ))
+                            .await?;
+                        service
+                            .get_mut_characteristic(HapType::CarbonDioxideDetected)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                if ppm > CARBON_DIOXIDE_ABNORMAL_PPM {
+                                    1
+                                } else {
+                                    0
+                                },
+                            )))
+                            .await?
+                    }
+                    Characteristic::Pm25Density(characteristics::Pm25Density { density }) => {
+                        service
+                            .get_mut_characteristic(HapType::Pm2_5Density)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(density as f64).unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::Pm10Density(characteristics::Pm10Density { density }) => {
+                        service
+                            .get_mut_characteristic(HapType::Pm10Density)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(density as f64).unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::VocDensity(characteristics::VocDensity { density }) => {
+                        service
+                            .get_mut_characteristic(HapType::VolatileOrganicCompoundDensity)
+                            .unwrap()
+                            .set_value(JsonValue::Number(
+                                serde_json::Number::from_f64(density as f64).unwrap(),
+                            ))
+                            .await?
+                    }
+                    Characteristic::Active(characteristics::Active { active }) => {
+                        service
+                            .get_mut_characteristic(HapType::Active)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(active as u8)))
+                            .await?;
+                        // purifiers additionally report whether they are purifying
+                        if let Some(current_air_purifier_state) =
+                            service.get_mut_characteristic(HapType::CurrentAirPurifierState)
+                        {
+                            use hap::characteristic::current_air_purifier_state::Value;
+
+                            let state = if active {
+                                Value::PurifyingAir
+                            } else {
+                                Value::Inactive
+                            };
+                            current_air_purifier_state
+                                .set_value(JsonValue::Number(serde_json::Number::from(state as u8)))
+                                .await?;
+                        }
+                    }
+                    Characteristic::RotationSpeed(characteristics::RotationSpeed {
+                        percentage,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::RotationSpeed)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(percentage)))
+                            .await?
+                    }
+                    Characteristic::RotationDirection(characteristics::RotationDirection {
+                        direction,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::RotationDirection)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                rotation_to_hap(direction),
+                            )))
+                            .await?
+                    }
+                    Characteristic::SwingMode(characteristics::SwingMode { swing }) => {
+                        service
+                            .get_mut_characteristic(HapType::SwingMode)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(swing as u8)))
+                            .await?
+                    }
+                    Characteristic::FilterLifeLevel(characteristics::FilterLifeLevel {
+                        percentage,
+                    }) => {
+                        service
+                            .get_mut_characteristic(HapType::FilterLifeLevel)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(percentage)))
+                            .await?
+                    }
+                    Characteristic::FilterChangeIndication(
+                        characteristics::FilterChangeIndication { change_needed },
+                    ) => {
+                        service
+                            .get_mut_characteristic(HapType::FilterChangeIndication)
+                            .unwrap()
+                            .set_value(JsonValue::Number(serde_json::Number::from(
+                                change_needed as u8,
+                            )))
+                            .await?
+                    }
+                    // HomeKit has no standard characteristics for power metering
+                    Characteristic::PowerConsumption(_)
+                    | Characteristic::Voltage(_)
+                    | Characteristic::ElectricCurrent(_)
+                    | Characteristic::EnergyConsumption(_) => {}
+                };
+            }
+        };
+        Ok(())
+    }
+}
+
+/// Carbon dioxide level above which HomeKit is told the level is abnormal
+const CARBON_DIOXIDE_ABNORMAL_PPM: f32 = 1000.0;
+
+fn air_quality_to_hap(quality: AirQualityLevel) -> u8 {
+    use hap::characteristic::current_air_quality::Value;
+
+    (match quality {
+        AirQualityLevel::Unknown => Value::Unknown,
+        AirQualityLevel::Excellent => Value::Excellent,
+        AirQualityLevel::Good => Value::Good,
+        AirQualityLevel::Fair => Value::Fair,
+        AirQualityLevel::Inferior => Value::Inferior,
+        AirQualityLevel::Poor => Value::Poor,
+    }) as u8
+}
+
+fn rotation_to_hap(rotation: Rotation) -> i32 {
+    use hap::characteristic::rotation_direction::Value;
+
+    (match rotation {
+        Rotation::Clockwise => Value::Clockwise,
+        Rotation::CounterClockwise => Value::Counterclockwise,
+    }) as i32
+}
+
+fn rotation_from_hap(value: i32) -> Rotation {
+    use hap::characteristic::rotation_direction::Value;
+
+    if value == Value::Counterclockwise as i32 {
+        Rotation::CounterClockwise
+    } else {
+        Rotation::Clockwise
+    }
+}
+
+fn heating_cooling_state_to_hap(state: HeatingCoolingState) -> u8 {
+    use hap::characteristic::current_heating_cooling_state::Value;
+
+    (match state {
+        HeatingCoolingState::Off => Value::Off,
+        HeatingCoolingState::Heat => Value::Heat,
+        HeatingCoolingState::Cool => Value::Cool,
+    }) as u8
+}
+
+fn heating_cooling_mode_to_hap(mode: HeatingCoolingMode) -> u8 {
+    use hap::characteristic::target_heating_cooling_state::Value;
+
+    (match mode {
+        HeatingCoolingMode::Off => Value::Off,
+        HeatingCoolingMode::Heat => Value::Heat,
+        HeatingCoolingMode::Cool => Value::Cool,
+        HeatingCoolingMode::Auto => Value::Auto,
+    }) as u8
+}
+
+fn heating_cooling_mode_from_hap(value: u8) -> HeatingCoolingMode {
+    use hap::characteristic::target_heating_cooling_state::Value;
+
+    match value {
+        v if v == Value::Heat as u8 => HeatingCoolingMode::Heat,
+        v if v == Value::Cool as u8 => HeatingCoolingMode::Cool,
+        v if v == Value::Auto as u8 => HeatingCoolingMode::Auto,
+        _ => HeatingCoolingMode::Off,
+    }
+}
+
+fn temperature_units_to_hap(units: TemperatureUnits) -> u8 {
+    match units {
+        TemperatureUnits::Celsius => 0,
+        TemperatureUnits::Fahrenheit => 1,
+    }
+}
+
+fn temperature_units_from_hap(value: u8) -> TemperatureUnits {
+    match value {
+        1 => TemperatureUnits::Fahrenheit,
+        _ => TemperatureUnits::Celsius,
+    }
+}
+
+fn movement_to_hap(movement: Movement) -> u8 {
+    match movement {
+        Movement::Closing => 0,
+        Movement::Opening => 1,
+        Movement::Stopped => 2,
+    }
+}
+
+fn lock_state_to_hap(state: LockState) -> u8 {
+    match state {
+        LockState::Unsecured => 0,
+        LockState::Secured => 1,
+        LockState::Jammed => 2,
+        LockState::Unknown => 3,
+    }
+}
+
+fn lock_target_state_to_hap(state: LockTargetState) -> u8 {
+    match state {
+        LockTargetState::Unsecured => 0,
+        LockTargetState::Secured => 1,
+    }
+}
+
+fn lock_target_state_from_hap(value: u8) -> LockTargetState {
+    match value {
+        0 => LockTargetState::Unsecured,
+        _ => LockTargetState::Secured,
+    }
+}
+
+/// Fails the write requested by the Home app, `hap` responds to it with an error HAP status instead of a success
+fn write_failed(err: accessory::Error) -> Box<dyn std::error::Error + Send + Sync> {
+    tracing::warn!("failed to write characteristic: {}", err);
+    Box::new(err)
+}
+
+#[derive(Debug, Default)]
+struct HygroThermometerAccessory {
+    id: u64,
+
+    pub accessory_information: hap::service::accessory_information::AccessoryInformationService,
+    pub temperature_sensor: hap::service::temperature_sensor::TemperatureSensorService,
+    pub humidity_sensor: hap::service::humidity_sensor::HumiditySensorService,
+    pub battery: hap::service::battery::BatteryService,
+}
+
+#[derive(Debug, Default)]
+struct LockAccessory {
+    id: u64,
+
+    pub accessory_information: hap::service::accessory_information::AccessoryInformationService,
+    pub lock_mechanism: hap::service::lock_mechanism::LockMechanismService,
+}
+
+#[derive(Debug, Default)]
+struct AirQualitySensorAccessory {
+    id: u64,
+
+    pub accessory_information: hap::service::accessory_information::AccessoryInformationService,
+    pub air_quality_sensor: hap::service::air_quality_sensor::AirQualitySensorService,
+    pub carbon_dioxide_sensor: hap::service::carbon_dioxide_sensor::CarbonDioxideSensorService,
+}
+
+#[derive(Debug, Default)]
+struct AirPurifierAccessory {
+    id: u64,
+
+    pub accessory_information: hap::service::accessory_information::AccessoryInformationService,
+    pub air_purifier: hap::service::air_purifier::AirPurifierService,
+    pub filter_maintenance: hap::service::filter_maintenance::FilterMaintenanceService,
+}
+
+/// Implements [`HapAccessory`](hap::accessory::HapAccessory) and `Serialize` for an accessory composed of the listed services.
+macro_rules! impl_hap_accessory {
+    ($accessory:ident { $($service:ident),+ $(,)? }) => {
+        impl hap::accessory::HapAccessory for $accessory {
+            fn get_id(&self) -> u64 {
+                self.id
+            }
+
+            fn set_id(&mut self, id: u64) {
+                self.id = id
+            }
+
+            fn get_service(&self, hap_type: HapType) -> Option<&dyn hap::service::HapService> {
+                for service in self.get_services() {
+                    if service.get_type() == hap_type {
+                        return Some(service);
+                    }
+                }
+                None
+            }
+
+            fn get_mut_service(
+                &mut self,
+                hap_type: HapType,
+            ) -> Option<&mut dyn hap::service::HapService> {
+                for service in self.get_mut_services() {
+                    if service.get_type() == hap_type {
+                        return Some(service);
+                    }
+                }
+                None
+            }
+
+            fn get_services(&self) -> Vec<&dyn hap::service::HapService> {
+                vec![$(&self.$service),+]
+            }
+
+            fn get_mut_services(&mut self) -> Vec<&mut dyn hap::service::HapService> {
+                vec![$(&mut self.$service),+]
+            }
+        }
+
+        impl Serialize for $accessory {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let mut state = serializer.serialize_struct("HapAccessory", 2)?;
+                state.serialize_field("aid", &self.get_id())?;
+                state.serialize_field("services", &self.get_services())?;
+                state.end()
+            }
+        }
+    };
+}
+
+impl_hap_accessory!(HygroThermometerAccessory {
+    accessory_information,
+    temperature_sensor,
+    humidity_sensor,
+    battery,
+});
+
+impl_hap_accessory!(LockAccessory {
+    accessory_information,
+    lock_mechanism,
+});
+
+impl_hap_accessory!(AirQualitySensorAccessory {
+    accessory_information,
+    air_quality_sensor,
+    carbon_dioxide_sensor,
+});
+
+impl_hap_accessory!(AirPurifierAccessory {
+    accessory_information,
+    air_purifier,
+    filter_maintenance,
+});
@@ -3,32 +3,91 @@ use super::Name;
 
 use super::Message;
 use crate::providers;
-use crate::providers::ProviderExt;
+use crate::providers::Provider;
 use async_trait::async_trait;
 use houseflow_config::hub::controllers::Lighthouse as Config;
+use houseflow_config::hub::Accessory;
+use houseflow_types::accessory;
 use houseflow_types::hub;
 use houseflow_types::lighthouse;
+use houseflow_types::protocol;
+use std::collections::HashMap;
 
 pub struct LighthouseController {
     provider: providers::MasterHandle,
-    client: ezsockets::Client<Message>,
+    client: ezsockets::Client<Self>,
+    /// Encoding preferred in our `Hello`
+    preferred_encoding: protocol::Encoding,
+    /// Handshake sent by the server, `None` until the server responds to our `Hello`
+    peer: Option<protocol::Hello>,
+    /// Encoding chosen by the server, JSON until the server responds to our `Hello`
+    encoding: protocol::Encoding,
+    /// Connected accessories, announced again to the server after reconnecting
+    accessories: HashMap<accessory::ID, Accessory>,
 }
 
 impl LighthouseController {
-    async fn send(&mut self, frame: lighthouse::HubFrame) -> anyhow::Result<()> {
-        let json = serde_json::to_string(&frame)?;
-        self.client.text(json).await;
+    async fn announce(&mut self, accessory: Accessory) -> Result<(), ezsockets::Error> {
+        let capabilities = self.provider.get_accessory_capabilities(accessory.id).await;
+        self.notify(lighthouse::HubFrame::AccessoryConnected(
+            lighthouse::AccessoryConnected {
+                accessory: accessory.into(),
+                capabilities,
+            },
+        ))
+        .await
+    }
+
+    async fn send(&mut self, frame: lighthouse::HubFrame) -> Result<(), protocol::SendError> {
+        let frame_type = lighthouse::HubFrameType::from(&frame).to_string();
+        if let Some(peer) = &self.peer {
+            if !peer.supports(&frame_type) {
+                return Err(protocol::SendError::UnsupportedFrame(frame_type));
+            }
+        }
+        match self.encoding.encode(&frame)? {
+            protocol::Payload::Text(text) => self.client.text(text).await,
+            protocol::Payload::Binary(bytes) => self.client.binary(bytes).await,
+        }
         Ok(())
     }
-}
 
-#[async_trait]
-impl ezsockets::ClientExt for LighthouseController {
-    type Params = Message;
+    /// Sends a frame that doesn't expect a response, frames not supported by the server are dropped.
+    async fn notify(&mut self, frame: lighthouse::HubFrame) -> Result<(), ezsockets::Error> {
+        match self.send(frame).await {
+            Err(protocol::SendError::UnsupportedFrame(frame_type)) => {
+                tracing::warn!("server does not support `{}` frames, dropping", frame_type);
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
 
-    async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
-        let frame = serde_json::from_str::<lighthouse::ServerFrame>(&text)?;
+    async fn frame(
+        &mut self,
+        frame: Result<lighthouse::ServerFrame, protocol::Error>,
+    ) -> Result<(), ezsockets::Error> {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                tracing::warn!("received invalid frame: {}", err);
+                self.notify(lighthouse::HubFrame::Error(err)).await?;
+                return Ok(());
+            }
+        };
         match frame {
+            lighthouse::ServerFrame::Hello(hello) => {
+                self.encoding = hello.encoding();
+                tracing::info!(software_version = %hello.software_version, encoding = %self.encoding, "negotiated lighthouse protocol version {}", hello.protocol_version);
+                self.peer = Some(hello);
+            }
+            lighthouse::ServerFrame::Error(err) => match err {
+                protocol::Error::UnsupportedVersion { .. } => {
+                    tracing::error!("server rejected the connection: {}", err);
+                    return Err(err.into());
+                }
+                err => tracing::warn!("server responded with error: {}", err),
+            },
             lighthouse::ServerFrame::ReadCharacteristic(lighthouse::ReadCharacteristic {
                 id,
                 accessory_id,
@@ -40,7 +99,7 @@ impl ezsockets::ClientExt for LighthouseController {
                     .read_characteristic(accessory_id, service_name, characteristic_name)
                     .await
                     .into();
-                self.send(lighthouse::HubFrame::ReadCharacteristicResult(
+                self.notify(lighthouse::HubFrame::ReadCharacteristicResult(
                     lighthouse::ReadCharacteristicResult { id, result },
                 ))
                 .await?;
@@ -56,29 +115,86 @@ impl ezsockets::ClientExt for LighthouseController {
                     .write_characteristic(accessory_id, service_name, characteristic)
                     .await
                     .into();
-                self.send(lighthouse::HubFrame::WriteCharacteristicResult(
+                self.notify(lighthouse::HubFrame::WriteCharacteristicResult(
                     lighthouse::WriteCharacteristicResult { id, result },
                 ))
                 .await?;
             }
-            _ => unimplemented!(),
+            lighthouse::ServerFrame::BatchReadCharacteristic(
+                lighthouse::BatchReadCharacteristic { id, reads },
+            ) => {
+                let results = self
+                    .provider
+                    .read_characteristics(reads)
+                    .await
+                    .into_iter()
+                    .map(Into::into)
+                    .collect();
+                self.notify(lighthouse::HubFrame::BatchReadCharacteristicResult(
+                    lighthouse::BatchReadCharacteristicResult { id, results },
+                ))
+                .await?;
+            }
+            lighthouse::ServerFrame::BatchWriteCharacteristic(
+                lighthouse::BatchWriteCharacteristic { id, writes },
+            ) => {
+                let results = self
+                    .provider
+                    .write_characteristics(writes)
+                    .await
+                    .into_iter()
+                    .map(Into::into)
+                    .collect();
+                self.notify(lighthouse::HubFrame::BatchWriteCharacteristicResult(
+                    lighthouse::BatchWriteCharacteristicResult { id, results },
+                ))
+                .await?;
+            }
+            lighthouse::ServerFrame::Identify(lighthouse::Identify { id, accessory_id }) => {
+                let result = self.provider.identify(accessory_id).await.into();
+                self.notify(lighthouse::HubFrame::IdentifyResult(
+                    lighthouse::IdentifyResult { id, result },
+                ))
+                .await?;
+            }
+            frame => {
+                let frame_type = lighthouse::ServerFrameType::from(&frame).to_string();
+                self.notify(lighthouse::HubFrame::Error(protocol::Error::UnknownFrame(
+                    frame_type,
+                )))
+                .await?;
+            }
         }
         Ok(())
     }
+}
+
+#[async_trait]
+impl ezsockets::ClientExt for LighthouseController {
+    type Params = Message;
+
+    async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode(&text, lighthouse::ServerFrameType::VARIANTS);
+        self.frame(frame).await
+    }
 
-    async fn binary(&mut self, _bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
-        todo!()
+    async fn binary(&mut self, bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode_binary(&bytes, lighthouse::ServerFrameType::VARIANTS);
+        self.frame(frame).await
     }
 
     async fn call(&mut self, params: Self::Params) -> Result<(), ezsockets::Error> {
         match params {
             Message::Connected { accessory } => {
-                self.send(lighthouse::HubFrame::AccessoryConnected(accessory.into()))
-                    .await?;
+                self.accessories.insert(accessory.id, accessory.clone());
+                self.announce(accessory).await?;
             }
             Message::Disconnected { accessory_id } => {
-                self.send(lighthouse::HubFrame::AccessoryDisconnected(accessory_id))
-                    .await?;
+                self.accessories.remove(&accessory_id);
+                self.notify(lighthouse::HubFrame::AccessoryDisconnected(
+                    lighthouse::AccessoryDisconnected { accessory_id },
+                ))
+                .await?;
             }
             Message::Updated {
                 accessory_id: _,
@@ -88,6 +204,23 @@ impl ezsockets::ClientExt for LighthouseController {
         };
         Ok(())
     }
+
+    async fn connected(&mut self) -> Result<(), ezsockets::Error> {
+        // the server has a new session, which knows nothing about the previous connection
+        self.peer = None;
+        self.encoding = Default::default();
+        let hello = protocol::hello!(
+            lighthouse::PROTOCOL_VERSION,
+            lighthouse::MIN_PROTOCOL_VERSION,
+            lighthouse::ServerFrameType::VARIANTS,
+        );
+        let hello = lighthouse::HubFrame::Hello(hello.prefer(self.preferred_encoding));
+        self.client.text(serde_json::to_string(&hello)?).await;
+        for accessory in self.accessories.values().cloned().collect::<Vec<_>>() {
+            self.announce(accessory).await?;
+        }
+        Ok(())
+    }
 }
 
 pub async fn new(
@@ -96,7 +229,14 @@ pub async fn new(
     provider: providers::MasterHandle,
 ) -> Result<Handle, anyhow::Error> {
     let (client, _) = ezsockets::connect(
-        |client| LighthouseController { provider, client },
+        |client| LighthouseController {
+            provider,
+            client,
+            preferred_encoding: config.encoding,
+            peer: None,
+            encoding: Default::default(),
+            accessories: HashMap::new(),
+        },
         ezsockets::ClientConfig::new(config.url).basic(&hub_id.to_string(), &config.password),
     )
     .await;
@@ -1,4 +1,98 @@
-pub fn app(master_provider: providers::MasterHandle) -> axum::Router {
+/// Number of events buffered for each watcher, slower watchers skip the oldest events
+const EVENTS_CAPACITY: usize = 256;
+
+/// Broadcasts the events of the accessories to the watchers.
+///
+/// Keeps the currently connected accessories, so new watchers learn about them first.
+/// The events are received by the handle returned from `controller()`, which has to be pushed to the master controller.
+#[derive(Debug, Clone)]
+pub struct Events {
+    sender: broadcast::Sender<meta::Event>,
+    accessories: Arc<RwLock<HashMap<accessory::ID, Accessory>>>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENTS_CAPACITY);
+        Self {
+            sender,
+            accessories: Default::default(),
+        }
+    }
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a controller handle which broadcasts the `Connected`/`Disconnected`/`Updated` events.
+    pub fn controller(&self) -> Handle {
+        let (sender, mut receiver) = acu::channel(Name::Meta);
+        let events = self.clone();
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let event = match message {
+                    Message::Connected { accessory } => {
+                        let accessory = Accessory::from(accessory);
+                        events
+                            .accessories
+                            .write()
+                            .unwrap()
+                            .insert(accessory.id, accessory.clone());
+                        meta::Event::Connected { accessory }
+                    }
+                    Message::Disconnected { accessory_id } => {
+                        events.accessories.write().unwrap().remove(&accessory_id);
+                        meta::Event::Disconnected { accessory_id }
+                    }
+                    Message::Updated {
+                        accessory_id,
+                        service_name,
+                        characteristic,
+                    } => meta::Event::Updated {
+                        accessory_id,
+                        service_name,
+                        characteristic,
+                    },
+                };
+                // fails only if nobody is watching
+                let _ = events.sender.send(event);
+            }
+        });
+        Handle { sender }
+    }
+
+    /// Returns the events passing the filter, starting with the currently connected accessories.
+    fn subscribe(&self, filter: meta::EventFilter) -> impl Stream<Item = meta::Event> {
+        let receiver = self.sender.subscribe();
+        let connected = self
+            .accessories
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .map(|accessory| meta::Event::Connected { accessory })
+            .collect::<Vec<_>>();
+        let events = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("watcher is too slow, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        let mut watcher = meta::Watcher::new(filter);
+        futures::stream::iter(connected)
+            .chain(events)
+            .filter(move |event| futures::future::ready(watcher.matches(event)))
+    }
+}
+
+pub fn app(master_provider: providers::MasterHandle, events: Events) -> axum::Router {
     use axum::routing::get;
     use axum::routing::post;
 
@@ -11,19 +105,44 @@ pub fn app(master_provider: providers::MasterHandle) -> axum::Router {
             "/characteristic/:accessory_id/:service_name",
             post(write_characteristic),
         )
+        .route("/accessory/:accessory_id/identify", post(identify))
+        .route("/events", get(watch))
+        .route(
+            "/characteristics/batch-read",
+            post(batch_read_characteristics),
+        )
+        .route(
+            "/characteristics/batch-write",
+            post(batch_write_characteristics),
+        )
         .layer(Extension(master_provider))
+        .layer(Extension(events))
 }
 
+use super::Handle;
+use super::Message;
+use super::Name;
 use crate::providers;
-use crate::providers::ProviderExt;
+use crate::providers::Provider;
 use axum::extract::Extension;
 use axum::extract::Json;
 use axum::extract::Path;
+use axum::extract::Query;
+use axum::response::sse;
+use axum::response::sse::Sse;
+use futures::Stream;
+use futures::StreamExt;
 use houseflow_types::accessory;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::accessory::Accessory;
 use houseflow_types::hub;
+use houseflow_types::meta;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+use tokio::sync::broadcast;
 
 async fn read_characteristic(
     Extension(master_provider): Extension<providers::MasterHandle>,
@@ -49,3 +168,48 @@ async fn write_characteristic(
         .await?;
     Ok(())
 }
+
+async fn batch_read_characteristics(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Json(request): Json<meta::BatchReadRequest>,
+) -> Json<meta::BatchReadResponse> {
+    let results = master_provider
+        .read_characteristics(request.reads)
+        .await
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Json(meta::BatchReadResponse { results })
+}
+
+async fn batch_write_characteristics(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Json(request): Json<meta::BatchWriteRequest>,
+) -> Json<meta::BatchWriteResponse> {
+    let results = master_provider
+        .write_characteristics(request.writes)
+        .await
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Json(meta::BatchWriteResponse { results })
+}
+
+async fn identify(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Path(accessory_id): Path<accessory::ID>,
+) -> Result<(), hub::Error> {
+    master_provider.identify(accessory_id).await?;
+    Ok(())
+}
+
+/// Streams the events as server-sent events, until the watcher disconnects.
+async fn watch(
+    Extension(events): Extension<Events>,
+    Query(filter): Query<meta::EventFilter>,
+) -> Sse<impl Stream<Item = Result<sse::Event, serde_json::Error>>> {
+    let events = events
+        .subscribe(filter)
+        .map(|event| sse::Event::default().json_data(event));
+    Sse::new(events).keep_alive(sse::KeepAlive::default())
+}
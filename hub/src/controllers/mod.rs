@@ -22,12 +22,15 @@ use houseflow_config::hub::Accessory;
 use houseflow_types::accessory;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::routing;
 
 #[derive(Debug, Clone, PartialEq, Eq, strum::Display, strum::IntoStaticStr)]
 pub enum Name {
     Master,
     Hap,
     Lighthouse,
+    Meta,
+    Router,
 }
 
 impl acu::MasterName for Name {
@@ -54,6 +57,18 @@ pub enum Message {
 
 impl acu::Message for Message {}
 
+impl routing::ConnectionEvent for Message {
+    fn connection(&self) -> Option<routing::Connection> {
+        match self {
+            Self::Connected { accessory } => Some(routing::Connection::Connected(accessory.id)),
+            Self::Disconnected { accessory_id } => {
+                Some(routing::Connection::Disconnected(*accessory_id))
+            }
+            Self::Updated { .. } => None,
+        }
+    }
+}
+
 use async_trait::async_trait;
 
 #[async_trait]
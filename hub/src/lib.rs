@@ -44,6 +44,24 @@ macro_rules! optional_provider {
 
 pub type ConfiguredAccessories = Arc<ArcSwap<Vec<Accessory>>>;
 
+/// Cargo features of the controllers and providers that the hub is compiled with, e.g `controllers-hap`
+pub fn features() -> Vec<&'static str> {
+    [
+        ("controllers-hap", cfg!(feature = "controllers-hap")),
+        ("controllers-meta", cfg!(feature = "controllers-meta")),
+        (
+            "controllers-lighthouse",
+            cfg!(feature = "controllers-lighthouse"),
+        ),
+        ("providers-hive", cfg!(feature = "providers-hive")),
+        ("providers-mijia", cfg!(feature = "providers-mijia")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(feature, _)| feature)
+    .collect()
+}
+
 pub async fn run(config: Config) -> Result<(), anyhow::Error> {
     #[allow(unused_imports)]
     use acu::MasterExt;
@@ -59,6 +77,9 @@ pub async fn run(config: Config) -> Result<(), anyhow::Error> {
     let master_controller = controllers::MasterHandle::new();
     #[allow(unused_variables)]
     let master_provider = providers::MasterHandle::new();
+    master_controller
+        .push(master_provider.router(controllers::Name::Router))
+        .await;
 
     let controller_router = {
         let Controllers {
@@ -84,7 +105,9 @@ pub async fn run(config: Config) -> Result<(), anyhow::Error> {
 
         optional_controller!(meta, {
             let _meta = meta;
-            let app = controllers::meta::app(master_provider.clone());
+            let events = controllers::meta::Events::new();
+            master_controller.push(events.controller()).await;
+            let app = controllers::meta::app(master_provider.clone(), events);
             router = router.nest("/meta", app);
         });
 
@@ -26,6 +26,13 @@ async fn main() -> Result<(), anyhow::Error> {
         Err(err) => panic!("Config error: {}", err),
     };
     tracing::debug!("Config: {:#?}", config);
+    let unsupported = config.unsupported_components(&houseflow_hub::features());
+    if !unsupported.is_empty() {
+        for error in &unsupported {
+            tracing::error!("{}", error);
+        }
+        anyhow::bail!("the configuration uses components which the hub is not compiled with");
+    }
 
     houseflow_hub::run(config).await?;
     Ok(())
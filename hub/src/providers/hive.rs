@@ -5,7 +5,7 @@ use super::SessionMessage;
 use crate::controllers;
 use crate::controllers::ControllerExt;
 use crate::providers;
-use crate::providers::ProviderExt;
+use crate::providers::Provider;
 use crate::ConfiguredAccessories;
 use async_trait::async_trait;
 use axum::body::Body;
@@ -19,11 +19,19 @@ use houseflow_config::hub::Accessory;
 use houseflow_config::hub::HiveProvider as Config;
 use houseflow_types::accessory;
 use houseflow_types::hive;
+use houseflow_types::protocol;
+use houseflow_types::routing;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::sync::oneshot;
 
+/// Time given to an accessory to send its `Hello`, accessories which don't send it are legacy ones
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Time given to an accessory speaking protocol version 4 or newer to send its capabilities after the handshake
+const CAPABILITIES_TIMEOUT: Duration = Duration::from_secs(5);
+
 type Server = ezsockets::Server<HiveProvider>;
 type Session = ezsockets::Session<accessory::ID, SessionMessage>;
 
@@ -39,6 +47,19 @@ pub struct HiveProvider {
     configured_accessories: ConfiguredAccessories,
 }
 
+impl HiveProvider {
+    /// Returns the session of a connected accessory, along with its ID to group batch requests by
+    fn route(
+        &self,
+        accessory_id: accessory::ID,
+    ) -> Result<(accessory::ID, Session), accessory::Error> {
+        self.sessions
+            .get(&accessory_id)
+            .map(|session| (accessory_id, session.clone()))
+            .ok_or(accessory::Error::NotConnected)
+    }
+}
+
 pub fn new(
     _config: Config,
     controller: controllers::MasterHandle,
@@ -66,17 +87,24 @@ impl ezsockets::ServerExt for HiveProvider {
         let Args { accessory } = args;
         let session = Session::create(
             |session| HiveSession {
+                announce_timer: Some(announce_after(session.clone(), HANDSHAKE_TIMEOUT)),
                 session,
                 accessory_id: accessory.id,
+                capabilities: accessory.r#type.capabilities(),
+                accessory: Some(accessory.clone()),
                 controller: self.controller.clone(),
                 characteristic_write_results: Default::default(),
                 characteristic_read_results: Default::default(),
+                batch_write_results: Default::default(),
+                batch_read_results: Default::default(),
+                identify_results: Default::default(),
+                peer: None,
+                encoding: Default::default(),
             },
             accessory.id,
             socket,
         );
         self.sessions.insert(accessory.id, session.clone());
-        self.controller.connected(accessory).await;
         Ok(session)
     }
 
@@ -85,6 +113,7 @@ impl ezsockets::ServerExt for HiveProvider {
         id: <Self::Session as ezsockets::SessionExt>::ID,
     ) -> Result<(), ezsockets::Error> {
         self.sessions.remove(&id).unwrap();
+        self.controller.disconnected(id).await;
         Ok(())
     }
 
@@ -126,6 +155,80 @@ impl ezsockets::ServerExt for HiveProvider {
                     .unwrap();
                 respond_to.send(result).unwrap();
             }
+            Message::ReadCharacteristics { reads, respond_to } => {
+                let reads: Vec<_> = reads
+                    .into_iter()
+                    .map(|read| (self.route(read.accessory_id), read))
+                    .collect();
+                tokio::spawn(async move {
+                    let results = routing::scatter(
+                        reads,
+                        |(accessory_id, _)| *accessory_id,
+                        |(_, session), reads| async move {
+                            session
+                                .call_with(|respond_to| SessionMessage::ReadCharacteristics {
+                                    reads,
+                                    respond_to,
+                                })
+                                .await
+                                .await
+                                .unwrap_or_default()
+                        },
+                    )
+                    .await;
+                    respond_to.send(results).unwrap();
+                });
+            }
+            Message::WriteCharacteristics { writes, respond_to } => {
+                let writes: Vec<_> = writes
+                    .into_iter()
+                    .map(|write| (self.route(write.accessory_id), write))
+                    .collect();
+                tokio::spawn(async move {
+                    let results = routing::scatter(
+                        writes,
+                        |(accessory_id, _)| *accessory_id,
+                        |(_, session), writes| async move {
+                            session
+                                .call_with(|respond_to| SessionMessage::WriteCharacteristics {
+                                    writes,
+                                    respond_to,
+                                })
+                                .await
+                                .await
+                                .unwrap_or_default()
+                        },
+                    )
+                    .await;
+                    respond_to.send(results).unwrap();
+                });
+            }
+            Message::Identify {
+                accessory_id,
+                respond_to,
+            } => {
+                let session = self.sessions.get(&accessory_id).unwrap();
+                let result = session
+                    .call_with(|respond_to| SessionMessage::Identify { respond_to })
+                    .await
+                    .await
+                    .unwrap();
+                respond_to.send(result).unwrap();
+            }
+            Message::GetAccessoryCapabilities {
+                accessory_id,
+                respond_to,
+            } => {
+                let capabilities = match self.sessions.get(&accessory_id) {
+                    Some(session) => Some(
+                        session
+                            .call_with(|respond_to| SessionMessage::GetCapabilities { respond_to })
+                            .await,
+                    ),
+                    None => None,
+                };
+                respond_to.send(capabilities).unwrap();
+            }
             Message::GetAccessoryConfiguration {
                 accessory_id,
                 respond_to,
@@ -232,9 +335,18 @@ pub async fn websocket_handler(
     Ok(websocket.on_upgrade(server, Args { accessory }))
 }
 
+/// Requests sent to the peer which are awaiting a response, by the ID of the request frame
+type Pending<T> = HashMap<hive::FrameID, oneshot::Sender<T>>;
+
 pub struct HiveSession {
     session: Session,
     accessory_id: accessory::ID,
+    /// Configuration of the accessory, `Some` until the accessory is announced to the controllers
+    accessory: Option<Accessory>,
+    /// Capabilities of the accessory, writes are validated against them before being sent
+    capabilities: accessory::capabilities::Capabilities,
+    /// Announces the accessory with the default capabilities of its type if it doesn't send its own in time
+    announce_timer: Option<tokio::task::JoinHandle<()>>,
     controller: controllers::MasterHandle,
     characteristic_write_results:
         HashMap<hive::FrameID, oneshot::Sender<Result<(), accessory::Error>>>,
@@ -242,6 +354,277 @@ pub struct HiveSession {
         hive::FrameID,
         oneshot::Sender<Result<accessory::characteristics::Characteristic, accessory::Error>>,
     >,
+    batch_write_results: HashMap<hive::FrameID, oneshot::Sender<Vec<Result<(), accessory::Error>>>>,
+    batch_read_results: HashMap<
+        hive::FrameID,
+        oneshot::Sender<Vec<Result<accessory::characteristics::Characteristic, accessory::Error>>>,
+    >,
+    identify_results: HashMap<hive::FrameID, oneshot::Sender<Result<(), accessory::Error>>>,
+    /// Handshake sent by the accessory, `None` for legacy accessories which don't send it
+    peer: Option<protocol::Hello>,
+    /// Encoding negotiated during the handshake, JSON until then
+    encoding: protocol::Encoding,
+}
+
+impl HiveSession {
+    async fn send(&mut self, frame: hive::HubFrame) -> Result<(), protocol::SendError> {
+        let frame_type = hive::HubFrameType::from(&frame).to_string();
+        if let Some(peer) = &self.peer {
+            if !peer.supports(&frame_type) {
+                return Err(protocol::SendError::UnsupportedFrame(frame_type));
+            }
+        }
+        match self.encoding.encode(&frame)? {
+            protocol::Payload::Text(text) => self.session.text(text).await,
+            protocol::Payload::Binary(bytes) => self.session.binary(bytes).await,
+        }
+        Ok(())
+    }
+
+    /// Sends a frame that doesn't expect a response, frames not supported by the accessory are dropped.
+    async fn notify(&mut self, frame: hive::HubFrame) -> Result<(), ezsockets::Error> {
+        match self.send(frame).await {
+            Err(protocol::SendError::UnsupportedFrame(frame_type)) => {
+                tracing::warn!(accessory_id = %self.accessory_id, "accessory does not support `{}` frames, dropping", frame_type);
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// Sends a request frame and stores `respond_to` in `pending` until the accessory responds.
+    ///
+    /// If the accessory does not support the frame, `respond_to` is answered right away with the error mapped by `unsupported`.
+    async fn request<T>(
+        &mut self,
+        pending: fn(&mut Self) -> &mut Pending<T>,
+        frame_id: hive::FrameID,
+        frame: hive::HubFrame,
+        respond_to: oneshot::Sender<T>,
+        unsupported: impl FnOnce(accessory::Error) -> T,
+    ) -> Result<(), ezsockets::Error> {
+        pending(self).insert(frame_id, respond_to);
+        match self.send(frame).await {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                let respond_to = pending(self).remove(&frame_id).unwrap();
+                match err {
+                    protocol::SendError::UnsupportedFrame(frame_type) => {
+                        tracing::warn!(accessory_id = %self.accessory_id, "accessory does not support `{}` frames", frame_type);
+                        let _ = respond_to.send(unsupported(accessory::Error::RequestNotSupported));
+                        Ok(())
+                    }
+                    err => Err(err.into()),
+                }
+            }
+        }
+    }
+
+    /// Answers the pending request with the result sent by the accessory.
+    ///
+    /// Results of unknown requests, e.g. duplicated ones, are dropped and reported back to the accessory.
+    async fn respond<T>(
+        &mut self,
+        pending: fn(&mut Self) -> &mut Pending<T>,
+        frame_id: hive::FrameID,
+        result: T,
+    ) -> Result<(), ezsockets::Error> {
+        match pending(self).remove(&frame_id) {
+            Some(respond_to) => {
+                // the requester might have stopped waiting
+                let _ = respond_to.send(result);
+                Ok(())
+            }
+            None => {
+                tracing::warn!(accessory_id = %self.accessory_id, "received result of unknown request {}, ignoring", frame_id);
+                self.notify(hive::HubFrame::Error(protocol::Error::MalformedFrame(
+                    format!("unknown request id {}", frame_id),
+                )))
+                .await
+            }
+        }
+    }
+
+    /// Sends a batch request as a single batch frame, or as one frame per item to accessories which don't support `batch_frame_type`.
+    ///
+    /// Returns the receiver of the results, in the order of the items.
+    #[allow(clippy::too_many_arguments)]
+    async fn batch_request<T, R: std::fmt::Debug + Clone + Send + 'static>(
+        &mut self,
+        items: Vec<T>,
+        batch_frame_type: hive::HubFrameType,
+        batch_frame: impl FnOnce(hive::FrameID, Vec<T>) -> hive::HubFrame,
+        single_frame: impl Fn(hive::FrameID, T) -> hive::HubFrame,
+        pending_batch: fn(&mut Self) -> &mut Pending<routing::Results<R>>,
+        pending_single: fn(&mut Self) -> &mut Pending<Result<R, accessory::Error>>,
+    ) -> Result<oneshot::Receiver<routing::Results<R>>, ezsockets::Error> {
+        let (response_tx, response_rx) = oneshot::channel();
+        if items.is_empty() {
+            response_tx.send(Vec::new()).unwrap();
+        } else if self.peer_supports(batch_frame_type) {
+            let len = items.len();
+            let frame_id = rand::random();
+            let frame = batch_frame(frame_id, items);
+            self.request(pending_batch, frame_id, frame, response_tx, |err| {
+                vec![Err(err); len]
+            })
+            .await?;
+        } else {
+            let mut receivers = Vec::with_capacity(items.len());
+            for item in items {
+                let frame_id = rand::random();
+                let (sender, receiver) = oneshot::channel();
+                self.request(
+                    pending_single,
+                    frame_id,
+                    single_frame(frame_id, item),
+                    sender,
+                    Err,
+                )
+                .await?;
+                receivers.push(receiver);
+            }
+            tokio::spawn(async move {
+                response_tx.send(routing::join(receivers).await).unwrap();
+            });
+        }
+        Ok(response_rx)
+    }
+
+    /// Returns true if the accessory declared support for the frame type, legacy accessories support only single reads and writes.
+    fn peer_supports(&self, frame_type: hive::HubFrameType) -> bool {
+        self.peer
+            .as_ref()
+            .is_some_and(|peer| peer.supports(&frame_type.to_string()))
+    }
+
+    /// Announces the accessory to the controllers, once its capabilities are known.
+    ///
+    /// Accessories speaking protocol version 4 or newer are announced after they send their capabilities,
+    /// older ones right after the handshake, and legacy ones when they send their first frame.
+    /// Accessories which don't get there in time are announced with the default capabilities of their type by the `announce_timer`.
+    async fn announce(&mut self) {
+        if let Some(timer) = self.announce_timer.take() {
+            timer.abort();
+        }
+        if let Some(accessory) = self.accessory.take() {
+            self.controller.connected(accessory).await;
+        }
+    }
+
+    async fn handshake(&mut self, peer: protocol::Hello) -> Result<(), ezsockets::Error> {
+        let mut hello = protocol::hello!(
+            hive::PROTOCOL_VERSION,
+            hive::MIN_PROTOCOL_VERSION,
+            hive::AccessoryFrameType::VARIANTS,
+        );
+        match hello.negotiate(&peer) {
+            Ok(version) => {
+                let encoding = hello.negotiate_encoding(&peer);
+                tracing::info!(accessory_id = %self.accessory_id, software_version = %peer.software_version, %encoding, "negotiated hive protocol version {}", version);
+                hello.protocol_version = version;
+                hello.encodings = vec![encoding];
+                self.peer = Some(peer);
+                self.notify(hive::HubFrame::Hello(hello)).await?;
+                self.encoding = encoding;
+                if version < hive::CAPABILITIES_PROTOCOL_VERSION {
+                    self.announce().await;
+                } else if let Some(timer) = self
+                    .announce_timer
+                    .replace(announce_after(self.session.clone(), CAPABILITIES_TIMEOUT))
+                {
+                    timer.abort();
+                }
+                Ok(())
+            }
+            Err(err) => {
+                tracing::error!(accessory_id = %self.accessory_id, software_version = %peer.software_version, "rejecting accessory: {}", err);
+                self.notify(hive::HubFrame::Error(err.clone())).await?;
+                Err(err.into())
+            }
+        }
+    }
+
+    async fn frame(
+        &mut self,
+        frame: Result<hive::AccessoryFrame, protocol::Error>,
+    ) -> Result<(), ezsockets::Error> {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                tracing::warn!(accessory_id = %self.accessory_id, "received invalid frame: {}", err);
+                return self.notify(hive::HubFrame::Error(err)).await;
+            }
+        };
+        if self.peer.is_none() && !matches!(frame, hive::AccessoryFrame::Hello(_)) {
+            self.announce().await;
+        }
+        match frame {
+            hive::AccessoryFrame::Hello(hello) => self.handshake(hello).await?,
+            hive::AccessoryFrame::Error(err) => {
+                tracing::warn!(accessory_id = %self.accessory_id, "accessory responded with error: {}", err);
+            }
+            hive::AccessoryFrame::Capabilities(_) if self.accessory.is_none() => {
+                tracing::warn!(accessory_id = %self.accessory_id, "received capabilities of an already announced accessory, ignoring");
+                self.notify(hive::HubFrame::Error(protocol::Error::MalformedFrame(
+                    String::from("capabilities were already announced"),
+                )))
+                .await?;
+            }
+            hive::AccessoryFrame::Capabilities(frame) => {
+                if let Some(capabilities) = frame.capabilities {
+                    self.capabilities = capabilities;
+                }
+                self.announce().await;
+            }
+            hive::AccessoryFrame::UpdateCharacteristic(frame) => {
+                self.controller
+                    .updated(self.accessory_id, frame.service_name, frame.characteristic)
+                    .await;
+            }
+            hive::AccessoryFrame::CharacteristicReadResult(frame) => {
+                self.respond(
+                    |session| &mut session.characteristic_read_results,
+                    frame.id,
+                    frame.result.into(),
+                )
+                .await?
+            }
+            hive::AccessoryFrame::CharacteristicWriteResult(frame) => {
+                self.respond(
+                    |session| &mut session.characteristic_write_results,
+                    frame.id,
+                    frame.result.into(),
+                )
+                .await?
+            }
+            hive::AccessoryFrame::BatchCharacteristicReadResult(frame) => {
+                self.respond(
+                    |session| &mut session.batch_read_results,
+                    frame.id,
+                    frame.results.into_iter().map(Into::into).collect(),
+                )
+                .await?
+            }
+            hive::AccessoryFrame::BatchCharacteristicWriteResult(frame) => {
+                self.respond(
+                    |session| &mut session.batch_write_results,
+                    frame.id,
+                    frame.results.into_iter().map(Into::into).collect(),
+                )
+                .await?
+            }
+            hive::AccessoryFrame::IdentifyResult(frame) => {
+                self.respond(
+                    |session| &mut session.identify_results,
+                    frame.id,
+                    frame.result.into(),
+                )
+                .await?
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -255,31 +638,13 @@ impl ezsockets::SessionExt for HiveSession {
     }
 
     async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
-        let json = serde_json::from_str::<hive::AccessoryFrame>(&text)?;
-        match json {
-            hive::AccessoryFrame::UpdateCharacteristic(frame) => {
-                self.controller
-                    .updated(self.accessory_id, frame.service_name, frame.characteristic)
-                    .await;
-            }
-            hive::AccessoryFrame::CharacteristicReadResult(frame) => self
-                .characteristic_read_results
-                .remove(&frame.id)
-                .unwrap()
-                .send(frame.result.into())
-                .unwrap(),
-            hive::AccessoryFrame::CharacteristicWriteResult(frame) => self
-                .characteristic_write_results
-                .remove(&frame.id)
-                .unwrap()
-                .send(frame.result.into())
-                .unwrap(),
-        }
-        Ok(())
+        let frame = protocol::decode(&text, hive::AccessoryFrameType::VARIANTS);
+        self.frame(frame).await
     }
 
-    async fn binary(&mut self, _bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
-        todo!()
+    async fn binary(&mut self, bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode_binary(&bytes, hive::AccessoryFrameType::VARIANTS);
+        self.frame(frame).await
     }
 
     async fn call(&mut self, params: Self::Params) -> Result<(), ezsockets::Error> {
@@ -295,11 +660,15 @@ impl ezsockets::SessionExt for HiveSession {
                     service_name,
                     characteristic_name,
                 });
-                let text = serde_json::to_string(&frame)?;
                 let (response_tx, response_rx) = oneshot::channel();
-                self.characteristic_read_results
-                    .insert(frame_id, response_tx);
-                self.session.text(text).await;
+                self.request(
+                    |session| &mut session.characteristic_read_results,
+                    frame_id,
+                    frame,
+                    response_tx,
+                    Err,
+                )
+                .await?;
                 respond_to.send(response_rx).unwrap();
             }
             SessionMessage::WriteCharacteristic {
@@ -307,20 +676,165 @@ impl ezsockets::SessionExt for HiveSession {
                 characteristic,
                 respond_to,
             } => {
+                if let Err(err) = self
+                    .capabilities
+                    .validate_write(service_name, &characteristic)
+                {
+                    let (response_tx, response_rx) = oneshot::channel();
+                    response_tx.send(Err(err)).unwrap();
+                    respond_to.send(response_rx).unwrap();
+                    return Ok(());
+                }
                 let frame_id = rand::random();
                 let frame = hive::HubFrame::WriteCharacteristic(hive::WriteCharacteristic {
                     id: frame_id,
                     service_name,
                     characteristic,
                 });
-                let text = serde_json::to_string(&frame)?;
                 let (response_tx, response_rx) = oneshot::channel();
-                self.characteristic_write_results
-                    .insert(frame_id, response_tx);
-                self.session.text(text).await;
+                self.request(
+                    |session| &mut session.characteristic_write_results,
+                    frame_id,
+                    frame,
+                    response_tx,
+                    Err,
+                )
+                .await?;
                 respond_to.send(response_rx).unwrap();
             }
+            SessionMessage::ReadCharacteristics { reads, respond_to } => {
+                let response_rx = self
+                    .batch_request(
+                        reads,
+                        hive::HubFrameType::BatchReadCharacteristic,
+                        |id, reads| {
+                            hive::HubFrame::BatchReadCharacteristic(hive::BatchReadCharacteristic {
+                                id,
+                                reads: reads
+                                    .into_iter()
+                                    .map(|read| hive::CharacteristicRead {
+                                        service_name: read.service_name,
+                                        characteristic_name: read.characteristic_name,
+                                    })
+                                    .collect(),
+                            })
+                        },
+                        |id, read| {
+                            hive::HubFrame::ReadCharacteristic(hive::ReadCharacteristic {
+                                id,
+                                service_name: read.service_name,
+                                characteristic_name: read.characteristic_name,
+                            })
+                        },
+                        |session| &mut session.batch_read_results,
+                        |session| &mut session.characteristic_read_results,
+                    )
+                    .await?;
+                respond_to.send(response_rx).unwrap();
+            }
+            SessionMessage::WriteCharacteristics { writes, respond_to } => {
+                // writes rejected by the capabilities are not sent to the accessory
+                let writes = writes.into_iter().map(|write| {
+                    let result = self
+                        .capabilities
+                        .validate_write(write.service_name, &write.characteristic);
+                    (result, write)
+                });
+                let (mut batches, mut results) = routing::split(writes, |_| ());
+                let (indexes, writes) = batches
+                    .pop()
+                    .map(|batch| (batch.indexes, batch.items))
+                    .unwrap_or_default();
+                let batch_rx = self
+                    .batch_request(
+                        writes,
+                        hive::HubFrameType::BatchWriteCharacteristic,
+                        |id, writes| {
+                            hive::HubFrame::BatchWriteCharacteristic(
+                                hive::BatchWriteCharacteristic {
+                                    id,
+                                    writes: writes
+                                        .into_iter()
+                                        .map(|write| hive::CharacteristicWrite {
+                                            service_name: write.service_name,
+                                            characteristic: write.characteristic,
+                                        })
+                                        .collect(),
+                                },
+                            )
+                        },
+                        |id, write| {
+                            hive::HubFrame::WriteCharacteristic(hive::WriteCharacteristic {
+                                id,
+                                service_name: write.service_name,
+                                characteristic: write.characteristic,
+                            })
+                        },
+                        |session| &mut session.batch_write_results,
+                        |session| &mut session.characteristic_write_results,
+                    )
+                    .await?;
+                let (response_tx, response_rx) = oneshot::channel();
+                tokio::spawn(async move {
+                    routing::merge(&mut results, indexes, batch_rx.await.unwrap_or_default());
+                    response_tx.send(results).unwrap();
+                });
+                respond_to.send(response_rx).unwrap();
+            }
+            SessionMessage::Identify { respond_to } => {
+                let (response_tx, response_rx) = oneshot::channel();
+                if self.peer_supports(hive::HubFrameType::Identify) {
+                    let frame_id = rand::random();
+                    let frame = hive::HubFrame::Identify(hive::Identify { id: frame_id });
+                    self.request(
+                        |session| &mut session.identify_results,
+                        frame_id,
+                        frame,
+                        response_tx,
+                        Err,
+                    )
+                    .await?;
+                } else {
+                    // accessories older than protocol version 5 can't identify themselves
+                    response_tx
+                        .send(Err(accessory::Error::RequestNotSupported))
+                        .unwrap();
+                }
+                respond_to.send(response_rx).unwrap();
+            }
+            SessionMessage::GetCapabilities { respond_to } => {
+                respond_to.send(self.capabilities.clone()).unwrap();
+            }
+            SessionMessage::AnnounceTimeout => {
+                if self.accessory.is_some() {
+                    match &self.peer {
+                        None => {
+                            tracing::info!(accessory_id = %self.accessory_id, "accessory did not send a hello, treating it as a legacy accessory")
+                        }
+                        Some(_) => {
+                            tracing::warn!(accessory_id = %self.accessory_id, "accessory did not send its capabilities, using the defaults")
+                        }
+                    }
+                    self.announce().await;
+                }
+            }
         };
         Ok(())
     }
 }
+
+impl Drop for HiveSession {
+    fn drop(&mut self) {
+        if let Some(timer) = self.announce_timer.take() {
+            timer.abort();
+        }
+    }
+}
+
+/// Asks the session to announce its accessory after `timeout`, unless the timer is aborted before
+fn announce_after(session: Session, timeout: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        tokio::time::sleep(timeout).await;
+        session.call(SessionMessage::AnnounceTimeout).await;
+    })
+}
@@ -195,6 +195,61 @@ impl MijiaProvider {
         Ok(())
     }
 
+    fn write_characteristic(
+        &self,
+        service_name: ServiceName,
+        characteristic: &Characteristic,
+    ) -> Result<(), accessory::Error> {
+        let error = if service_name == ServiceName::TemperatureSensor {
+            if matches!(characteristic, Characteristic::CurrentTemperature(_)) {
+                accessory::Error::CharacteristicReadOnly
+            } else {
+                accessory::Error::CharacteristicNotSupported
+            }
+        } else {
+            accessory::Error::ServiceNotSupported
+        };
+        Err(error)
+    }
+
+    fn read_characteristic(
+        &self,
+        accessory_id: &AccessoryID,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+    ) -> Result<Characteristic, accessory::Error> {
+        let last_readings = self
+            .last_readings
+            .get(accessory_id)
+            .ok_or(accessory::Error::NotConnected)?;
+
+        match service_name {
+            accessory::services::ServiceName::TemperatureSensor => {
+                if characteristic_name == CharacteristicName::CurrentTemperature {
+                    Ok(Characteristic::CurrentTemperature(
+                        characteristics::CurrentTemperature {
+                            temperature: last_readings.temperature,
+                        },
+                    ))
+                } else {
+                    Err(accessory::Error::CharacteristicNotSupported)
+                }
+            }
+            accessory::services::ServiceName::HumiditySensor => {
+                if characteristic_name == CharacteristicName::CurrentHumidity {
+                    Ok(Characteristic::CurrentHumidity(
+                        characteristics::CurrentHumidity {
+                            humidity: last_readings.humidity as f32,
+                        },
+                    ))
+                } else {
+                    Err(accessory::Error::CharacteristicNotSupported)
+                }
+            }
+            _ => Err(accessory::Error::ServiceNotSupported),
+        }
+    }
+
     async fn handle_provider_message(&mut self, message: Message) -> Result<(), Error> {
         match message {
             Message::WriteCharacteristic {
@@ -203,17 +258,9 @@ impl MijiaProvider {
                 characteristic,
                 respond_to,
             } => {
-                let error = if service_name == ServiceName::TemperatureSensor {
-                    if matches!(characteristic, Characteristic::CurrentTemperature(_)) {
-                        accessory::Error::CharacteristicReadOnly
-                    } else {
-                        accessory::Error::CharacteristicNotSupported
-                    }
-                } else {
-                    accessory::Error::ServiceNotSupported
-                };
-
-                respond_to.send(Err(error)).unwrap();
+                respond_to
+                    .send(self.write_characteristic(service_name, &characteristic))
+                    .unwrap();
             }
             Message::ReadCharacteristic {
                 accessory_id,
@@ -221,34 +268,42 @@ impl MijiaProvider {
                 characteristic_name,
                 respond_to,
             } => {
-                let last_readings = self.last_readings.get(&accessory_id).unwrap().clone();
-
-                let characteristic = match service_name {
-                    accessory::services::ServiceName::TemperatureSensor => {
-                        if characteristic_name == CharacteristicName::CurrentTemperature {
-                            Ok(Characteristic::CurrentTemperature(
-                                characteristics::CurrentTemperature {
-                                    temperature: last_readings.temperature,
-                                },
-                            ))
-                        } else {
-                            Err(accessory::Error::CharacteristicNotSupported)
-                        }
-                    }
-                    accessory::services::ServiceName::HumiditySensor => {
-                        if characteristic_name == CharacteristicName::CurrentHumidity {
-                            Ok(Characteristic::CurrentHumidity(
-                                characteristics::CurrentHumidity {
-                                    humidity: last_readings.humidity as f32,
-                                },
-                            ))
-                        } else {
-                            Err(accessory::Error::CharacteristicNotSupported)
-                        }
-                    }
-                    _ => Err(accessory::Error::ServiceNotSupported),
-                };
-                respond_to.send(characteristic).unwrap();
+                respond_to
+                    .send(self.read_characteristic(
+                        &accessory_id,
+                        service_name,
+                        characteristic_name,
+                    ))
+                    .unwrap();
+            }
+            Message::ReadCharacteristics { reads, respond_to } => {
+                let results = reads
+                    .into_iter()
+                    .map(|read| {
+                        self.read_characteristic(
+                            &read.accessory_id,
+                            read.service_name,
+                            read.characteristic_name,
+                        )
+                    })
+                    .collect();
+                respond_to.send(results).unwrap();
+            }
+            Message::WriteCharacteristics { writes, respond_to } => {
+                let results = writes
+                    .into_iter()
+                    .map(|write| {
+                        self.write_characteristic(write.service_name, &write.characteristic)
+                    })
+                    .collect();
+                respond_to.send(results).unwrap();
+            }
+            Message::Identify {
+                accessory_id: _,
+                respond_to,
+            } => {
+                // the sensors have no way to identify themselves
+                respond_to.send(Ok(())).unwrap();
             }
             Message::IsConnected {
                 accessory_id,
@@ -261,6 +316,21 @@ impl MijiaProvider {
                     )
                     .unwrap();
             }
+            Message::GetAccessoryCapabilities {
+                accessory_id,
+                respond_to,
+            } => {
+                let capabilities = self
+                    .bluetooth_device_id_by_accessory_id(&accessory_id)
+                    .and_then(|_| {
+                        self.configured_accessories
+                            .load()
+                            .iter()
+                            .find(|accessory| accessory.id == accessory_id)
+                            .map(|accessory| accessory.r#type.capabilities())
+                    });
+                respond_to.send(capabilities).unwrap();
+            }
             Message::GetAccessoryConfiguration {
                 accessory_id,
                 respond_to,
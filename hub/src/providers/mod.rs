@@ -9,10 +9,14 @@ use async_trait::async_trait;
 use futures::future;
 use houseflow_config::hub::Accessory;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::accessory::CharacteristicRead;
+use houseflow_types::accessory::CharacteristicWrite;
 use houseflow_types::accessory::{Error, ID};
+use houseflow_types::routing;
 use tokio::sync::oneshot;
 
 #[derive(Debug, Clone, PartialEq, Eq, strum::Display, strum::IntoStaticStr)]
@@ -42,10 +46,26 @@ pub enum Message {
         characteristic: Characteristic,
         respond_to: oneshot::Sender<Result<(), accessory::Error>>,
     },
+    ReadCharacteristics {
+        reads: Vec<CharacteristicRead>,
+        respond_to: oneshot::Sender<Vec<Result<Characteristic, accessory::Error>>>,
+    },
+    WriteCharacteristics {
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<Vec<Result<(), accessory::Error>>>,
+    },
+    Identify {
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Result<(), accessory::Error>>,
+    },
     GetAccessoryConfiguration {
         accessory_id: accessory::ID,
         respond_to: oneshot::Sender<Option<Accessory>>,
     },
+    GetAccessoryCapabilities {
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Option<Capabilities>>,
+    },
     IsConnected {
         accessory_id: accessory::ID,
         respond_to: oneshot::Sender<bool>,
@@ -54,69 +74,88 @@ pub enum Message {
 
 impl acu::Message for Message {}
 
-pub type Handle = acu::Handle<Message, Name>;
-
-#[async_trait]
-pub trait ProviderExt {
-    async fn read_characteristic(
-        &self,
-        accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic_name: CharacteristicName,
-    ) -> Result<Characteristic, accessory::Error>;
-    async fn write_characteristic(
-        &self,
-        accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic: Characteristic,
-    ) -> Result<(), accessory::Error>;
-    async fn is_connected(&self, accessory_id: accessory::ID) -> bool;
-    async fn get_accessory_configuration(&self, accessory_id: accessory::ID) -> Option<Accessory>;
-}
-
-#[async_trait]
-impl ProviderExt for Handle {
-    async fn read_characteristic(
-        &self,
+impl routing::ProviderMessage for Message {
+    fn read_characteristic(
         accessory_id: ID,
         service_name: ServiceName,
         characteristic_name: CharacteristicName,
-    ) -> Result<Characteristic, Error> {
-        self.sender
-            .call_with(|respond_to| Message::ReadCharacteristic {
-                accessory_id,
-                service_name,
-                characteristic_name,
-                respond_to,
-            })
-            .await
+        respond_to: oneshot::Sender<Result<Characteristic, Error>>,
+    ) -> Self {
+        Self::ReadCharacteristic {
+            accessory_id,
+            service_name,
+            characteristic_name,
+            respond_to,
+        }
     }
 
-    async fn write_characteristic(
-        &self,
+    fn write_characteristic(
         accessory_id: ID,
         service_name: ServiceName,
         characteristic: Characteristic,
-    ) -> Result<(), Error> {
-        self.sender
-            .call_with(|respond_to| Message::WriteCharacteristic {
-                accessory_id,
-                service_name,
-                characteristic,
-                respond_to,
-            })
-            .await
+        respond_to: oneshot::Sender<Result<(), Error>>,
+    ) -> Self {
+        Self::WriteCharacteristic {
+            accessory_id,
+            service_name,
+            characteristic,
+            respond_to,
+        }
     }
 
-    async fn is_connected(&self, accessory_id: ID) -> bool {
-        self.sender
-            .call_with(|respond_to| Message::IsConnected {
-                accessory_id,
-                respond_to,
-            })
-            .await
+    fn read_characteristics(
+        reads: Vec<CharacteristicRead>,
+        respond_to: oneshot::Sender<Vec<Result<Characteristic, Error>>>,
+    ) -> Self {
+        Self::ReadCharacteristics { reads, respond_to }
+    }
+
+    fn write_characteristics(
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<Vec<Result<(), Error>>>,
+    ) -> Self {
+        Self::WriteCharacteristics { writes, respond_to }
     }
 
+    fn identify(accessory_id: ID, respond_to: oneshot::Sender<Result<(), Error>>) -> Self {
+        Self::Identify {
+            accessory_id,
+            respond_to,
+        }
+    }
+
+    fn is_connected(accessory_id: ID, respond_to: oneshot::Sender<bool>) -> Self {
+        Self::IsConnected {
+            accessory_id,
+            respond_to,
+        }
+    }
+
+    fn get_accessory_capabilities(
+        accessory_id: ID,
+        respond_to: oneshot::Sender<Option<Capabilities>>,
+    ) -> Self {
+        Self::GetAccessoryCapabilities {
+            accessory_id,
+            respond_to,
+        }
+    }
+}
+
+pub type Handle = acu::Handle<Message, Name>;
+
+/// Handle to all of the providers, see `routing::Master`.
+pub type MasterHandle = routing::Master<Message, Name>;
+
+pub use routing::Provider;
+
+#[async_trait]
+pub trait ProviderExt: Provider {
+    async fn get_accessory_configuration(&self, accessory_id: accessory::ID) -> Option<Accessory>;
+}
+
+#[async_trait]
+impl ProviderExt for Handle {
     async fn get_accessory_configuration(&self, accessory_id: ID) -> Option<Accessory> {
         self.sender
             .call_with(|respond_to| Message::GetAccessoryConfiguration {
@@ -127,63 +166,17 @@ impl ProviderExt for Handle {
     }
 }
 
-pub type MasterHandle = acu::MasterHandle<Message, Name>;
-
 #[async_trait]
 impl ProviderExt for MasterHandle {
-    async fn read_characteristic(
-        &self,
-        accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic_name: CharacteristicName,
-    ) -> Result<Characteristic, accessory::Error> {
-        let slaves = self.slaves().await;
-        let futures = slaves
-            .iter()
-            .map(|handle| async move { (handle, handle.is_connected(accessory_id).await) });
-        let results = future::join_all(futures).await;
-        let slave = results
-            .iter()
-            .find_map(|(handle, connected)| if *connected { Some(handle) } else { None })
-            .ok_or(accessory::Error::NotConnected)?;
-        slave
-            .read_characteristic(accessory_id, service_name, characteristic_name)
-            .await
-    }
-
-    async fn write_characteristic(
-        &self,
-        accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic: Characteristic,
-    ) -> Result<(), accessory::Error> {
-        let slaves = self.slaves().await;
-        let futures = slaves
-            .iter()
-            .map(|handle| async move { (handle, handle.is_connected(accessory_id).await) });
-        let results = future::join_all(futures).await;
-        let slave = results
-            .iter()
-            .find_map(|(handle, connected)| if *connected { Some(handle) } else { None })
-            .ok_or(accessory::Error::NotConnected)?;
-        slave
-            .write_characteristic(accessory_id, service_name, characteristic)
-            .await
-    }
-
-    async fn is_connected(&self, accessory_id: accessory::ID) -> bool {
+    async fn get_accessory_configuration(&self, accessory_id: accessory::ID) -> Option<Accessory> {
+        if let Some(slave) = self.route(accessory_id).await {
+            return slave.get_accessory_configuration(accessory_id).await;
+        }
         let slaves = self.slaves().await;
         let futures = slaves
             .iter()
-            .map(|handle| handle.is_connected(accessory_id));
-        let results = future::join_all(futures).await;
-        results.iter().any(|connected| *connected)
-    }
-
-    async fn get_accessory_configuration(&self, accessory_id: accessory::ID) -> Option<Accessory> {
-        let slaves = self.slaves().await;
-        let slave = slaves.first().unwrap(); // TODO: Do something different maybe?
-        slave.get_accessory_configuration(accessory_id).await
+            .map(|handle| handle.get_accessory_configuration(accessory_id));
+        future::join_all(futures).await.into_iter().flatten().next()
     }
 }
 
@@ -207,6 +200,23 @@ pub enum SessionMessage {
         characteristic: Characteristic,
         respond_to: oneshot::Sender<oneshot::Receiver<Result<(), accessory::Error>>>,
     },
+    ReadCharacteristics {
+        reads: Vec<CharacteristicRead>,
+        respond_to:
+            oneshot::Sender<oneshot::Receiver<Vec<Result<Characteristic, accessory::Error>>>>,
+    },
+    WriteCharacteristics {
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<oneshot::Receiver<Vec<Result<(), accessory::Error>>>>,
+    },
+    Identify {
+        respond_to: oneshot::Sender<oneshot::Receiver<Result<(), accessory::Error>>>,
+    },
+    GetCapabilities {
+        respond_to: oneshot::Sender<Capabilities>,
+    },
+    /// Sent by the session to itself when the accessory hasn't been announced in time
+    AnnounceTimeout,
 }
 
 impl acu::Message for SessionMessage {}
@@ -226,6 +236,20 @@ pub trait SessionExt {
         service_name: ServiceName,
         characteristic: Characteristic,
     ) -> Result<(), accessory::Error>;
+
+    async fn read_characteristics(
+        &self,
+        reads: Vec<CharacteristicRead>,
+    ) -> Vec<Result<Characteristic, accessory::Error>>;
+
+    async fn write_characteristics(
+        &self,
+        writes: Vec<CharacteristicWrite>,
+    ) -> Vec<Result<(), accessory::Error>>;
+
+    async fn identify(&self) -> Result<(), accessory::Error>;
+
+    async fn capabilities(&self) -> Capabilities;
 }
 
 #[async_trait]
@@ -261,4 +285,40 @@ impl SessionExt for SessionHandle {
             .await
             .unwrap()
     }
+
+    async fn read_characteristics(
+        &self,
+        reads: Vec<CharacteristicRead>,
+    ) -> Vec<Result<Characteristic, accessory::Error>> {
+        self.sender
+            .call_with(|respond_to| SessionMessage::ReadCharacteristics { reads, respond_to })
+            .await
+            .await
+            .unwrap()
+    }
+
+    async fn write_characteristics(
+        &self,
+        writes: Vec<CharacteristicWrite>,
+    ) -> Vec<Result<(), accessory::Error>> {
+        self.sender
+            .call_with(|respond_to| SessionMessage::WriteCharacteristics { writes, respond_to })
+            .await
+            .await
+            .unwrap()
+    }
+
+    async fn identify(&self) -> Result<(), accessory::Error> {
+        self.sender
+            .call_with(|respond_to| SessionMessage::Identify { respond_to })
+            .await
+            .await
+            .unwrap()
+    }
+
+    async fn capabilities(&self) -> Capabilities {
+        self.sender
+            .call_with(|respond_to| SessionMessage::GetCapabilities { respond_to })
+            .await
+    }
 }
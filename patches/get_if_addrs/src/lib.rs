@@ -0,0 +1,3 @@
+//! Re-exports `if-addrs` under the name of `get_if_addrs`, which it was forked from.
+
+pub use if_addrs::*;
@@ -0,0 +1,96 @@
+use crate::extensions;
+use crate::extractors::AdminID;
+use crate::providers::lighthouse::hash_password;
+use axum::Json;
+use houseflow_config::server::providers::LighthouseHub;
+use houseflow_types::admin::RegisterHubRequest;
+use houseflow_types::admin::RegisterHubResponse;
+use houseflow_types::errors::ServerError;
+use houseflow_types::hub;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+/// Length of the generated hub passwords
+const PASSWORD_LENGTH: usize = 32;
+
+/// Registers the hub with a generated password, which is returned only once
+#[tracing::instrument(name = "RegisterHub", skip(config, config_file, _admin_id), err)]
+pub async fn register(
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
+    AdminID(_admin_id): AdminID,
+    Json(request): Json<RegisterHubRequest>,
+) -> Result<Json<RegisterHubResponse>, ServerError> {
+    let password: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(PASSWORD_LENGTH)
+        .map(char::from)
+        .collect();
+    let hub = LighthouseHub {
+        id: hub::ID::new_v4(),
+        name: request.name,
+        password_hash: hash_password(&password),
+        structure_id: request.structure_id,
+    };
+    let id = hub.id;
+    super::update(&config, &config_file, |config| {
+        config
+            .providers
+            .lighthouse
+            .get_or_insert_with(Default::default)
+            .hubs
+            .push(hub);
+        Ok(())
+    })
+    .await?;
+    tracing::info!(hub_id = %id, "hub registered");
+    Ok(Json(RegisterHubResponse { id, password }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::ConfigFile;
+    use crate::extractors::AdminID;
+    use crate::providers::lighthouse::verify_password;
+    use crate::providers::lighthouse::PasswordVerification;
+    use crate::test_utils::*;
+    use axum::extract::Extension;
+    use axum::Json;
+    use houseflow_types::admin::RegisterHubRequest;
+    use houseflow_types::structure;
+    use houseflow_types::structure::Structure;
+
+    #[tokio::test]
+    async fn register() {
+        let admin = get_user();
+        let structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Zukago"),
+        };
+        let config = get_config(GetConfig {
+            users: vec![admin.clone()],
+            structures: vec![structure.clone()],
+            ..Default::default()
+        })
+        .await;
+        let Json(response) = super::register(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Json(RegisterHubRequest {
+                name: String::from("Simple Hub"),
+                structure_id: structure.id,
+            }),
+        )
+        .await
+        .unwrap();
+        let config = config.get();
+        let hub = &config.providers.lighthouse.as_ref().unwrap().hubs[0];
+        assert_eq!(hub.id, response.id);
+        assert_eq!(hub.structure_id, structure.id);
+        assert_eq!(
+            verify_password(&response.password, &hub.password_hash),
+            PasswordVerification::Valid
+        );
+    }
+}
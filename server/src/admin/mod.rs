@@ -0,0 +1,69 @@
+pub mod hubs;
+pub mod permissions;
+pub mod structures;
+pub mod users;
+
+use crate::extensions;
+use houseflow_config::server::Config;
+use houseflow_config::Config as _;
+use houseflow_types::errors::InternalError;
+use houseflow_types::errors::ServerError;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Configuration file that the changes made with the admin API are written back to.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    /// `None` keeps the changes only until the server restarts
+    path: Option<PathBuf>,
+    /// Changes are applied one at a time, so concurrent requests don't overwrite each other
+    lock: Arc<Mutex<()>>,
+}
+
+impl ConfigFile {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            lock: Default::default(),
+        }
+    }
+}
+
+/// Applies the change to a copy of the configuration and validates it.
+///
+/// The managed sections are written to the file before the configuration is swapped, so the server never uses changes that weren't persisted.
+pub(crate) async fn update<T>(
+    config: &extensions::Config,
+    file: &extensions::ConfigFile,
+    change: impl FnOnce(&mut Config) -> Result<T, ServerError>,
+) -> Result<T, ServerError> {
+    let _lock = file.lock.lock().await;
+    let mut updated = Config::clone(&config.get());
+    let result = change(&mut updated)?;
+    updated.validate().map_err(ServerError::ValidationError)?;
+    if let Some(path) = &file.path {
+        updated
+            .write_managed(path)
+            .map_err(|err| InternalError::Other(format!("write configuration: {}", err)))?;
+    }
+    config.update(updated);
+    Ok(result)
+}
+
+pub fn app() -> axum::Router {
+    use axum::routing::delete;
+    use axum::routing::get;
+    use axum::routing::post;
+
+    axum::Router::new()
+        .route("/users", get(users::list).post(users::add))
+        .route("/users/:user_id", delete(users::remove))
+        .route("/structures", post(structures::add))
+        .route("/permissions", post(permissions::grant))
+        .route(
+            "/permissions/:structure_id/:user_id",
+            delete(permissions::revoke),
+        )
+        .route("/hubs", post(hubs::register))
+}
@@ -0,0 +1,139 @@
+use crate::extensions;
+use crate::extractors::AdminID;
+use axum::extract::Path;
+use axum::Json;
+use houseflow_types::errors::ServerError;
+use houseflow_types::permission::Permission;
+use houseflow_types::structure;
+use houseflow_types::user;
+
+/// Grants the permission, replacing the current permission of the user to the structure
+#[tracing::instrument(name = "GrantPermission", skip(config, config_file, _admin_id), err)]
+pub async fn grant(
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
+    AdminID(_admin_id): AdminID,
+    Json(permission): Json<Permission>,
+) -> Result<(), ServerError> {
+    super::update(&config, &config_file, |config| {
+        config.permissions.retain(|current| {
+            current.structure_id != permission.structure_id || current.user_id != permission.user_id
+        });
+        config.permissions.push(permission);
+        Ok(())
+    })
+    .await
+}
+
+#[tracing::instrument(name = "RevokePermission", skip(config, config_file, _admin_id), err)]
+pub async fn revoke(
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
+    AdminID(_admin_id): AdminID,
+    Path((structure_id, user_id)): Path<(structure::ID, user::ID)>,
+) -> Result<(), ServerError> {
+    super::update(&config, &config_file, |config| {
+        config
+            .get_permission(&structure_id, &user_id)
+            .ok_or_else(|| {
+                ServerError::ValidationError(format!(
+                    "user {} has no permission to structure {}",
+                    user_id, structure_id
+                ))
+            })?;
+        config.permissions.retain(|permission| {
+            permission.structure_id != structure_id || permission.user_id != user_id
+        });
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::ConfigFile;
+    use crate::extractors::AdminID;
+    use crate::test_utils::*;
+    use axum::extract::Extension;
+    use axum::extract::Path;
+    use axum::Json;
+    use houseflow_types::errors::ServerError;
+    use houseflow_types::permission::Permission;
+    use houseflow_types::structure;
+    use houseflow_types::structure::Structure;
+
+    #[tokio::test]
+    async fn grant_and_revoke() {
+        let admin = get_user();
+        let user = get_user();
+        let structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Zukago"),
+        };
+        let config = get_config(GetConfig {
+            users: vec![admin.clone(), user.clone()],
+            structures: vec![structure.clone()],
+            ..Default::default()
+        })
+        .await;
+        let permission = Permission {
+            structure_id: structure.id,
+            user_id: user.id,
+            is_manager: false,
+        };
+        for is_manager in [false, true] {
+            super::grant(
+                config.clone(),
+                Extension(ConfigFile::default()),
+                AdminID(admin.id),
+                Json(Permission {
+                    is_manager,
+                    ..permission.clone()
+                }),
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            config.get().permissions,
+            vec![Permission {
+                is_manager: true,
+                ..permission.clone()
+            }]
+        );
+
+        super::revoke(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Path((structure.id, user.id)),
+        )
+        .await
+        .unwrap();
+        assert!(config.get().permissions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn grant_unknown_structure() {
+        let admin = get_user();
+        let config = get_config(GetConfig {
+            users: vec![admin.clone()],
+            ..Default::default()
+        })
+        .await;
+        let err = super::grant(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Json(Permission {
+                structure_id: structure::ID::new_v4(),
+                user_id: admin.id,
+                is_manager: true,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ServerError::ValidationError(_)));
+        assert!(config.get().permissions.is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::extensions;
+use crate::extractors::AdminID;
+use axum::Json;
+use houseflow_types::admin::AddStructureRequest;
+use houseflow_types::errors::ServerError;
+use houseflow_types::structure;
+use houseflow_types::structure::Structure;
+
+#[tracing::instrument(name = "AddStructure", skip(config, config_file, _admin_id), err)]
+pub async fn add(
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
+    AdminID(_admin_id): AdminID,
+    Json(request): Json<AddStructureRequest>,
+) -> Result<Json<Structure>, ServerError> {
+    let structure = super::update(&config, &config_file, |config| {
+        let structure = Structure {
+            id: structure::ID::new_v4(),
+            name: request.name,
+        };
+        config.structures.push(structure.clone());
+        Ok(structure)
+    })
+    .await?;
+    Ok(Json(structure))
+}
@@ -0,0 +1,166 @@
+use crate::extensions;
+use crate::extractors::AdminID;
+use axum::extract::Path;
+use axum::Json;
+use houseflow_types::admin::AddUserRequest;
+use houseflow_types::admin::ListUsersResponse;
+use houseflow_types::admin::RemoveUserResponse;
+use houseflow_types::errors::AuthError;
+use houseflow_types::errors::ServerError;
+use houseflow_types::user;
+use houseflow_types::user::User;
+
+#[tracing::instrument(name = "ListUsers", skip(config, _admin_id), err)]
+pub async fn list(
+    config: extensions::Config,
+    AdminID(_admin_id): AdminID,
+) -> Result<Json<ListUsersResponse>, ServerError> {
+    Ok(Json(ListUsersResponse {
+        users: config.get().users.clone(),
+    }))
+}
+
+#[tracing::instrument(name = "AddUser", skip(config, config_file, _admin_id), err)]
+pub async fn add(
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
+    AdminID(_admin_id): AdminID,
+    Json(request): Json<AddUserRequest>,
+) -> Result<Json<User>, ServerError> {
+    let user = super::update(&config, &config_file, |config| {
+        if config.get_user_by_email(&request.email).is_some() {
+            return Err(AuthError::UserAlreadyExists.into());
+        }
+        let user = User {
+            id: user::ID::new_v4(),
+            username: request.username,
+            email: request.email,
+            admin: request.admin,
+            preferences: Default::default(),
+        };
+        config.users.push(user.clone());
+        Ok(user)
+    })
+    .await?;
+    tracing::info!(user_id = %user.id, username = %user.username, "user added");
+    Ok(Json(user))
+}
+
+/// Removes the user together with their permissions
+#[tracing::instrument(name = "RemoveUser", skip(config, config_file, _admin_id), err)]
+pub async fn remove(
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
+    AdminID(_admin_id): AdminID,
+    Path(user_id): Path<user::ID>,
+) -> Result<Json<RemoveUserResponse>, ServerError> {
+    let removed_permissions = super::update(&config, &config_file, |config| {
+        config.get_user(&user_id).ok_or(AuthError::UserNotFound)?;
+        config.users.retain(|user| user.id != user_id);
+        let permissions = config.permissions.len();
+        config
+            .permissions
+            .retain(|permission| permission.user_id != user_id);
+        Ok(permissions - config.permissions.len())
+    })
+    .await?;
+    Ok(Json(RemoveUserResponse {
+        user_id,
+        removed_permissions,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::admin::ConfigFile;
+    use crate::extractors::AdminID;
+    use crate::test_utils::*;
+    use axum::extract::Extension;
+    use axum::extract::Path;
+    use axum::Json;
+    use houseflow_types::admin::AddUserRequest;
+    use houseflow_types::errors::AuthError;
+    use houseflow_types::errors::ServerError;
+    use houseflow_types::permission::Permission;
+    use houseflow_types::structure;
+    use houseflow_types::structure::Structure;
+
+    #[tokio::test]
+    async fn add_and_list() {
+        let admin = get_user();
+        let config = get_config(GetConfig {
+            users: vec![admin.clone()],
+            ..Default::default()
+        })
+        .await;
+        let request = AddUserRequest {
+            username: String::from("jane"),
+            email: lettre::Address::new("jane", "email.com").unwrap(),
+            admin: false,
+        };
+        let Json(user) = super::add(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Json(request.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.email, request.email);
+        let Json(response) = super::list(config.clone(), AdminID(admin.id))
+            .await
+            .unwrap();
+        assert_eq!(response.users, vec![admin.clone(), user]);
+
+        let err = super::add(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Json(request),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, ServerError::AuthError(AuthError::UserAlreadyExists));
+    }
+
+    #[tokio::test]
+    async fn remove() {
+        let admin = get_user();
+        let user = get_user();
+        let structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Zukago"),
+        };
+        let config = get_config(GetConfig {
+            users: vec![admin.clone(), user.clone()],
+            structures: vec![structure.clone()],
+            permissions: vec![Permission {
+                structure_id: structure.id,
+                user_id: user.id,
+                is_manager: false,
+            }],
+        })
+        .await;
+        let Json(response) = super::remove(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Path(user.id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.removed_permissions, 1);
+        assert_eq!(config.get().users, vec![admin.clone()]);
+        assert!(config.get().permissions.is_empty());
+
+        let err = super::remove(
+            config.clone(),
+            Extension(ConfigFile::default()),
+            AdminID(admin.id),
+            Path(user.id),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, ServerError::AuthError(AuthError::UserNotFound));
+    }
+}
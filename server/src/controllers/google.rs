@@ -0,0 +1,716 @@
+pub use super::Handle;
+
+use super::Message;
+use super::Name;
+use crate::extensions;
+use crate::extractors::UserID;
+use crate::google;
+use crate::providers;
+use crate::providers::Provider;
+use crate::providers::ProviderExt;
+use axum::extract::Extension;
+use axum::Json;
+use google_smart_home::execute;
+use google_smart_home::query;
+use google_smart_home::sync;
+use google_smart_home::Request;
+use google_smart_home::RequestInput;
+use google_smart_home::Response;
+use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
+use houseflow_types::accessory::Accessory;
+use houseflow_types::accessory::CharacteristicRead;
+use houseflow_types::accessory::CharacteristicWrite;
+use houseflow_types::errors::ServerError;
+use houseflow_types::structure;
+use houseflow_types::user;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+/// Accessories exposed to Google Home.
+///
+/// The accessories are kept up to date by the handle returned from `controller()`, which has to be pushed to the master controller.
+#[derive(Debug, Clone, Default)]
+pub struct Accessories {
+    accessories: Arc<RwLock<HashMap<accessory::ID, Accessory>>>,
+}
+
+impl Accessories {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a controller handle which keeps track of the `Connected`/`Disconnected` accessories.
+    pub fn controller(&self) -> Handle {
+        let (sender, mut receiver) = acu::channel(Name::Google);
+        let accessories = self.accessories.clone();
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    Message::Connected { accessory } => {
+                        accessories.write().unwrap().insert(accessory.id, accessory);
+                    }
+                    Message::Disconnected { accessory_id } => {
+                        accessories.write().unwrap().remove(&accessory_id);
+                    }
+                    Message::Updated { .. } => {}
+                }
+            }
+        });
+        Handle { sender }
+    }
+
+    fn get(&self, accessory_id: &accessory::ID) -> Option<Accessory> {
+        self.accessories.read().unwrap().get(accessory_id).cloned()
+    }
+
+    fn all(&self) -> Vec<Accessory> {
+        self.accessories.read().unwrap().values().cloned().collect()
+    }
+}
+
+pub fn app(accessories: Accessories) -> axum::Router {
+    use axum::routing::post;
+
+    axum::Router::new()
+        .route("/fulfillment", post(fulfillment))
+        .layer(Extension(accessories))
+}
+
+/// Handles the SYNC, QUERY, EXECUTE and DISCONNECT intents sent by Google on behalf of the user.
+pub async fn fulfillment(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Extension(accessories): Extension<Accessories>,
+    config: extensions::Config,
+    UserID(user_id): UserID,
+    Json(request): Json<Request>,
+) -> Result<Json<Response>, ServerError> {
+    let fulfillment = Fulfillment {
+        master_provider,
+        accessories,
+        config,
+        user_id,
+    };
+    // Google sends exactly one input per request
+    let input = request
+        .inputs
+        .into_iter()
+        .next()
+        .ok_or_else(|| ServerError::ValidationError(String::from("missing request input")))?;
+    let request_id = request.request_id;
+    let response = match input {
+        RequestInput::Sync => Response::Sync(sync::response::Response {
+            request_id,
+            payload: fulfillment.sync().await,
+        }),
+        RequestInput::Query(payload) => Response::Query(query::response::Response {
+            request_id,
+            payload: fulfillment.query(payload).await,
+        }),
+        RequestInput::Execute(payload) => Response::Execute(execute::response::Response {
+            request_id,
+            payload: fulfillment.execute(payload).await,
+        }),
+        RequestInput::Disconnect => Response::Disconnect,
+    };
+    Ok(Json(response))
+}
+
+struct Fulfillment {
+    master_provider: providers::MasterHandle,
+    accessories: Accessories,
+    config: extensions::Config,
+    user_id: user::ID,
+}
+
+impl Fulfillment {
+    /// Returns the structure of the accessory, if the user has permission to it.
+    async fn structure(&self, accessory_id: accessory::ID) -> Option<structure::ID> {
+        let structure_id = self
+            .master_provider
+            .get_accessory_structure(accessory_id)
+            .await?;
+        self.config
+            .get()
+            .get_permission(&structure_id, &self.user_id)
+            .map(|_| structure_id)
+    }
+
+    /// Returns the device of the user, or the reason why it can't be used.
+    async fn device(&self, device_id: &str) -> Result<Device, DeviceError> {
+        let accessory = device_id
+            .parse()
+            .ok()
+            .and_then(|accessory_id| self.accessories.get(&accessory_id))
+            .ok_or(DeviceError::NotFound)?;
+        let structure_id = self
+            .structure(accessory.id)
+            .await
+            .ok_or(DeviceError::NotFound)?;
+        let capabilities = self
+            .master_provider
+            .get_accessory_capabilities(accessory.id)
+            .await
+            .ok_or(DeviceError::Accessory(accessory::Error::NotConnected))?;
+        Ok(Device {
+            accessory,
+            capabilities,
+            structure_id,
+        })
+    }
+
+    async fn sync(&self) -> sync::response::Payload {
+        let mut devices = Vec::new();
+        for accessory in self.accessories.all() {
+            if self.structure(accessory.id).await.is_none() {
+                continue;
+            }
+            // accessory could disconnect in the meantime
+            let capabilities = match self
+                .master_provider
+                .get_accessory_capabilities(accessory.id)
+                .await
+            {
+                Some(capabilities) => capabilities,
+                None => continue,
+            };
+            devices.push(sync::response::PayloadDevice {
+                id: accessory.id.to_string(),
+                device_type: google::device_type(&accessory.r#type),
+                traits: google::traits(&capabilities),
+                name: sync::response::PayloadDeviceName {
+                    default_names: None,
+                    name: accessory.name,
+                    nicknames: None,
+                },
+                will_report_state: false,
+                notification_supported_by_agent: false,
+                room_hint: Some(accessory.room_name),
+                device_info: None,
+                attributes: google::attributes(&capabilities),
+                custom_data: None,
+                other_device_ids: None,
+            });
+        }
+        sync::response::Payload {
+            agent_user_id: self.user_id.to_string(),
+            error_code: None,
+            debug_string: None,
+            devices,
+        }
+    }
+
+    async fn query(&self, payload: query::request::Payload) -> query::response::Payload {
+        let mut devices = HashMap::new();
+        for device in payload.devices {
+            let state = self.query_device(&device.id).await;
+            devices.insert(device.id, state);
+        }
+        query::response::Payload {
+            error_code: None,
+            debug_string: None,
+            devices,
+        }
+    }
+
+    async fn query_device(&self, device_id: &str) -> query::response::PayloadDevice {
+        let Device {
+            accessory,
+            capabilities,
+            ..
+        } = match self.device(device_id).await {
+            Ok(device) => device,
+            Err(err) => return err.query_device(),
+        };
+        let reads = capabilities
+            .services
+            .iter()
+            .flat_map(|service| {
+                service
+                    .characteristics
+                    .iter()
+                    .filter(|characteristic| characteristic.permissions.read)
+                    .map(|characteristic| CharacteristicRead {
+                        accessory_id: accessory.id,
+                        service_name: service.name,
+                        characteristic_name: characteristic.name,
+                    })
+            })
+            .collect::<Vec<_>>();
+        let results = self
+            .master_provider
+            .read_characteristics(reads.clone())
+            .await;
+        let mut state = query::response::State {
+            online: true,
+            ..Default::default()
+        };
+        for (read, result) in reads.iter().zip(results) {
+            match result {
+                Ok(characteristic) => {
+                    google::apply_state(&mut state, read.service_name, &characteristic)
+                }
+                Err(accessory::Error::NotConnected) => {
+                    return DeviceError::Accessory(accessory::Error::NotConnected).query_device()
+                }
+                Err(err) => {
+                    tracing::debug!(accessory_id = %accessory.id, "failed to read {}: {}", read.characteristic_name, err)
+                }
+            }
+        }
+        query::response::PayloadDevice {
+            status: query::response::PayloadDeviceStatus::Success,
+            error_code: None,
+            state,
+        }
+    }
+
+    async fn execute(&self, payload: execute::request::Payload) -> execute::response::Payload {
+        let mut commands = Vec::new();
+        for command in payload.commands {
+            for device in &command.devices {
+                commands.push(self.execute_device(&device.id, &command.execution).await);
+            }
+        }
+        execute::response::Payload {
+            error_code: None,
+            debug_string: None,
+            commands,
+        }
+    }
+
+    async fn execute_device(
+        &self,
+        device_id: &str,
+        executions: &[execute::request::PayloadCommandExecution],
+    ) -> execute::response::PayloadCommand {
+        let ids = vec![device_id.to_string()];
+        let Device {
+            accessory,
+            capabilities,
+            structure_id,
+        } = match self.device(device_id).await {
+            Ok(device) => device,
+            Err(err) => return err.execute_command(ids),
+        };
+        let pin = self
+            .config
+            .get()
+            .controllers
+            .google
+            .as_ref()
+            .and_then(|google| google.get_pin(&structure_id).map(String::from));
+        let mut writes = Vec::new();
+        for execution in executions {
+            if let Err(challenge_needed) = google::challenge(
+                &execution.command,
+                execution.challenge.as_ref(),
+                pin.as_deref(),
+            ) {
+                return execute::response::PayloadCommand {
+                    ids,
+                    status: execute::response::PayloadCommandStatus::Error,
+                    states: Default::default(),
+                    error_code: Some(String::from("challengeNeeded")),
+                    challenge_needed: Some(challenge_needed),
+                };
+            }
+            let (service_name, characteristics) =
+                match google::command(&capabilities, &execution.command) {
+                    Some(command) => command,
+                    None => {
+                        return DeviceError::Accessory(accessory::Error::RequestNotSupported)
+                            .execute_command(ids)
+                    }
+                };
+            writes.extend(
+                characteristics
+                    .into_iter()
+                    .map(|characteristic| CharacteristicWrite {
+                        accessory_id: accessory.id,
+                        service_name,
+                        characteristic,
+                    }),
+            );
+        }
+        let results = self.master_provider.write_characteristics(writes).await;
+        match results.into_iter().find_map(Result::err) {
+            Some(err) => DeviceError::Accessory(err).execute_command(ids),
+            None => execute::response::PayloadCommand {
+                ids,
+                status: execute::response::PayloadCommandStatus::Success,
+                states: Default::default(),
+                error_code: None,
+                challenge_needed: None,
+            },
+        }
+    }
+}
+
+/// Accessory of the user, along with what's needed to serve it.
+struct Device {
+    accessory: Accessory,
+    capabilities: Capabilities,
+    structure_id: structure::ID,
+}
+
+/// Reason why a device couldn't be queried or commanded.
+enum DeviceError {
+    /// Device is unknown, or the user has no permission to it
+    NotFound,
+    Accessory(accessory::Error),
+}
+
+impl DeviceError {
+    fn is_offline(&self) -> bool {
+        matches!(self, Self::Accessory(accessory::Error::NotConnected))
+    }
+
+    fn error_code(&self) -> String {
+        match self {
+            Self::NotFound => String::from("deviceNotFound"),
+            Self::Accessory(err) => google::error_code(err).to_string(),
+        }
+    }
+
+    fn query_device(self) -> query::response::PayloadDevice {
+        let status = if self.is_offline() {
+            query::response::PayloadDeviceStatus::Offline
+        } else {
+            query::response::PayloadDeviceStatus::Error
+        };
+        query::response::PayloadDevice {
+            status,
+            error_code: Some(self.error_code()),
+            state: Default::default(),
+        }
+    }
+
+    fn execute_command(self, ids: Vec<String>) -> execute::response::PayloadCommand {
+        let status = if self.is_offline() {
+            execute::response::PayloadCommandStatus::Offline
+        } else {
+            execute::response::PayloadCommandStatus::Error
+        };
+        execute::response::PayloadCommand {
+            ids,
+            status,
+            states: Default::default(),
+            error_code: Some(self.error_code()),
+            challenge_needed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::ControllerExt;
+    use crate::test_utils::*;
+    use acu::MasterExt;
+    use houseflow_types::accessory::characteristics;
+    use houseflow_types::accessory::characteristics::Characteristic;
+    use houseflow_types::accessory::manufacturers;
+    use houseflow_types::permission::Permission;
+    use houseflow_types::structure::Structure;
+    use serde_json::json;
+
+    /// Provider of lightbulbs connected to the structure, reading them always returns `on`
+    fn provider(accessories: Vec<accessory::ID>, structure_id: structure::ID) -> providers::Handle {
+        let (sender, mut receiver) = acu::channel(providers::Name::Dummy);
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    providers::Message::IsConnected {
+                        accessory_id,
+                        respond_to,
+                    } => respond_to
+                        .send(accessories.contains(&accessory_id))
+                        .unwrap(),
+                    providers::Message::GetAccessoryStructure {
+                        accessory_id,
+                        respond_to,
+                    } => respond_to
+                        .send(accessories.contains(&accessory_id).then_some(structure_id))
+                        .unwrap(),
+                    providers::Message::GetAccessoryCapabilities {
+                        accessory_id,
+                        respond_to,
+                    } => respond_to
+                        .send(
+                            accessories
+                                .contains(&accessory_id)
+                                .then(|| lightbulb(accessory_id).r#type.capabilities()),
+                        )
+                        .unwrap(),
+                    providers::Message::ReadCharacteristics { reads, respond_to } => respond_to
+                        .send(
+                            reads
+                                .iter()
+                                .map(|_| Ok(Characteristic::On(characteristics::On { on: true })))
+                                .collect(),
+                        )
+                        .unwrap(),
+                    providers::Message::WriteCharacteristics { writes, respond_to } => respond_to
+                        .send(writes.iter().map(|_| Ok(())).collect())
+                        .unwrap(),
+                    message => panic!("unexpected message: {:?}", message),
+                }
+            }
+        });
+        providers::Handle { sender }
+    }
+
+    fn lightbulb(id: accessory::ID) -> Accessory {
+        Accessory {
+            id,
+            name: String::from("Night Lamp"),
+            room_name: String::from("Bedroom"),
+            r#type: accessory::Type::Houseflow(manufacturers::Houseflow::Lightbulb {
+                brightness: false,
+                color: false,
+                color_temperature: false,
+            }),
+        }
+    }
+
+    struct Setup {
+        user: user::User,
+        structure: Structure,
+        /// Lightbulb in the structure of the user
+        owned: accessory::ID,
+        /// Lightbulb in a structure that the user has no permission to
+        foreign: accessory::ID,
+        master_provider: providers::MasterHandle,
+        accessories: Accessories,
+        config: extensions::Config,
+    }
+
+    async fn setup() -> Setup {
+        let user = get_user();
+        let structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Zukago"),
+        };
+        let foreign_structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Gdansk"),
+        };
+        let config = get_config(GetConfig {
+            users: vec![user.clone()],
+            permissions: vec![Permission {
+                structure_id: structure.id,
+                user_id: user.id,
+                is_manager: false,
+            }],
+            structures: vec![structure.clone(), foreign_structure.clone()],
+        })
+        .await;
+        let (owned, foreign) = (accessory::ID::new_v4(), accessory::ID::new_v4());
+        let master_provider = providers::MasterHandle::new();
+        master_provider
+            .push(provider(vec![owned], structure.id))
+            .await;
+        master_provider
+            .push(provider(vec![foreign], foreign_structure.id))
+            .await;
+        let accessories = Accessories::new();
+        let controller = accessories.controller();
+        for accessory_id in [owned, foreign] {
+            controller.connected(lightbulb(accessory_id)).await;
+        }
+        // wait for the controller to handle the connected accessories
+        while accessories.all().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        Setup {
+            user,
+            structure,
+            owned,
+            foreign,
+            master_provider,
+            accessories,
+            config,
+        }
+    }
+
+    async fn fulfill(setup: &Setup, request: serde_json::Value) -> Response {
+        let Json(response) = fulfillment(
+            Extension(setup.master_provider.clone()),
+            Extension(setup.accessories.clone()),
+            setup.config.clone(),
+            UserID(setup.user.id),
+            Json(serde_json::from_value(request).unwrap()),
+        )
+        .await
+        .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn sync() {
+        let setup = setup().await;
+        let request = json!({
+            "requestId": "sync",
+            "inputs": [{ "intent": "action.devices.SYNC" }],
+        });
+        let payload = match fulfill(&setup, request).await {
+            Response::Sync(response) => response.payload,
+            response => panic!("unexpected response: {:?}", response),
+        };
+        assert_eq!(payload.agent_user_id, setup.user.id.to_string());
+        assert_eq!(payload.devices.len(), 1);
+        let device = &payload.devices[0];
+        assert_eq!(device.id, setup.owned.to_string());
+        assert_eq!(device.device_type, google_smart_home::device::Type::Light);
+        assert_eq!(device.name.name, "Night Lamp");
+        assert_eq!(device.room_hint.as_deref(), Some("Bedroom"));
+        assert!(device
+            .traits
+            .contains(&google_smart_home::device::Trait::OnOff));
+    }
+
+    #[tokio::test]
+    async fn query() {
+        let setup = setup().await;
+        let request = json!({
+            "requestId": "query",
+            "inputs": [{
+                "intent": "action.devices.QUERY",
+                "payload": {
+                    "devices": [
+                        { "id": setup.owned.to_string() },
+                        { "id": setup.foreign.to_string() },
+                    ],
+                },
+            }],
+        });
+        let payload = match fulfill(&setup, request).await {
+            Response::Query(response) => response.payload,
+            response => panic!("unexpected response: {:?}", response),
+        };
+        let owned = &payload.devices[&setup.owned.to_string()];
+        assert_eq!(owned.status, query::response::PayloadDeviceStatus::Success);
+        assert!(owned.state.online);
+        assert_eq!(owned.state.on, Some(true));
+        let foreign = &payload.devices[&setup.foreign.to_string()];
+        assert_eq!(foreign.status, query::response::PayloadDeviceStatus::Error);
+        assert_eq!(foreign.error_code.as_deref(), Some("deviceNotFound"));
+    }
+
+    #[tokio::test]
+    async fn execute() {
+        let setup = setup().await;
+        let request = json!({
+            "requestId": "execute",
+            "inputs": [{
+                "intent": "action.devices.EXECUTE",
+                "payload": {
+                    "commands": [{
+                        "devices": [
+                            { "id": setup.owned.to_string() },
+                            { "id": setup.foreign.to_string() },
+                        ],
+                        "execution": [{
+                            "command": "action.devices.commands.OnOff",
+                            "params": { "on": true },
+                        }],
+                    }],
+                },
+            }],
+        });
+        let payload = match fulfill(&setup, request).await {
+            Response::Execute(response) => response.payload,
+            response => panic!("unexpected response: {:?}", response),
+        };
+        assert_eq!(payload.commands.len(), 2);
+        assert_eq!(payload.commands[0].ids, vec![setup.owned.to_string()]);
+        assert_eq!(
+            payload.commands[0].status,
+            execute::response::PayloadCommandStatus::Success
+        );
+        assert_eq!(payload.commands[1].ids, vec![setup.foreign.to_string()]);
+        assert_eq!(
+            payload.commands[1].status,
+            execute::response::PayloadCommandStatus::Error
+        );
+        assert_eq!(
+            payload.commands[1].error_code.as_deref(),
+            Some("deviceNotFound")
+        );
+    }
+
+    #[tokio::test]
+    async fn unlock() {
+        let setup = setup().await;
+        let unlock = |challenge: serde_json::Value| {
+            json!({
+                "requestId": "unlock",
+                "inputs": [{
+                    "intent": "action.devices.EXECUTE",
+                    "payload": {
+                        "commands": [{
+                            "devices": [{ "id": setup.owned.to_string() }],
+                            "execution": [{
+                                "command": "action.devices.commands.LockUnlock",
+                                "params": { "lock": false },
+                                "challenge": challenge,
+                            }],
+                        }],
+                    },
+                }],
+            })
+        };
+        let execute = |request| async {
+            match fulfill(&setup, request).await {
+                Response::Execute(response) => response.payload.commands[0].clone(),
+                response => panic!("unexpected response: {:?}", response),
+            }
+        };
+        let challenge_needed = |r#type| Some(execute::response::ChallengeNeeded { r#type });
+
+        // until a PIN is configured for the structure, unlocking only has to be acknowledged
+        let command = execute(unlock(json!({}))).await;
+        assert_eq!(
+            command.status,
+            execute::response::PayloadCommandStatus::Error
+        );
+        assert_eq!(command.error_code.as_deref(), Some("challengeNeeded"));
+        assert_eq!(
+            command.challenge_needed,
+            challenge_needed(execute::response::ChallengeType::AckNeeded)
+        );
+        let command = execute(unlock(json!({ "ack": true }))).await;
+        assert_eq!(
+            command.status,
+            execute::response::PayloadCommandStatus::Success
+        );
+
+        let mut config = (**setup.config.get()).clone();
+        config.controllers.google = Some(houseflow_config::server::controllers::Google {
+            pins: vec![houseflow_config::server::controllers::StructurePin {
+                structure_id: setup.structure.id,
+                pin: String::from("1234"),
+            }],
+        });
+        setup.config.update(config);
+
+        let command = execute(unlock(json!({ "ack": true }))).await;
+        assert_eq!(
+            command.challenge_needed,
+            challenge_needed(execute::response::ChallengeType::PinNeeded)
+        );
+        let command = execute(unlock(json!({ "pin": "0000" }))).await;
+        assert_eq!(
+            command.challenge_needed,
+            challenge_needed(execute::response::ChallengeType::ChallengeFailedPinNeeded)
+        );
+        let command = execute(unlock(json!({ "pin": "1234" }))).await;
+        assert_eq!(
+            command.status,
+            execute::response::PayloadCommandStatus::Success
+        );
+        assert_eq!(command.challenge_needed, None);
+    }
+}
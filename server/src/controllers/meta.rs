@@ -2,15 +2,31 @@ pub use super::Handle;
 
 use super::Message;
 use super::Name;
+use crate::extensions;
+use crate::extractors::UserID;
 use crate::providers;
+use crate::providers::Provider;
 use crate::providers::ProviderExt;
-use acu::MasterExt;
 use anyhow::Error;
+use axum::extract::Query;
+use axum::response::sse;
+use axum::response::sse::Sse;
 use axum::Json;
-use futures::future::join_all;
+use futures::Stream;
+use futures::StreamExt;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::Accessory;
 use houseflow_types::errors::ControllerError;
 use houseflow_types::errors::ServerError;
+use houseflow_types::meta;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+
+/// Number of events buffered for each watcher, slower watchers skip the oldest events
+const EVENTS_CAPACITY: usize = 256;
 
 pub fn new() -> Handle {
     let (sender, receiver) = acu::channel(Name::Master);
@@ -46,7 +62,136 @@ impl MetaController {
     }
 }
 
-pub fn app(handle: Handle) -> axum::Router {
+/// Broadcasts the events of the accessories to the watchers.
+///
+/// Keeps the currently connected accessories, so new watchers learn about them first.
+/// The events are received by the handle returned from `controller()`, which has to be pushed to the master controller.
+#[derive(Debug, Clone)]
+pub struct Events {
+    /// Events along with the structure of their accessory, used to pass them only to the users with permission to it
+    sender: broadcast::Sender<(Option<structure::ID>, meta::Event)>,
+    accessories: Arc<RwLock<HashMap<accessory::ID, ConnectedAccessory>>>,
+}
+
+/// Connected accessory along with its structure, if the provider knows it
+type ConnectedAccessory = (Accessory, Option<structure::ID>);
+
+impl Default for Events {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENTS_CAPACITY);
+        Self {
+            sender,
+            accessories: Default::default(),
+        }
+    }
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a controller handle which broadcasts the `Connected`/`Disconnected`/`Updated` events.
+    ///
+    /// Structures of the accessories are looked up when they connect, as they're unknown to the providers once the accessories disconnect.
+    pub fn controller(&self, master_provider: providers::MasterHandle) -> Handle {
+        let (sender, mut receiver) = acu::channel(Name::Events);
+        let events = self.clone();
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let accessory_id = match &message {
+                    Message::Connected { accessory } => accessory.id,
+                    Message::Disconnected { accessory_id } => *accessory_id,
+                    Message::Updated { accessory_id, .. } => *accessory_id,
+                };
+                let structure_id = match &message {
+                    Message::Connected { .. } => {
+                        master_provider.get_accessory_structure(accessory_id).await
+                    }
+                    _ => events
+                        .accessories
+                        .read()
+                        .unwrap()
+                        .get(&accessory_id)
+                        .and_then(|(_, structure_id)| *structure_id),
+                };
+                let event = match message {
+                    Message::Connected { accessory } => {
+                        events
+                            .accessories
+                            .write()
+                            .unwrap()
+                            .insert(accessory.id, (accessory.clone(), structure_id));
+                        meta::Event::Connected { accessory }
+                    }
+                    Message::Disconnected { accessory_id } => {
+                        events.accessories.write().unwrap().remove(&accessory_id);
+                        meta::Event::Disconnected { accessory_id }
+                    }
+                    Message::Updated {
+                        accessory_id,
+                        service_name,
+                        characteristic,
+                    } => meta::Event::Updated {
+                        accessory_id,
+                        service_name,
+                        characteristic,
+                    },
+                };
+                // fails only if nobody is watching
+                let _ = events.sender.send((structure_id, event));
+            }
+        });
+        Handle { sender }
+    }
+
+    /// Returns the events passing the filter, starting with the currently connected accessories.
+    ///
+    /// Only the events of the accessories in the structures that the user has permission to are passed,
+    /// permissions are checked for every event, so revoking them takes effect immediately.
+    fn subscribe(
+        &self,
+        filter: meta::EventFilter,
+        user_id: user::ID,
+        config: extensions::Config,
+    ) -> impl Stream<Item = meta::Event> {
+        let receiver = self.sender.subscribe();
+        let connected = self
+            .accessories
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .map(|(accessory, structure_id)| (structure_id, meta::Event::Connected { accessory }))
+            .collect::<Vec<_>>();
+        let events = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("watcher is too slow, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        let mut watcher = meta::Watcher::new(filter);
+        futures::stream::iter(connected)
+            .chain(events)
+            .filter_map(move |(structure_id, event)| {
+                let permitted = structure_id.is_some_and(|structure_id| {
+                    config
+                        .get()
+                        .get_permission(&structure_id, &user_id)
+                        .is_some()
+                });
+                let event = (permitted && watcher.matches(&event)).then_some(event);
+                futures::future::ready(event)
+            })
+    }
+}
+
+pub fn app(handle: Handle, events: Events) -> axum::Router {
     use axum::routing::get;
     use axum::routing::post;
 
@@ -59,7 +204,23 @@ pub fn app(handle: Handle) -> axum::Router {
             "/characteristic/:accessory_id/:service_name",
             post(write_characteristic),
         )
+        .route(
+            "/accessory/:accessory_id/capabilities",
+            get(get_accessory_capabilities),
+        )
+        .route("/accessory/:accessory_id/identify", post(identify))
+        .route("/accessories", get(list_accessories))
+        .route("/events", get(watch))
+        .route(
+            "/characteristics/batch-read",
+            post(batch_read_characteristics),
+        )
+        .route(
+            "/characteristics/batch-write",
+            post(batch_write_characteristics),
+        )
         .layer(Extension(handle))
+        .layer(Extension(events))
 }
 
 use axum::extract::Extension;
@@ -67,29 +228,51 @@ use axum::extract::Path;
 use houseflow_types::accessory;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::structure;
+use houseflow_types::user;
 
+/// Reads the characteristic, authenticated users get the value converted to their display preferences.
 pub async fn read_characteristic(
     Extension(master_provider): Extension<providers::MasterHandle>,
+    config: extensions::Config,
+    user_id: Option<UserID>,
     Path((accessory_id, service_name, characteristic_name)): Path<(
         accessory::ID,
         ServiceName,
         CharacteristicName,
     )>,
-) -> Result<Json<Characteristic>, ServerError> {
-    let slaves: Vec<providers::Handle> = master_provider.slaves().await;
-    let futures = slaves
-        .iter()
-        .map(|provider| async move { (provider, provider.is_connected(accessory_id).await) });
-    let results = join_all(futures).await;
-    let provider = results
-        .into_iter()
-        .find_map(|(provider, connected)| if connected { Some(provider) } else { None })
+) -> Result<Json<meta::ReadResponse>, ServerError> {
+    let provider = master_provider
+        .route(accessory_id)
+        .await
         .ok_or(ControllerError::AccessoryNotConnected)?;
     let characteristic = provider
         .read_characteristic(accessory_id, service_name, characteristic_name)
         .await
         .map_err(ControllerError::AccessoryError)?;
-    Ok(Json(characteristic))
+    let preferences = user_id
+        .and_then(|UserID(user_id)| {
+            config
+                .get()
+                .get_user(&user_id)
+                .map(|user| user.preferences.clone())
+        })
+        .unwrap_or_default();
+    Ok(Json(display(characteristic, &preferences)))
+}
+
+fn display(characteristic: Characteristic, preferences: &user::Preferences) -> meta::ReadResponse {
+    let display = characteristic
+        .value()
+        .zip(CharacteristicName::from(&characteristic).unit())
+        .map(|(value, unit)| {
+            let (unit, value) = unit.localize(value, preferences);
+            meta::DisplayValue { value, unit }
+        });
+    meta::ReadResponse {
+        characteristic,
+        display,
+    }
 }
 
 pub async fn write_characteristic(
@@ -97,14 +280,9 @@ pub async fn write_characteristic(
     Path((accessory_id, service_name)): Path<(accessory::ID, ServiceName)>,
     Json(characteristic): Json<Characteristic>,
 ) -> Result<(), ServerError> {
-    let slaves: Vec<providers::Handle> = master_provider.slaves().await;
-    let futures = slaves
-        .iter()
-        .map(|provider| async move { (provider, provider.is_connected(accessory_id).await) });
-    let results = join_all(futures).await;
-    let provider = results
-        .into_iter()
-        .find_map(|(provider, connected)| if connected { Some(provider) } else { None })
+    let provider = master_provider
+        .route(accessory_id)
+        .await
         .ok_or(ControllerError::AccessoryNotConnected)?;
     provider
         .write_characteristic(accessory_id, service_name, characteristic)
@@ -112,3 +290,213 @@ pub async fn write_characteristic(
         .map_err(ControllerError::AccessoryError)?;
     Ok(())
 }
+
+pub async fn batch_read_characteristics(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Json(request): Json<meta::BatchReadRequest>,
+) -> Json<meta::BatchReadResponse> {
+    let results = master_provider
+        .read_characteristics(request.reads)
+        .await
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Json(meta::BatchReadResponse { results })
+}
+
+pub async fn batch_write_characteristics(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Json(request): Json<meta::BatchWriteRequest>,
+) -> Json<meta::BatchWriteResponse> {
+    let results = master_provider
+        .write_characteristics(request.writes)
+        .await
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Json(meta::BatchWriteResponse { results })
+}
+
+pub async fn list_accessories(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    config: extensions::Config,
+) -> Json<meta::ListAccessoriesResponse> {
+    let mut accessories = Vec::new();
+    for id in master_provider.get_accessories().await {
+        // accessory could disconnect in the meantime
+        if let Some(capabilities) = master_provider.get_accessory_capabilities(id).await {
+            let structure = master_provider
+                .get_accessory_structure(id)
+                .await
+                .and_then(|structure_id| config.get().get_structure(&structure_id).cloned());
+            accessories.push(meta::ConnectedAccessory {
+                id,
+                capabilities,
+                structure,
+            });
+        }
+    }
+    Json(meta::ListAccessoriesResponse { accessories })
+}
+
+/// Streams the events as server-sent events, until the watcher disconnects.
+pub async fn watch(
+    Extension(events): Extension<Events>,
+    config: extensions::Config,
+    UserID(user_id): UserID,
+    Query(filter): Query<meta::EventFilter>,
+) -> Sse<impl Stream<Item = Result<sse::Event, serde_json::Error>>> {
+    let events = events
+        .subscribe(filter, user_id, config)
+        .map(|event| sse::Event::default().json_data(event));
+    Sse::new(events).keep_alive(sse::KeepAlive::default())
+}
+
+pub async fn get_accessory_capabilities(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Path(accessory_id): Path<accessory::ID>,
+) -> Result<Json<Capabilities>, ServerError> {
+    let capabilities = master_provider
+        .get_accessory_capabilities(accessory_id)
+        .await
+        .ok_or(ControllerError::AccessoryNotConnected)?;
+    Ok(Json(capabilities))
+}
+
+pub async fn identify(
+    Extension(master_provider): Extension<providers::MasterHandle>,
+    Path(accessory_id): Path<accessory::ID>,
+) -> Result<(), ServerError> {
+    let provider = master_provider
+        .route(accessory_id)
+        .await
+        .ok_or(ControllerError::AccessoryNotConnected)?;
+    provider
+        .identify(accessory_id)
+        .await
+        .map_err(ControllerError::AccessoryError)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::ControllerExt;
+    use crate::test_utils::*;
+    use acu::MasterExt;
+    use houseflow_types::accessory::characteristics;
+    use houseflow_types::accessory::manufacturers;
+    use houseflow_types::permission::Permission;
+    use houseflow_types::structure::Structure;
+
+    /// Provider of the accessories connected to the structure
+    fn provider(accessories: Vec<accessory::ID>, structure_id: structure::ID) -> providers::Handle {
+        let (sender, mut receiver) = acu::channel(providers::Name::Dummy);
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    providers::Message::IsConnected {
+                        accessory_id,
+                        respond_to,
+                    } => respond_to
+                        .send(accessories.contains(&accessory_id))
+                        .unwrap(),
+                    providers::Message::GetAccessoryStructure {
+                        accessory_id,
+                        respond_to,
+                    } => respond_to
+                        .send(accessories.contains(&accessory_id).then_some(structure_id))
+                        .unwrap(),
+                    message => panic!("unexpected message: {:?}", message),
+                }
+            }
+        });
+        providers::Handle { sender }
+    }
+
+    fn thermometer(id: accessory::ID) -> Accessory {
+        Accessory {
+            id,
+            name: String::from("Thermometer"),
+            room_name: String::from("Bedroom"),
+            r#type: accessory::Type::XiaomiMijia(manufacturers::XiaomiMijia::HygroThermometer),
+        }
+    }
+
+    #[tokio::test]
+    async fn watch_permitted_structures() {
+        let user = get_user();
+        let structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Zukago"),
+        };
+        let foreign_structure = Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Gdansk"),
+        };
+        let config = get_config(GetConfig {
+            users: vec![user.clone()],
+            permissions: vec![Permission {
+                structure_id: structure.id,
+                user_id: user.id,
+                is_manager: false,
+            }],
+            structures: vec![structure.clone(), foreign_structure.clone()],
+        })
+        .await;
+        let (owned, foreign) = (accessory::ID::new_v4(), accessory::ID::new_v4());
+        let master_provider = providers::MasterHandle::new();
+        master_provider
+            .push(provider(vec![owned], structure.id))
+            .await;
+        master_provider
+            .push(provider(vec![foreign], foreign_structure.id))
+            .await;
+
+        let events = Events::new();
+        let controller = events.controller(master_provider);
+        for accessory_id in [owned, foreign] {
+            controller.connected(thermometer(accessory_id)).await;
+        }
+        // wait for the controller to handle the connected accessories
+        while events.accessories.read().unwrap().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        let watched = events.subscribe(meta::EventFilter::default(), user.id, config);
+
+        let characteristic =
+            Characteristic::CurrentTemperature(characteristics::CurrentTemperature {
+                temperature: 21.5,
+            });
+        for accessory_id in [foreign, owned] {
+            controller
+                .updated(
+                    accessory_id,
+                    ServiceName::TemperatureSensor,
+                    characteristic.clone(),
+                )
+                .await;
+        }
+        for accessory_id in [foreign, owned] {
+            controller.disconnected(accessory_id).await;
+        }
+
+        let watched = watched.take(3).collect::<Vec<_>>().await;
+        assert_eq!(
+            watched,
+            vec![
+                meta::Event::Connected {
+                    accessory: thermometer(owned),
+                },
+                meta::Event::Updated {
+                    accessory_id: owned,
+                    service_name: ServiceName::TemperatureSensor,
+                    characteristic,
+                },
+                meta::Event::Disconnected {
+                    accessory_id: owned,
+                },
+            ]
+        );
+    }
+}
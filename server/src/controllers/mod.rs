@@ -1,3 +1,4 @@
+pub mod google;
 pub mod meta;
 
 use async_trait::async_trait;
@@ -5,11 +6,15 @@ use houseflow_types::accessory;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::services::ServiceName;
 use houseflow_types::accessory::Accessory;
+use houseflow_types::routing;
 
 #[derive(Debug, Clone, PartialEq, Eq, strum::Display, strum::IntoStaticStr)]
 pub enum Name {
     Master,
     Meta,
+    Google,
+    Router,
+    Events,
 }
 
 impl acu::MasterName for Name {
@@ -36,6 +41,18 @@ pub enum Message {
 
 impl acu::Message for Message {}
 
+impl routing::ConnectionEvent for Message {
+    fn connection(&self) -> Option<routing::Connection> {
+        match self {
+            Self::Connected { accessory } => Some(routing::Connection::Connected(accessory.id)),
+            Self::Disconnected { accessory_id } => {
+                Some(routing::Connection::Disconnected(*accessory_id))
+            }
+            Self::Updated { .. } => None,
+        }
+    }
+}
+
 pub type Handle = acu::Handle<Message, Name>;
 
 #[async_trait]
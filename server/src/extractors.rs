@@ -26,6 +26,30 @@ impl axum::extract::FromRequest<Body> for UserID {
     }
 }
 
+/// ID of the authenticated user, rejects users who are not admins
+pub struct AdminID(pub user::ID);
+
+#[async_trait]
+impl axum::extract::FromRequest<Body> for AdminID {
+    type Rejection = ServerError;
+
+    async fn from_request(
+        req: &mut axum::extract::RequestParts<Body>,
+    ) -> Result<Self, Self::Rejection> {
+        let UserID(user_id) = UserID::from_request(req).await?;
+        let config: &extensions::Config = req.extensions().get().unwrap();
+        let user = config
+            .get()
+            .get_user(&user_id)
+            .cloned()
+            .ok_or(AuthError::UserNotFound)?;
+        if !user.admin {
+            return Err(AuthError::NotAdmin.into());
+        }
+        Ok(Self(user_id))
+    }
+}
+
 pub struct RefreshToken(pub Token<RefreshTokenClaims>);
 pub struct AccessToken(pub Token<AccessTokenClaims>);
 
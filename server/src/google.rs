@@ -0,0 +1,1231 @@
+//! Mapping between Houseflow accessories and Google Smart Home devices.
+
+use google_smart_home::device;
+use google_smart_home::device::commands::ColorValue;
+use google_smart_home::device::commands::OpenDirection;
+use google_smart_home::device::commands::SetFanSpeed;
+use google_smart_home::execute::request::Challenge;
+use google_smart_home::execute::response::ChallengeNeeded;
+use google_smart_home::execute::response::ChallengeType;
+use google_smart_home::query::response::CapacityUnit;
+use google_smart_home::query::response::CapacityValue;
+use google_smart_home::query::response::Color;
+use google_smart_home::query::response::CurrentSensorState;
+use google_smart_home::query::response::State;
+use google_smart_home::sync::response::Attributes;
+use google_smart_home::sync::response::ColorModel;
+use google_smart_home::sync::response::ColorTemperatureRange;
+use google_smart_home::sync::response::SensorDescriptiveCapabilities;
+use google_smart_home::sync::response::SensorNumericCapabilities;
+use google_smart_home::sync::response::SensorStateSupported;
+use google_smart_home::sync::response::ThermostatTemperatureRange;
+use google_smart_home::sync::response::ThermostatTemperatureUnit;
+use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
+use houseflow_types::accessory::characteristics;
+use houseflow_types::accessory::characteristics::AirQualityLevel;
+use houseflow_types::accessory::characteristics::Characteristic;
+use houseflow_types::accessory::characteristics::CharacteristicName;
+use houseflow_types::accessory::characteristics::HeatingCoolingMode;
+use houseflow_types::accessory::characteristics::HeatingCoolingState;
+use houseflow_types::accessory::characteristics::LockState;
+use houseflow_types::accessory::characteristics::LockTargetState;
+use houseflow_types::accessory::characteristics::Movement;
+use houseflow_types::accessory::color;
+use houseflow_types::accessory::services::ServiceName;
+use subtle::ConstantTimeEq;
+
+/// Device type of the accessory, exhaustive so that new accessory types have to be mapped here.
+pub fn device_type(r#type: &accessory::Type) -> device::Type {
+    use accessory::manufacturers;
+
+    match r#type {
+        accessory::Type::XiaomiMijia(manufacturers::XiaomiMijia::HygroThermometer) => {
+            device::Type::Sensor
+        }
+        accessory::Type::Houseflow(r#type) => match r#type {
+            manufacturers::Houseflow::Gate => device::Type::Gate,
+            manufacturers::Houseflow::Garage => device::Type::Garage,
+            manufacturers::Houseflow::Lightbulb { .. } => device::Type::Light,
+            manufacturers::Houseflow::Thermostat => device::Type::Thermostat,
+            manufacturers::Houseflow::Lock => device::Type::Lock,
+            manufacturers::Houseflow::Blinds => device::Type::Blinds,
+            manufacturers::Houseflow::Switch => device::Type::Switch,
+            manufacturers::Houseflow::Outlet => device::Type::Outlet,
+            manufacturers::Houseflow::Fan => device::Type::Fan,
+            manufacturers::Houseflow::AirPurifier => device::Type::Airpurifier,
+            manufacturers::Houseflow::SmokeSensor => device::Type::SmokeDetector,
+            manufacturers::Houseflow::ContactSensor
+            | manufacturers::Houseflow::MotionSensor
+            | manufacturers::Houseflow::OccupancySensor
+            | manufacturers::Houseflow::LeakSensor
+            | manufacturers::Houseflow::AirQualitySensor => device::Type::Sensor,
+        },
+    }
+}
+
+pub fn traits(capabilities: &Capabilities) -> Vec<device::Trait> {
+    let mut traits = vec![];
+    for service in &capabilities.services {
+        match service.name {
+            ServiceName::Light => {
+                traits.push(device::Trait::OnOff);
+                if service
+                    .characteristic(CharacteristicName::Brightness)
+                    .is_some()
+                {
+                    traits.push(device::Trait::Brightness);
+                }
+                if service.characteristic(CharacteristicName::Hue).is_some()
+                    || service
+                        .characteristic(CharacteristicName::ColorTemperature)
+                        .is_some()
+                {
+                    traits.push(device::Trait::ColorSetting);
+                }
+            }
+            ServiceName::Switch | ServiceName::Outlet => {
+                traits.push(device::Trait::OnOff);
+                if service
+                    .characteristic(CharacteristicName::EnergyConsumption)
+                    .is_some()
+                {
+                    traits.push(device::Trait::EnergyStorage);
+                }
+            }
+            ServiceName::GarageDoorOpener => traits.push(device::Trait::OpenClose),
+            ServiceName::Thermostat => traits.push(device::Trait::TemperatureSetting),
+            ServiceName::LockMechanism => traits.push(device::Trait::LockUnlock),
+            ServiceName::ContactSensor => traits.push(device::Trait::OpenClose),
+            ServiceName::AirQualitySensor => traits.push(device::Trait::SensorState),
+            ServiceName::Fan | ServiceName::AirPurifier => {
+                traits.push(device::Trait::OnOff);
+                if service
+                    .characteristic(CharacteristicName::RotationSpeed)
+                    .is_some()
+                {
+                    traits.push(device::Trait::FanSpeed);
+                }
+                if service
+                    .characteristic(CharacteristicName::FilterLifeLevel)
+                    .is_some()
+                    || service
+                        .characteristic(CharacteristicName::FilterChangeIndication)
+                        .is_some()
+                {
+                    traits.push(device::Trait::SensorState);
+                }
+            }
+            ServiceName::WindowCovering => {
+                traits.push(device::Trait::OpenClose);
+                traits.push(device::Trait::StartStop);
+            }
+            service_name if sensor_state(service_name).is_some() => {
+                traits.push(device::Trait::SensorState)
+            }
+            _ => {}
+        }
+    }
+    traits
+}
+
+pub fn attributes(capabilities: &Capabilities) -> Attributes {
+    let mut attributes = Attributes::default();
+    if let Some(light) = capabilities.service(ServiceName::Light) {
+        if light.characteristic(CharacteristicName::Hue).is_some()
+            && light
+                .characteristic(CharacteristicName::Saturation)
+                .is_some()
+        {
+            attributes.color_model = Some(ColorModel::Hsv);
+        }
+        attributes.color_temperature_range = light
+            .characteristic(CharacteristicName::ColorTemperature)
+            .and_then(|characteristic| characteristic.range)
+            .map(|range| ColorTemperatureRange {
+                // the warmest color has the most mireds
+                temperature_min_k: color::mireds_to_kelvin(range.max as u16).into(),
+                temperature_max_k: color::mireds_to_kelvin(range.min as u16).into(),
+            });
+    }
+    if let Some(thermostat) = capabilities.service(ServiceName::Thermostat) {
+        attributes.available_thermostat_modes = Some(
+            [
+                HeatingCoolingMode::Off,
+                HeatingCoolingMode::Heat,
+                HeatingCoolingMode::Cool,
+                HeatingCoolingMode::Auto,
+            ]
+            .iter()
+            .map(|mode| thermostat_mode(*mode).to_string())
+            .collect(),
+        );
+        attributes.thermostat_temperature_range = thermostat
+            .characteristic(CharacteristicName::TargetTemperature)
+            .and_then(|characteristic| characteristic.range)
+            .map(|range| ThermostatTemperatureRange {
+                min_threshold_celsius: range.min.into(),
+                max_threshold_celsius: range.max.into(),
+            });
+        attributes.thermostat_temperature_unit = Some(ThermostatTemperatureUnit::C);
+    }
+    // Google has no trait for metering, so consumed energy is reported through a query-only EnergyStorage
+    let metered = [ServiceName::Switch, ServiceName::Outlet]
+        .into_iter()
+        .filter_map(|service_name| capabilities.service(service_name))
+        .any(|service| {
+            service
+                .characteristic(CharacteristicName::EnergyConsumption)
+                .is_some()
+        });
+    if metered {
+        attributes.query_only_energy_storage = Some(true);
+        attributes.is_rechargeable = Some(false);
+    }
+    if capabilities.service(ServiceName::WindowCovering).is_some() {
+        attributes.open_direction = Some(vec![OpenDirection::Up]);
+        attributes.pausable = Some(false);
+    }
+    if let Some(fan) = fan_service(capabilities).and_then(|name| capabilities.service(name)) {
+        if fan
+            .characteristic(CharacteristicName::RotationSpeed)
+            .is_some()
+        {
+            attributes.supports_fan_speed_percent = Some(true);
+        }
+    }
+    if capabilities.service(ServiceName::ContactSensor).is_some() {
+        attributes.discrete_only_open_close = Some(true);
+        attributes.query_only_open_close = Some(true);
+    }
+    let mut sensor_states = capabilities
+        .services
+        .iter()
+        .filter_map(|service| sensor_state(service.name))
+        .map(|(name, detected, clear)| SensorStateSupported {
+            name: name.to_string(),
+            descriptive_capabilities: Some(SensorDescriptiveCapabilities {
+                available_states: vec![detected.to_string(), clear.to_string()],
+            }),
+            numeric_capabilities: None,
+        })
+        .collect::<Vec<_>>();
+    if capabilities
+        .service(ServiceName::AirQualitySensor)
+        .is_some()
+    {
+        sensor_states.push(SensorStateSupported {
+            name: String::from("AirQuality"),
+            descriptive_capabilities: Some(SensorDescriptiveCapabilities {
+                available_states: [
+                    AirQualityLevel::Excellent,
+                    AirQualityLevel::Good,
+                    AirQualityLevel::Fair,
+                    AirQualityLevel::Inferior,
+                    AirQualityLevel::Poor,
+                    AirQualityLevel::Unknown,
+                ]
+                .iter()
+                .map(|quality| air_quality(*quality).to_string())
+                .collect(),
+            }),
+            numeric_capabilities: None,
+        });
+    }
+    let filter_change_indication = capabilities
+        .service(ServiceName::AirPurifier)
+        .and_then(|purifier| purifier.characteristic(CharacteristicName::FilterChangeIndication));
+    if filter_change_indication.is_some() {
+        sensor_states.push(SensorStateSupported {
+            name: String::from("FilterCleanliness"),
+            descriptive_capabilities: Some(SensorDescriptiveCapabilities {
+                available_states: vec![
+                    filter_cleanliness(false).to_string(),
+                    filter_cleanliness(true).to_string(),
+                ],
+            }),
+            numeric_capabilities: None,
+        });
+    }
+    sensor_states.extend(
+        capabilities
+            .services
+            .iter()
+            .flat_map(|service| &service.characteristics)
+            .filter_map(|characteristic| numeric_sensor_state(characteristic.name))
+            .map(|(name, unit)| SensorStateSupported {
+                name: name.to_string(),
+                descriptive_capabilities: None,
+                numeric_capabilities: Some(SensorNumericCapabilities {
+                    raw_value_unit: unit.to_string(),
+                }),
+            }),
+    );
+    if !sensor_states.is_empty() {
+        attributes.sensor_states_supported = Some(sensor_states);
+    }
+    attributes
+}
+
+/// Updates the Google device state with a characteristic read from the accessory.
+pub fn apply_state(state: &mut State, service_name: ServiceName, characteristic: &Characteristic) {
+    match (service_name, characteristic) {
+        (ServiceName::Light | ServiceName::Switch | ServiceName::Outlet, Characteristic::On(v)) => {
+            state.on = Some(v.on)
+        }
+        (ServiceName::Fan | ServiceName::AirPurifier, Characteristic::Active(v)) => {
+            state.on = Some(v.active)
+        }
+        (ServiceName::Fan | ServiceName::AirPurifier, Characteristic::RotationSpeed(v)) => {
+            state.current_fan_speed_percent = Some(v.percentage)
+        }
+        (ServiceName::AirPurifier, Characteristic::FilterLifeLevel(v)) => set_sensor_state(
+            state,
+            CurrentSensorState {
+                name: String::from("FilterLifeTime"),
+                current_sensor_state: None,
+                raw_value: Some(v.percentage.into()),
+            },
+        ),
+        (ServiceName::AirPurifier, Characteristic::FilterChangeIndication(v)) => set_sensor_state(
+            state,
+            CurrentSensorState {
+                name: String::from("FilterCleanliness"),
+                current_sensor_state: Some(filter_cleanliness(v.change_needed).to_string()),
+                raw_value: None,
+            },
+        ),
+        (ServiceName::Outlet, Characteristic::OutletInUse(v)) => {
+            state.is_plugged_in = Some(v.in_use)
+        }
+        (ServiceName::Switch | ServiceName::Outlet, Characteristic::EnergyConsumption(v)) => {
+            state.capacity_remaining = vec![CapacityValue {
+                raw_value: v.kilowatt_hours.into(),
+                unit: CapacityUnit::KilowattHours,
+            }]
+        }
+        (ServiceName::Light, Characteristic::Brightness(v)) => {
+            state.brightness = Some(v.percentage)
+        }
+        (ServiceName::Light, Characteristic::Hue(v)) => {
+            let (_, saturation, value) = spectrum_hsv(state);
+            state.color = Some(Color::SpectrumHsv {
+                hue: v.degrees.into(),
+                saturation,
+                value,
+            })
+        }
+        (ServiceName::Light, Characteristic::Saturation(v)) => {
+            let (hue, _, value) = spectrum_hsv(state);
+            state.color = Some(Color::SpectrumHsv {
+                hue,
+                saturation: f64::from(v.percentage) / 100.0,
+                value,
+            })
+        }
+        (ServiceName::Light, Characteristic::ColorTemperature(v)) => {
+            state.color = Some(Color::TemperatureK(
+                color::mireds_to_kelvin(v.mireds).into(),
+            ))
+        }
+        (ServiceName::LockMechanism, Characteristic::CurrentLockState(v)) => {
+            state.is_locked = Some(v.state == LockState::Secured);
+            state.is_jammed = Some(v.state == LockState::Jammed);
+        }
+        (ServiceName::WindowCovering, Characteristic::CurrentPosition(v)) => {
+            state.open_percent = Some(v.position)
+        }
+        (ServiceName::WindowCovering, Characteristic::PositionState(v)) => {
+            state.is_running = Some(v.movement != Movement::Stopped)
+        }
+        (ServiceName::ContactSensor, Characteristic::ContactSensorState(v)) => {
+            state.open_percent = Some(if v.contact_detected { 0 } else { 100 })
+        }
+        (
+            service_name,
+            Characteristic::MotionDetected(characteristics::MotionDetected {
+                motion_detected: detected,
+            })
+            | Characteristic::OccupancyDetected(characteristics::OccupancyDetected {
+                occupancy_detected: detected,
+            })
+            | Characteristic::LeakDetected(characteristics::LeakDetected {
+                leak_detected: detected,
+            })
+            | Characteristic::SmokeDetected(characteristics::SmokeDetected {
+                smoke_detected: detected,
+            }),
+        ) => {
+            if let Some((name, detected_state, clear_state)) = sensor_state(service_name) {
+                let current_sensor_state = if *detected {
+                    detected_state
+                } else {
+                    clear_state
+                };
+                set_sensor_state(
+                    state,
+                    CurrentSensorState {
+                        name: name.to_string(),
+                        current_sensor_state: Some(current_sensor_state.to_string()),
+                        raw_value: None,
+                    },
+                );
+            }
+        }
+        (ServiceName::AirQualitySensor, Characteristic::AirQuality(v)) => set_sensor_state(
+            state,
+            CurrentSensorState {
+                name: String::from("AirQuality"),
+                current_sensor_state: Some(air_quality(v.quality).to_string()),
+                raw_value: None,
+            },
+        ),
+        (
+            ServiceName::AirQualitySensor,
+            Characteristic::CarbonDioxideLevel(characteristics::CarbonDioxideLevel { ppm: value })
+            | Characteristic::Pm25Density(characteristics::Pm25Density { density: value })
+            | Characteristic::Pm10Density(characteristics::Pm10Density { density: value }),
+        ) => {
+            let (name, _) = numeric_sensor_state(CharacteristicName::from(characteristic)).unwrap();
+            set_sensor_state(
+                state,
+                CurrentSensorState {
+                    name: name.to_string(),
+                    current_sensor_state: None,
+                    raw_value: Some((*value).into()),
+                },
+            )
+        }
+        (ServiceName::Thermostat, Characteristic::CurrentTemperature(v)) => {
+            state.thermostat_temperature_ambient = Some(v.temperature.into())
+        }
+        (ServiceName::Thermostat, Characteristic::TargetTemperature(v)) => {
+            state.thermostat_temperature_setpoint = Some(v.temperature.into())
+        }
+        (ServiceName::Thermostat, Characteristic::TargetHeatingCoolingState(v)) => {
+            state.thermostat_mode = Some(thermostat_mode(v.mode).to_string())
+        }
+        (ServiceName::Thermostat, Characteristic::CurrentHeatingCoolingState(v)) => {
+            state.active_thermostat_mode = Some(active_thermostat_mode(v.state).to_string())
+        }
+        _ => {}
+    }
+}
+
+/// Translates a Google command to the characteristics that should be written to the accessory.
+///
+/// Capabilities of the accessory decide between services sharing a trait, like a garage door and blinds.
+pub fn command(
+    capabilities: &Capabilities,
+    command: &device::Command,
+) -> Option<(ServiceName, Vec<Characteristic>)> {
+    match command {
+        device::Command::OnOff(v) if fan_service(capabilities).is_some() => Some((
+            fan_service(capabilities).unwrap(),
+            vec![Characteristic::Active(characteristics::Active {
+                active: v.on,
+            })],
+        )),
+        device::Command::OnOff(v) => {
+            let service_name = [ServiceName::Switch, ServiceName::Outlet]
+                .into_iter()
+                .find(|service_name| capabilities.service(*service_name).is_some())
+                .unwrap_or(ServiceName::Light);
+            Some((
+                service_name,
+                vec![Characteristic::On(characteristics::On { on: v.on })],
+            ))
+        }
+        device::Command::BrightnessAbsolute(v) => Some((
+            ServiceName::Light,
+            vec![Characteristic::Brightness(characteristics::Brightness {
+                percentage: v.brightness,
+            })],
+        )),
+        device::Command::ColorAbsolute(v) => {
+            let characteristics = match &v.color.value {
+                ColorValue::Temperature { temperature } => {
+                    vec![Characteristic::ColorTemperature(
+                        characteristics::ColorTemperature {
+                            mireds: color::kelvin_to_mireds((*temperature).into()),
+                        },
+                    )]
+                }
+                ColorValue::Rgb { spectrum_rgb } => {
+                    hsv_characteristics(color::rgb_to_hsv(*spectrum_rgb))
+                }
+                ColorValue::Hsv { spectrum_hsv } => hsv_characteristics(color::Hsv {
+                    hue: spectrum_hsv.hue as f32,
+                    saturation: spectrum_hsv.saturation as f32,
+                    value: spectrum_hsv.value as f32,
+                }),
+            };
+            Some((ServiceName::Light, characteristics))
+        }
+        device::Command::LockUnlock(v) => Some((
+            ServiceName::LockMechanism,
+            vec![Characteristic::TargetLockState(
+                characteristics::TargetLockState {
+                    state: if v.lock {
+                        LockTargetState::Secured
+                    } else {
+                        LockTargetState::Unsecured
+                    },
+                },
+            )],
+        )),
+        device::Command::OpenClose(v)
+            if capabilities.service(ServiceName::WindowCovering).is_some() =>
+        {
+            Some((
+                ServiceName::WindowCovering,
+                vec![Characteristic::TargetPosition(
+                    characteristics::TargetPosition {
+                        position: v.open_percent,
+                    },
+                )],
+            ))
+        }
+        device::Command::OpenClose(v) => Some((
+            ServiceName::GarageDoorOpener,
+            vec![Characteristic::TargetDoorState(
+                characteristics::TargetDoorState {
+                    open_percent: v.open_percent,
+                },
+            )],
+        )),
+        device::Command::SetFanSpeed(SetFanSpeed::Percent { fan_speed_percent }) => Some((
+            fan_service(capabilities)?,
+            vec![Characteristic::RotationSpeed(
+                characteristics::RotationSpeed {
+                    percentage: *fan_speed_percent,
+                },
+            )],
+        )),
+        // blinds can't be started without a target, only stopped
+        device::Command::StartStop(v) if !v.start => Some((
+            ServiceName::WindowCovering,
+            vec![Characteristic::HoldPosition(
+                characteristics::HoldPosition { hold: true },
+            )],
+        )),
+        device::Command::ThermostatTemperatureSetpoint(v) => Some((
+            ServiceName::Thermostat,
+            vec![Characteristic::TargetTemperature(
+                characteristics::TargetTemperature {
+                    temperature: v.thermostat_temperature_setpoint as f32,
+                },
+            )],
+        )),
+        device::Command::ThermostatSetMode(v) => Some((
+            ServiceName::Thermostat,
+            vec![Characteristic::TargetHeatingCoolingState(
+                characteristics::TargetHeatingCoolingState {
+                    mode: thermostat_mode_from_google(&v.thermostat_mode)?,
+                },
+            )],
+        )),
+        _ => None,
+    }
+}
+
+/// Checks the two-factor challenge of the command.
+///
+/// Unlocking requires the PIN of the structure, so a lock is never opened by an overheard voice command alone.
+/// Structures without a configured PIN only ask the user to acknowledge the unlocking.
+pub fn challenge(
+    command: &device::Command,
+    challenge: Option<&Challenge>,
+    pin: Option<&str>,
+) -> Result<(), ChallengeNeeded> {
+    let unlock = matches!(command, device::Command::LockUnlock(v) if !v.lock);
+    if !unlock {
+        return Ok(());
+    }
+    let pin = match pin {
+        Some(pin) => pin,
+        None if challenge.and_then(|challenge| challenge.ack) == Some(true) => return Ok(()),
+        None => {
+            return Err(ChallengeNeeded {
+                r#type: ChallengeType::AckNeeded,
+            })
+        }
+    };
+    let r#type = match challenge.and_then(|challenge| challenge.pin.as_deref()) {
+        Some(given) if bool::from(given.as_bytes().ct_eq(pin.as_bytes())) => return Ok(()),
+        Some(_) => ChallengeType::ChallengeFailedPinNeeded,
+        None => ChallengeType::PinNeeded,
+    };
+    Err(ChallengeNeeded { r#type })
+}
+
+/// Google error code of a failed accessory request.
+pub fn error_code(err: &accessory::Error) -> &'static str {
+    match err {
+        accessory::Error::NotConnected => "deviceOffline",
+        accessory::Error::ValueOutOfRange => "valueOutOfRange",
+        accessory::Error::CharacteristicReadOnly | accessory::Error::CharacteristicWriteOnly => {
+            "actionNotAvailable"
+        }
+        accessory::Error::CharacteristicNotSupported
+        | accessory::Error::ServiceNotSupported
+        | accessory::Error::RequestNotSupported => "functionNotSupported",
+        _ => "hardError",
+    }
+}
+
+fn hsv_characteristics(hsv: color::Hsv) -> Vec<Characteristic> {
+    vec![
+        Characteristic::Hue(characteristics::Hue { degrees: hsv.hue }),
+        Characteristic::Saturation(characteristics::Saturation {
+            percentage: hsv.saturation * 100.0,
+        }),
+        Characteristic::Brightness(characteristics::Brightness {
+            percentage: (hsv.value * 100.0).round() as u8,
+        }),
+    ]
+}
+
+/// Current color of the device as hue, saturation and value, starting from white if it wasn't set yet.
+fn spectrum_hsv(state: &State) -> (f64, f64, f64) {
+    match state.color {
+        Some(Color::SpectrumHsv {
+            hue,
+            saturation,
+            value,
+        }) => (hue, saturation, value),
+        _ => (
+            0.0,
+            0.0,
+            state.brightness.map_or(1.0, |v| f64::from(v) / 100.0),
+        ),
+    }
+}
+
+/// Name of the Google sensor and its descriptive states when something is detected and when it's not.
+fn sensor_state(service_name: ServiceName) -> Option<(&'static str, &'static str, &'static str)> {
+    match service_name {
+        ServiceName::MotionSensor => Some(("Motion", "motion detected", "no motion detected")),
+        ServiceName::OccupancySensor => {
+            Some(("Occupancy", "occupancy detected", "no occupancy detected"))
+        }
+        ServiceName::LeakSensor => Some(("WaterLeak", "leak", "no leak")),
+        ServiceName::SmokeSensor => Some(("SmokeLevel", "smoke detected", "no smoke detected")),
+        _ => None,
+    }
+}
+
+/// Fan or air purifier service of the accessory.
+fn fan_service(capabilities: &Capabilities) -> Option<ServiceName> {
+    [ServiceName::Fan, ServiceName::AirPurifier]
+        .into_iter()
+        .find(|service_name| capabilities.service(*service_name).is_some())
+}
+
+fn filter_cleanliness(change_needed: bool) -> &'static str {
+    if change_needed {
+        "needs replacement"
+    } else {
+        "clean"
+    }
+}
+
+/// Google sensor name and unit of a numeric sensor characteristic.
+///
+/// VOC density is left out, Google expects it in parts per million which can't be derived from µg/m³ without knowing the compounds.
+fn numeric_sensor_state(
+    characteristic_name: CharacteristicName,
+) -> Option<(&'static str, &'static str)> {
+    match characteristic_name {
+        CharacteristicName::CarbonDioxideLevel => Some(("CarbonDioxideLevel", "PARTS_PER_MILLION")),
+        CharacteristicName::Pm25Density => Some(("PM2.5", "MICROGRAMS_PER_CUBIC_METER")),
+        CharacteristicName::Pm10Density => Some(("PM10", "MICROGRAMS_PER_CUBIC_METER")),
+        CharacteristicName::FilterLifeLevel => Some(("FilterLifeTime", "PERCENTAGE")),
+        _ => None,
+    }
+}
+
+fn air_quality(quality: AirQualityLevel) -> &'static str {
+    match quality {
+        AirQualityLevel::Excellent => "healthy",
+        AirQualityLevel::Good => "good",
+        AirQualityLevel::Fair => "fair",
+        AirQualityLevel::Inferior => "poor",
+        AirQualityLevel::Poor => "very poor",
+        AirQualityLevel::Unknown => "unknown",
+    }
+}
+
+/// Replaces the previous state of the same sensor.
+fn set_sensor_state(state: &mut State, sensor_state: CurrentSensorState) {
+    state
+        .current_sensor_state_data
+        .retain(|current| current.name != sensor_state.name);
+    state.current_sensor_state_data.push(sensor_state);
+}
+
+fn thermostat_mode(mode: HeatingCoolingMode) -> &'static str {
+    match mode {
+        HeatingCoolingMode::Off => "off",
+        HeatingCoolingMode::Heat => "heat",
+        HeatingCoolingMode::Cool => "cool",
+        HeatingCoolingMode::Auto => "heatcool",
+    }
+}
+
+fn thermostat_mode_from_google(mode: &str) -> Option<HeatingCoolingMode> {
+    match mode {
+        "off" => Some(HeatingCoolingMode::Off),
+        "heat" => Some(HeatingCoolingMode::Heat),
+        "cool" => Some(HeatingCoolingMode::Cool),
+        "heatcool" | "auto" => Some(HeatingCoolingMode::Auto),
+        _ => None,
+    }
+}
+
+fn active_thermostat_mode(state: HeatingCoolingState) -> &'static str {
+    match state {
+        HeatingCoolingState::Off => "none",
+        HeatingCoolingState::Heat => "heat",
+        HeatingCoolingState::Cool => "cool",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use google_smart_home::device::commands;
+
+    #[test]
+    fn thermostat_sync() {
+        let r#type = accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Thermostat);
+        let capabilities = r#type.capabilities();
+        assert_eq!(device_type(&r#type), device::Type::Thermostat);
+        assert_eq!(
+            traits(&capabilities),
+            vec![device::Trait::TemperatureSetting]
+        );
+        let attributes = attributes(&capabilities);
+        assert_eq!(
+            attributes.available_thermostat_modes,
+            Some(vec![
+                String::from("off"),
+                String::from("heat"),
+                String::from("cool"),
+                String::from("heatcool"),
+            ])
+        );
+        assert_eq!(
+            attributes.thermostat_temperature_range,
+            Some(ThermostatTemperatureRange {
+                min_threshold_celsius: 10.0,
+                max_threshold_celsius: 38.0,
+            })
+        );
+    }
+
+    #[test]
+    fn thermostat_state() {
+        let mut state = State::default();
+        apply_state(
+            &mut state,
+            ServiceName::Thermostat,
+            &Characteristic::TargetTemperature(characteristics::TargetTemperature {
+                temperature: 21.5,
+            }),
+        );
+        apply_state(
+            &mut state,
+            ServiceName::Thermostat,
+            &Characteristic::CurrentHeatingCoolingState(
+                characteristics::CurrentHeatingCoolingState {
+                    state: HeatingCoolingState::Off,
+                },
+            ),
+        );
+        assert_eq!(state.thermostat_temperature_setpoint, Some(21.5));
+        assert_eq!(state.active_thermostat_mode.as_deref(), Some("none"));
+    }
+
+    #[test]
+    fn thermostat_commands() {
+        let capabilities =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Thermostat)
+                .capabilities();
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::ThermostatTemperatureSetpoint(
+                    commands::ThermostatTemperatureSetpoint {
+                        thermostat_temperature_setpoint: 22.0,
+                    }
+                )
+            ),
+            Some((
+                ServiceName::Thermostat,
+                vec![Characteristic::TargetTemperature(
+                    characteristics::TargetTemperature { temperature: 22.0 }
+                )]
+            ))
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::ThermostatSetMode(commands::ThermostatSetMode {
+                    thermostat_mode: String::from("heatcool"),
+                })
+            ),
+            Some((
+                ServiceName::Thermostat,
+                vec![Characteristic::TargetHeatingCoolingState(
+                    characteristics::TargetHeatingCoolingState {
+                        mode: HeatingCoolingMode::Auto,
+                    }
+                )]
+            ))
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::ThermostatSetMode(commands::ThermostatSetMode {
+                    thermostat_mode: String::from("eco"),
+                })
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn light_sync() {
+        let r#type = accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Lightbulb {
+            brightness: true,
+            color: true,
+            color_temperature: true,
+        });
+        let capabilities = r#type.capabilities();
+        assert_eq!(
+            traits(&capabilities),
+            vec![
+                device::Trait::OnOff,
+                device::Trait::Brightness,
+                device::Trait::ColorSetting
+            ]
+        );
+        let attributes = attributes(&capabilities);
+        assert_eq!(attributes.color_model, Some(ColorModel::Hsv));
+        assert_eq!(
+            attributes.color_temperature_range,
+            Some(ColorTemperatureRange {
+                temperature_min_k: 2000,
+                temperature_max_k: 7142,
+            })
+        );
+    }
+
+    #[test]
+    fn light_state() {
+        let mut state = State {
+            brightness: Some(50),
+            ..Default::default()
+        };
+        apply_state(
+            &mut state,
+            ServiceName::Light,
+            &Characteristic::Hue(characteristics::Hue { degrees: 120.0 }),
+        );
+        apply_state(
+            &mut state,
+            ServiceName::Light,
+            &Characteristic::Saturation(characteristics::Saturation { percentage: 25.0 }),
+        );
+        assert_eq!(
+            state.color,
+            Some(Color::SpectrumHsv {
+                hue: 120.0,
+                saturation: 0.25,
+                value: 0.5,
+            })
+        );
+        apply_state(
+            &mut state,
+            ServiceName::Light,
+            &Characteristic::ColorTemperature(characteristics::ColorTemperature { mireds: 250 }),
+        );
+        assert_eq!(state.color, Some(Color::TemperatureK(4000)));
+    }
+
+    #[test]
+    fn light_commands() {
+        let capabilities =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Lightbulb {
+                brightness: true,
+                color: true,
+                color_temperature: true,
+            })
+            .capabilities();
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::ColorAbsolute(commands::ColorAbsolute {
+                    color: commands::Color {
+                        name: None,
+                        value: ColorValue::Temperature { temperature: 4000 },
+                    },
+                })
+            ),
+            Some((
+                ServiceName::Light,
+                vec![Characteristic::ColorTemperature(
+                    characteristics::ColorTemperature { mireds: 250 }
+                )]
+            ))
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::ColorAbsolute(commands::ColorAbsolute {
+                    color: commands::Color {
+                        name: None,
+                        value: ColorValue::Rgb {
+                            spectrum_rgb: 0x00FF00
+                        },
+                    },
+                })
+            ),
+            Some((
+                ServiceName::Light,
+                vec![
+                    Characteristic::Hue(characteristics::Hue { degrees: 120.0 }),
+                    Characteristic::Saturation(characteristics::Saturation { percentage: 100.0 }),
+                    Characteristic::Brightness(characteristics::Brightness { percentage: 100 }),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn unlock_challenge() {
+        let unlock = device::Command::LockUnlock(commands::LockUnlock {
+            lock: false,
+            follow_up_token: None,
+        });
+        let lock = device::Command::LockUnlock(commands::LockUnlock {
+            lock: true,
+            follow_up_token: None,
+        });
+        let pin = |pin: &str| Challenge {
+            ack: None,
+            pin: Some(pin.to_string()),
+        };
+        let ack = Challenge {
+            ack: Some(true),
+            pin: None,
+        };
+        let needed = |r#type| Err(ChallengeNeeded { r#type });
+
+        assert_eq!(challenge(&lock, None, Some("1234")), Ok(()));
+        assert_eq!(
+            challenge(&unlock, None, Some("1234")),
+            needed(ChallengeType::PinNeeded)
+        );
+        assert_eq!(
+            challenge(&unlock, Some(&pin("0000")), Some("1234")),
+            needed(ChallengeType::ChallengeFailedPinNeeded)
+        );
+        assert_eq!(challenge(&unlock, Some(&pin("1234")), Some("1234")), Ok(()));
+        assert_eq!(
+            challenge(&unlock, Some(&pin("12345")), Some("1234")),
+            needed(ChallengeType::ChallengeFailedPinNeeded)
+        );
+        assert_eq!(
+            challenge(&unlock, Some(&ack), Some("1234")),
+            needed(ChallengeType::PinNeeded)
+        );
+
+        // without a PIN an acknowledgement is enough
+        assert_eq!(
+            challenge(&unlock, None, None),
+            needed(ChallengeType::AckNeeded)
+        );
+        assert_eq!(
+            challenge(&unlock, Some(&pin("1234")), None),
+            needed(ChallengeType::AckNeeded)
+        );
+        assert_eq!(challenge(&unlock, Some(&ack), None), Ok(()));
+    }
+
+    #[test]
+    fn binary_sensors() {
+        let r#type = accessory::Type::Houseflow(accessory::manufacturers::Houseflow::LeakSensor);
+        let capabilities = r#type.capabilities();
+        assert_eq!(traits(&capabilities), vec![device::Trait::SensorState]);
+        assert_eq!(
+            attributes(&capabilities).sensor_states_supported,
+            Some(vec![SensorStateSupported {
+                name: String::from("WaterLeak"),
+                descriptive_capabilities: Some(SensorDescriptiveCapabilities {
+                    available_states: vec![String::from("leak"), String::from("no leak")],
+                }),
+                numeric_capabilities: None,
+            }])
+        );
+
+        let mut state = State::default();
+        for detected in [true, false] {
+            apply_state(
+                &mut state,
+                ServiceName::LeakSensor,
+                &ServiceName::LeakSensor.detected(detected).unwrap(),
+            );
+        }
+        assert_eq!(
+            state.current_sensor_state_data,
+            vec![CurrentSensorState {
+                name: String::from("WaterLeak"),
+                current_sensor_state: Some(String::from("no leak")),
+                raw_value: None,
+            }]
+        );
+
+        let r#type = accessory::Type::Houseflow(accessory::manufacturers::Houseflow::ContactSensor);
+        let capabilities = r#type.capabilities();
+        assert_eq!(traits(&capabilities), vec![device::Trait::OpenClose]);
+        assert_eq!(attributes(&capabilities).query_only_open_close, Some(true));
+        apply_state(
+            &mut state,
+            ServiceName::ContactSensor,
+            &ServiceName::ContactSensor.detected(false).unwrap(),
+        );
+        assert_eq!(state.open_percent, Some(100));
+    }
+
+    #[test]
+    fn air_quality_sensor() {
+        let capabilities =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::AirQualitySensor)
+                .capabilities();
+        assert_eq!(traits(&capabilities), vec![device::Trait::SensorState]);
+        let sensor_states = attributes(&capabilities).sensor_states_supported.unwrap();
+        assert_eq!(
+            sensor_states
+                .iter()
+                .map(|sensor_state| sensor_state.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["AirQuality", "CarbonDioxideLevel", "PM2.5", "PM10"]
+        );
+        assert_eq!(
+            sensor_states[1].numeric_capabilities,
+            Some(SensorNumericCapabilities {
+                raw_value_unit: String::from("PARTS_PER_MILLION"),
+            })
+        );
+
+        let mut state = State::default();
+        for ppm in [600.0, 1200.0] {
+            apply_state(
+                &mut state,
+                ServiceName::AirQualitySensor,
+                &Characteristic::CarbonDioxideLevel(characteristics::CarbonDioxideLevel { ppm }),
+            );
+        }
+        apply_state(
+            &mut state,
+            ServiceName::AirQualitySensor,
+            &Characteristic::AirQuality(characteristics::AirQuality {
+                quality: AirQualityLevel::Inferior,
+            }),
+        );
+        assert_eq!(
+            state.current_sensor_state_data,
+            vec![
+                CurrentSensorState {
+                    name: String::from("CarbonDioxideLevel"),
+                    current_sensor_state: None,
+                    raw_value: Some(1200.0),
+                },
+                CurrentSensorState {
+                    name: String::from("AirQuality"),
+                    current_sensor_state: Some(String::from("poor")),
+                    raw_value: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn air_purifier() {
+        let capabilities =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::AirPurifier)
+                .capabilities();
+        assert_eq!(
+            traits(&capabilities),
+            vec![
+                device::Trait::OnOff,
+                device::Trait::FanSpeed,
+                device::Trait::SensorState
+            ]
+        );
+        let attributes = attributes(&capabilities);
+        assert_eq!(attributes.supports_fan_speed_percent, Some(true));
+        assert_eq!(
+            attributes
+                .sensor_states_supported
+                .unwrap()
+                .iter()
+                .map(|sensor_state| sensor_state.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["FilterCleanliness", "FilterLifeTime"]
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::OnOff(commands::OnOff { on: true })
+            ),
+            Some((
+                ServiceName::AirPurifier,
+                vec![Characteristic::Active(characteristics::Active {
+                    active: true
+                })]
+            ))
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::SetFanSpeed(commands::SetFanSpeed::Percent {
+                    fan_speed_percent: 30
+                })
+            ),
+            Some((
+                ServiceName::AirPurifier,
+                vec![Characteristic::RotationSpeed(
+                    characteristics::RotationSpeed { percentage: 30 }
+                )]
+            ))
+        );
+
+        let mut state = State::default();
+        apply_state(
+            &mut state,
+            ServiceName::AirPurifier,
+            &Characteristic::RotationSpeed(characteristics::RotationSpeed { percentage: 30 }),
+        );
+        apply_state(
+            &mut state,
+            ServiceName::AirPurifier,
+            &Characteristic::FilterLifeLevel(characteristics::FilterLifeLevel { percentage: 12 }),
+        );
+        assert_eq!(state.current_fan_speed_percent, Some(30));
+        assert_eq!(
+            state.current_sensor_state_data,
+            vec![CurrentSensorState {
+                name: String::from("FilterLifeTime"),
+                current_sensor_state: None,
+                raw_value: Some(12.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn blinds() {
+        let capabilities =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Blinds).capabilities();
+        assert_eq!(
+            traits(&capabilities),
+            vec![device::Trait::OpenClose, device::Trait::StartStop]
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::OpenClose(commands::OpenClose {
+                    open_percent: 40,
+                    open_direction: Some(OpenDirection::Up),
+                })
+            ),
+            Some((
+                ServiceName::WindowCovering,
+                vec![Characteristic::TargetPosition(
+                    characteristics::TargetPosition { position: 40 }
+                )]
+            ))
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::StartStop(commands::StartStop {
+                    start: false,
+                    zone: None,
+                })
+            ),
+            Some((
+                ServiceName::WindowCovering,
+                vec![Characteristic::HoldPosition(
+                    characteristics::HoldPosition { hold: true }
+                )]
+            ))
+        );
+
+        let garage =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Garage).capabilities();
+        assert_eq!(
+            command(
+                &garage,
+                &device::Command::OpenClose(commands::OpenClose {
+                    open_percent: 100,
+                    open_direction: None,
+                })
+            ),
+            Some((
+                ServiceName::GarageDoorOpener,
+                vec![Characteristic::TargetDoorState(
+                    characteristics::TargetDoorState { open_percent: 100 }
+                )]
+            ))
+        );
+    }
+
+    #[test]
+    fn outlet() {
+        let capabilities =
+            accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Outlet).capabilities();
+        assert_eq!(
+            traits(&capabilities),
+            vec![device::Trait::OnOff, device::Trait::EnergyStorage]
+        );
+        assert_eq!(
+            attributes(&capabilities).query_only_energy_storage,
+            Some(true)
+        );
+        assert_eq!(
+            command(
+                &capabilities,
+                &device::Command::OnOff(commands::OnOff { on: true })
+            ),
+            Some((
+                ServiceName::Outlet,
+                vec![Characteristic::On(characteristics::On { on: true })]
+            ))
+        );
+
+        let mut state = State::default();
+        apply_state(
+            &mut state,
+            ServiceName::Outlet,
+            &Characteristic::OutletInUse(characteristics::OutletInUse { in_use: true }),
+        );
+        apply_state(
+            &mut state,
+            ServiceName::Outlet,
+            &Characteristic::EnergyConsumption(characteristics::EnergyConsumption {
+                kilowatt_hours: 1.5,
+            }),
+        );
+        assert_eq!(state.is_plugged_in, Some(true));
+        assert_eq!(
+            state.capacity_remaining,
+            vec![CapacityValue {
+                raw_value: 1.5,
+                unit: CapacityUnit::KilowattHours,
+            }]
+        );
+    }
+}
@@ -1,27 +1,25 @@
+pub mod admin;
 pub mod auth;
 pub mod clerk;
 pub mod controllers;
 pub mod extensions;
 pub mod extractors;
+pub mod google;
 pub mod mailer;
 pub mod providers;
 
 use acu::MasterExt;
 use anyhow::Context;
-use arc_swap::ArcSwap;
 use axum::extract::Extension;
 use houseflow_config::dynamic;
 use houseflow_config::server::Config;
 use houseflow_config::server::Network as NetworkConfig;
 use houseflow_config::server::Tls as TlsConfig;
-use houseflow_types::hub::Hub;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use crate::providers::lighthouse::LighthouseProviderMessage;
 
-pub type ConfiguredHubs = Arc<ArcSwap<Vec<Hub>>>;
-
 pub struct ArgMailers {
     pub dummy: Option<mailer::dummy::Handle>,
     pub smtp: Option<mailer::smtp::Handle>,
@@ -41,10 +39,12 @@ type ControllerCreateFn<H> = CreateFn<providers::MasterHandle, H>;
 pub struct ArgControllers {
     // pub dummy: Option<controllers::dum>,
     pub meta: ControllerCreateFn<controllers::meta::Handle>,
+    pub google: ControllerCreateFn<controllers::google::Accessories>,
 }
 
 pub struct Arg {
     pub config: dynamic::Config<Config>,
+    pub config_file: admin::ConfigFile,
     pub clerk: Arc<dyn clerk::Clerk>,
     pub mailers: ArgMailers,
     pub providers: ArgProviders,
@@ -60,6 +60,7 @@ impl Server {
     pub async fn new(
         Arg {
             config,
+            config_file,
             clerk,
             mailers,
             providers,
@@ -71,18 +72,31 @@ impl Server {
 
         let router = Router::new()
             .route("/health-check", get(health_check))
-            .nest("/auth", auth::app());
+            .nest("/auth", auth::app())
+            .nest("/admin", admin::app());
 
         let master_controller = controllers::MasterHandle::new();
         let master_provider = providers::MasterHandle::new();
+        master_controller
+            .push(master_provider.router(controllers::Name::Router))
+            .await;
 
         let controller_router = async {
-            let ArgControllers { meta } = controllers;
+            let ArgControllers { meta, google } = controllers;
             let mut router = Router::new();
             if let Some(meta) = meta {
                 let meta = meta(master_provider.clone());
                 master_controller.push(meta.clone()).await;
-                router = router.nest("/meta", controllers::meta::app(meta));
+                let events = controllers::meta::Events::new();
+                master_controller
+                    .push(events.controller(master_provider.clone()))
+                    .await;
+                router = router.nest("/meta", controllers::meta::app(meta, events));
+            }
+            if let Some(google) = google {
+                let accessories = google(master_provider.clone());
+                master_controller.push(accessories.controller()).await;
+                router = router.nest("/google", controllers::google::app(accessories));
             }
             router
         }
@@ -136,6 +150,7 @@ impl Server {
             .nest("/controller", controller_router)
             .nest("/provider", provider_router)
             .layer(Extension(config.clone()))
+            .layer(Extension(config_file))
             .layer(Extension(clerk))
             .layer(Extension(master_controller))
             .layer(Extension(master_provider))
@@ -253,7 +268,10 @@ mod test_utils {
                 smtp: None,
                 dummy: Some(mailers::Dummy {}),
             },
-            controllers: Controllers { meta: None },
+            controllers: Controllers {
+                meta: None,
+                google: None,
+            },
             providers: Providers { lighthouse: None },
             logins: Logins {
                 google: Some(GoogleLogin {
@@ -353,6 +371,7 @@ mod test_utils {
             username: format!("john-{}", id.clone()),
             email: lettre::Address::new("john", "email.com").unwrap(),
             admin: false,
+            preferences: Default::default(),
         }
     }
 }
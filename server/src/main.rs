@@ -6,6 +6,7 @@ use houseflow_config::server::Mailers;
 use houseflow_config::server::Providers;
 use houseflow_config::Config as _;
 use houseflow_config::Error as ConfigError;
+use houseflow_server::admin::ConfigFile;
 use houseflow_server::clerk::Clerk;
 use houseflow_server::mailer;
 use houseflow_server::providers;
@@ -71,31 +72,45 @@ async fn main() -> Result<(), anyhow::Error> {
         }
     };
 
+    let controllers_config = config.controllers.to_owned();
+    let providers_config = config.providers.to_owned();
+    let config = dynamic::Config::new(config);
+
     let providers = {
-        let Providers { lighthouse } = config.providers.to_owned();
+        let Providers { lighthouse } = providers_config;
         ArgProviders {
             dummy: None,
             lighthouse: match lighthouse {
-                Some(lighthouse) => Some(Box::new(|master_controller| {
-                    providers::lighthouse::new(master_controller, lighthouse)
-                })),
+                Some(_) => {
+                    let config = config.clone();
+                    Some(Box::new(move |master_controller| {
+                        providers::lighthouse::new(master_controller, config)
+                    }))
+                }
                 None => None,
             },
         }
     };
 
     let controllers = {
-        let Controllers { meta } = config.controllers.to_owned();
+        let Controllers { meta, google } = controllers_config;
         ArgControllers {
             meta: match meta {
                 Some(_meta) => Some(Box::new(|_master_controller| controllers::meta::new())),
                 None => None,
             },
+            google: match google {
+                Some(_google) => Some(Box::new(|_master_controller| {
+                    controllers::google::Accessories::new()
+                })),
+                None => None,
+            },
         }
     };
 
     let server = Server::new(Arg {
-        config: dynamic::Config::new(config),
+        config,
+        config_file: ConfigFile::new(Some(config_path)),
         clerk,
         mailers,
         providers,
@@ -1,8 +1,12 @@
 use super::Message;
 use crate::controllers;
 use crate::controllers::ControllerExt;
-use crate::ConfiguredHubs;
+use crate::extensions;
 use anyhow::Context;
+use argon2::password_hash::PasswordHasher;
+use argon2::password_hash::PasswordVerifier;
+use argon2::password_hash::SaltString;
+use argon2::Argon2;
 use async_trait::async_trait;
 use axum::body::Body;
 use axum::extract::Extension;
@@ -11,17 +15,26 @@ use axum::headers;
 use axum::http::StatusCode;
 use axum::response::Response;
 use axum::Router;
-use houseflow_config::server::providers::Lighthouse as Config;
+use houseflow_config::dynamic;
+use houseflow_config::server::providers::LighthouseHub;
+use houseflow_config::server::Config;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::accessory::CharacteristicRead;
+use houseflow_types::accessory::CharacteristicWrite;
 use houseflow_types::hub;
 use houseflow_types::lighthouse;
+use houseflow_types::protocol;
+use houseflow_types::routing;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
 use std::collections::HashMap;
-use std::collections::HashSet;
+use subtle::ConstantTimeEq;
 use tokio::sync::oneshot;
 
 pub type Server = ezsockets::Server<LighthouseProvider>;
@@ -33,19 +46,38 @@ pub enum LighthouseProviderMessage {
         id: hub::ID,
         respond_to: oneshot::Sender<bool>,
     },
+    AccessoryConnected {
+        hub_id: hub::ID,
+        accessory_id: accessory::ID,
+        capabilities: Capabilities,
+    },
+    AccessoryDisconnected {
+        accessory_id: accessory::ID,
+    },
     Message(Message),
 }
 
 #[derive(Debug, Clone)]
 pub struct LighthouseProvider {
+    server: Server,
     sessions: HashMap<hub::ID, Session>,
+    /// Hubs that the accessories are connected to
+    accessories: HashMap<accessory::ID, hub::ID>,
+    /// Capabilities announced by the connected accessories, writes are validated against them before being sent
+    capabilities: HashMap<accessory::ID, Capabilities>,
     controller: controllers::MasterHandle,
-    config: Config,
+    config: dynamic::Config<Config>,
 }
 
-pub fn new(master_controller: controllers::MasterHandle, config: Config) -> Server {
-    let (server, _) = Server::create(|_| LighthouseProvider {
+pub fn new(
+    master_controller: controllers::MasterHandle,
+    config: dynamic::Config<Config>,
+) -> Server {
+    let (server, _) = Server::create(|server| LighthouseProvider {
+        server,
         sessions: Default::default(),
+        accessories: Default::default(),
+        capabilities: Default::default(),
         controller: master_controller,
         config,
     });
@@ -62,7 +94,7 @@ impl ezsockets::ServerExt for LighthouseProvider {
         &mut self,
         socket: ezsockets::Socket,
         _address: std::net::SocketAddr,
-        (hub_id, hub_password_hash): <Self::Session as ezsockets::SessionExt>::Args,
+        hub_id: <Self::Session as ezsockets::SessionExt>::Args,
     ) -> Result<
         ezsockets::Session<
             <Self::Session as ezsockets::SessionExt>::ID,
@@ -70,23 +102,19 @@ impl ezsockets::ServerExt for LighthouseProvider {
         >,
         ezsockets::Error,
     > {
-        assert_eq!(
-            self.config
-                .hubs
-                .iter()
-                .find(|hub| hub.id == hub_id)
-                .unwrap()
-                .password_hash,
-            hub_password_hash
-        );
         let session = Session::create(
             |handle| LighthouseSession {
                 session: handle,
+                server: self.server.clone(),
                 hub_id,
                 controller: self.controller.clone(),
-                connected_accessories: Default::default(),
                 characteristic_write_results: Default::default(),
                 characteristic_read_results: Default::default(),
+                batch_write_results: Default::default(),
+                batch_read_results: Default::default(),
+                identify_results: Default::default(),
+                peer: None,
+                encoding: Default::default(),
             },
             hub_id,
             socket,
@@ -100,6 +128,17 @@ impl ezsockets::ServerExt for LighthouseProvider {
         id: <Self::Session as ezsockets::SessionExt>::ID,
     ) -> Result<(), ezsockets::Error> {
         self.sessions.remove(&id);
+        let accessories = self
+            .accessories
+            .iter()
+            .filter(|(_, hub_id)| **hub_id == id)
+            .map(|(accessory_id, _)| *accessory_id)
+            .collect::<Vec<_>>();
+        for accessory_id in accessories {
+            self.accessories.remove(&accessory_id);
+            self.capabilities.remove(&accessory_id);
+            self.controller.disconnected(accessory_id).await;
+        }
         Ok(())
     }
 
@@ -108,6 +147,18 @@ impl ezsockets::ServerExt for LighthouseProvider {
             LighthouseProviderMessage::IsHubConnected { id, respond_to } => {
                 respond_to.send(self.sessions.contains_key(&id)).unwrap();
             }
+            LighthouseProviderMessage::AccessoryConnected {
+                hub_id,
+                accessory_id,
+                capabilities,
+            } => {
+                self.accessories.insert(accessory_id, hub_id);
+                self.capabilities.insert(accessory_id, capabilities);
+            }
+            LighthouseProviderMessage::AccessoryDisconnected { accessory_id } => {
+                self.accessories.remove(&accessory_id);
+                self.capabilities.remove(&accessory_id);
+            }
             LighthouseProviderMessage::Message(message) => match message {
                 Message::ReadCharacteristic {
                     accessory_id,
@@ -117,7 +168,6 @@ impl ezsockets::ServerExt for LighthouseProvider {
                 } => {
                     let hub_session = self
                         .find_accessory_session(accessory_id)
-                        .await
                         .context("hub with the accessory is not connected")?;
                     let result = hub_session
                         .call_with(|respond_to| SessionMessage::ReadCharacteristic {
@@ -137,9 +187,14 @@ impl ezsockets::ServerExt for LighthouseProvider {
                     characteristic,
                     respond_to,
                 } => {
+                    if let Err(err) =
+                        self.validate_write(accessory_id, service_name, &characteristic)
+                    {
+                        respond_to.send(Err(err)).unwrap();
+                        return Ok(());
+                    }
                     let hub_session = self
                         .find_accessory_session(accessory_id)
-                        .await
                         .context("hub with the accessory is not connected")?;
                     let result = hub_session
                         .call_with(|respond_to| SessionMessage::WriteCharacteristic {
@@ -153,29 +208,107 @@ impl ezsockets::ServerExt for LighthouseProvider {
                         .unwrap();
                     respond_to.send(result).unwrap();
                 }
-                Message::GetAccessories { respond_to } => {
-                    let accessories = self.sessions.values().map(|session| {
-                        session
-                            .call_with(|respond_to| SessionMessage::GetAccessories { respond_to })
+                Message::ReadCharacteristics { reads, respond_to } => {
+                    let reads: Vec<_> = reads
+                        .into_iter()
+                        .map(|read| (self.route(read.accessory_id), read))
+                        .collect();
+                    tokio::spawn(async move {
+                        let results = routing::scatter(
+                            reads,
+                            |(hub_id, _)| *hub_id,
+                            |(_, hub_session), reads| async move {
+                                hub_session
+                                    .call_with(|respond_to| SessionMessage::ReadCharacteristics {
+                                        reads,
+                                        respond_to,
+                                    })
+                                    .await
+                                    .await
+                                    .unwrap_or_default()
+                            },
+                        )
+                        .await;
+                        respond_to.send(results).unwrap();
                     });
-                    let accessories = futures::future::join_all(accessories).await;
-                    let accessories = accessories.into_iter().flatten().collect();
-                    respond_to.send(accessories).unwrap();
                 }
-                Message::IsConnected {
+                Message::WriteCharacteristics { writes, respond_to } => {
+                    let writes: Vec<_> = writes
+                        .into_iter()
+                        .map(|write| {
+                            let hub = self
+                                .validate_write(
+                                    write.accessory_id,
+                                    write.service_name,
+                                    &write.characteristic,
+                                )
+                                .and_then(|()| self.route(write.accessory_id));
+                            (hub, write)
+                        })
+                        .collect();
+                    tokio::spawn(async move {
+                        let results = routing::scatter(
+                            writes,
+                            |(hub_id, _)| *hub_id,
+                            |(_, hub_session), writes| async move {
+                                hub_session
+                                    .call_with(|respond_to| SessionMessage::WriteCharacteristics {
+                                        writes,
+                                        respond_to,
+                                    })
+                                    .await
+                                    .await
+                                    .unwrap_or_default()
+                            },
+                        )
+                        .await;
+                        respond_to.send(results).unwrap();
+                    });
+                }
+                Message::Identify {
                     accessory_id,
                     respond_to,
                 } => {
-                    let values = self.sessions.iter().map(|(_, session)| {
-                        session.call_with(|respond_to| SessionMessage::IsAccessoryConnected {
+                    let hub_session = self
+                        .find_accessory_session(accessory_id)
+                        .context("hub with the accessory is not connected")?;
+                    let result = hub_session
+                        .call_with(|respond_to| SessionMessage::Identify {
                             accessory_id,
                             respond_to,
                         })
-                    });
-                    let values = futures::future::join_all(values).await;
-                    let is_connected = values.iter().any(|is_connected| *is_connected);
+                        .await
+                        .await
+                        .unwrap();
+                    respond_to.send(result).unwrap();
+                }
+                Message::GetAccessories { respond_to } => {
+                    let accessories = self.accessories.keys().copied().collect();
+                    respond_to.send(accessories).unwrap();
+                }
+                Message::IsConnected {
+                    accessory_id,
+                    respond_to,
+                } => {
+                    let is_connected = self.accessories.contains_key(&accessory_id);
                     respond_to.send(is_connected).unwrap();
                 }
+                Message::GetAccessoryCapabilities {
+                    accessory_id,
+                    respond_to,
+                } => {
+                    let capabilities = self.capabilities.get(&accessory_id).cloned();
+                    respond_to.send(capabilities).unwrap();
+                }
+                Message::GetAccessoryStructure {
+                    accessory_id,
+                    respond_to,
+                } => {
+                    let structure_id = self.accessories.get(&accessory_id).and_then(|hub_id| {
+                        find_hub(&self.config.get(), hub_id).map(|hub| hub.structure_id)
+                    });
+                    respond_to.send(structure_id).unwrap();
+                }
             },
         };
         Ok(())
@@ -183,22 +316,34 @@ impl ezsockets::ServerExt for LighthouseProvider {
 }
 
 impl LighthouseProvider {
-    async fn find_accessory_session(&mut self, accessory_id: accessory::ID) -> Option<&Session> {
-        let values = self.sessions.iter().map(|(_, session)| async move {
-            (
-                session,
-                session
-                    .call_with(|respond_to| SessionMessage::IsAccessoryConnected {
-                        accessory_id,
-                        respond_to,
-                    })
-                    .await,
-            )
-        });
-        let accessories = futures::future::join_all(values).await;
-        accessories
-            .into_iter()
-            .find_map(|(session, is_connected)| if is_connected { Some(session) } else { None })
+    fn find_accessory_session(&self, accessory_id: accessory::ID) -> Option<&Session> {
+        let hub_id = self.accessories.get(&accessory_id)?;
+        self.sessions.get(hub_id)
+    }
+
+    /// Returns the session of the hub that the accessory is connected to, along with the hub ID to group batch requests by
+    fn route(&self, accessory_id: accessory::ID) -> Result<(hub::ID, Session), accessory::Error> {
+        let hub_id = self
+            .accessories
+            .get(&accessory_id)
+            .ok_or(accessory::Error::NotConnected)?;
+        let hub_session = self
+            .sessions
+            .get(hub_id)
+            .ok_or(accessory::Error::NotConnected)?;
+        Ok((*hub_id, hub_session.clone()))
+    }
+
+    fn validate_write(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic: &Characteristic,
+    ) -> Result<(), accessory::Error> {
+        self.capabilities
+            .get(&accessory_id)
+            .ok_or(accessory::Error::NotConnected)?
+            .validate_write(service_name, characteristic)
     }
 }
 
@@ -209,6 +354,7 @@ pub struct HubCredentials(hub::ID, hub::Password);
 pub enum ConnectError {
     InvalidAuthorizationHeader(String),
     HubNotFound,
+    InvalidPassword,
     HubAlreadyConnected,
 }
 
@@ -217,6 +363,7 @@ impl axum::response::IntoResponse for ConnectError {
         let status = match self {
             Self::InvalidAuthorizationHeader(_) => StatusCode::BAD_REQUEST,
             Self::HubNotFound => StatusCode::UNAUTHORIZED,
+            Self::InvalidPassword => StatusCode::UNAUTHORIZED,
             Self::HubAlreadyConnected => StatusCode::NOT_ACCEPTABLE,
         };
         let mut response = axum::Json(self).into_response();
@@ -246,18 +393,69 @@ impl axum::extract::FromRequest<Body> for HubCredentials {
     }
 }
 
+/// Hashes the password of a hub with Argon2 and a random salt, only the hashes are kept in the configuration
+pub fn hash_password(password: &str) -> hub::PasswordHash {
+    let salt = SaltString::generate(&mut rand::rngs::OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("default Argon2 parameters are valid")
+        .to_string()
+}
+
+/// Checks the password of a hub against the hash from the configuration.
+pub fn verify_password(password: &str, password_hash: &str) -> PasswordVerification {
+    match argon2::PasswordHash::new(password_hash) {
+        Ok(hash) => match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => PasswordVerification::Valid,
+            Err(_) => PasswordVerification::Invalid,
+        },
+        // hex-encoded SHA-256 hashes written before Argon2 was used
+        Err(_) => {
+            let legacy_hash = hex::encode(Sha256::digest(password.as_bytes()));
+            if bool::from(legacy_hash.as_bytes().ct_eq(password_hash.as_bytes())) {
+                PasswordVerification::Legacy
+            } else {
+                PasswordVerification::Invalid
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordVerification {
+    Valid,
+    Invalid,
+    /// Password matches an unsalted SHA-256 hash, which has to be replaced with an Argon2 one
+    Legacy,
+}
+
+fn find_hub<'a>(config: &'a Config, hub_id: &hub::ID) -> Option<&'a LighthouseHub> {
+    config
+        .providers
+        .lighthouse
+        .as_ref()?
+        .hubs
+        .iter()
+        .find(|hub| hub.id == *hub_id)
+}
+
 pub async fn websocket_handler(
     websocket: ezsockets::axum::Upgrade,
-    Extension(configured_hubs): Extension<ConfiguredHubs>,
+    config: extensions::Config,
+    config_file: extensions::ConfigFile,
     Extension(server): Extension<Server>,
     HubCredentials(hub_id, password): HubCredentials,
 ) -> Result<impl axum::response::IntoResponse, ConnectError> {
-    let hub = configured_hubs
-        .load()
-        .iter()
-        .find(|hub| hub.id == hub_id)
+    let hub = find_hub(&config.get(), &hub_id)
         .cloned()
         .ok_or(ConnectError::HubNotFound)?;
+    match verify_password(&password, &hub.password_hash) {
+        PasswordVerification::Valid => {}
+        PasswordVerification::Invalid => return Err(ConnectError::InvalidPassword),
+        PasswordVerification::Legacy => {
+            migrate_password_hash(&config, &config_file, hub.id, &password).await
+        }
+    }
     let is_connected = server
         .call_with(|respond_to| LighthouseProviderMessage::IsHubConnected {
             id: hub.id,
@@ -268,9 +466,37 @@ pub async fn websocket_handler(
         return Err(ConnectError::HubAlreadyConnected);
     }
 
-    // TODO: Verify password and remove following line
-    tracing::warn!("{} connected without password", hub_id);
-    Ok(websocket.on_upgrade(server, (hub_id, password)))
+    Ok(websocket.on_upgrade(server, hub_id))
+}
+
+/// Replaces the legacy password hash of the hub with an Argon2 one.
+///
+/// Failing to do so doesn't prevent the hub from connecting, the migration is retried on its next connection.
+async fn migrate_password_hash(
+    config: &extensions::Config,
+    config_file: &extensions::ConfigFile,
+    hub_id: hub::ID,
+    password: &str,
+) {
+    let password_hash = hash_password(password);
+    let result = crate::admin::update(config, config_file, |config| {
+        let hubs = config
+            .providers
+            .lighthouse
+            .iter_mut()
+            .flat_map(|lighthouse| &mut lighthouse.hubs);
+        for hub in hubs.filter(|hub| hub.id == hub_id) {
+            hub.password_hash = password_hash.clone();
+        }
+        Ok(())
+    })
+    .await;
+    match result {
+        Ok(()) => tracing::info!(%hub_id, "migrated the legacy password hash of the hub to Argon2"),
+        Err(err) => {
+            tracing::error!(%hub_id, "failed to migrate the legacy password hash of the hub: {}", err)
+        }
+    }
 }
 
 pub fn app(server: Server) -> Router {
@@ -283,13 +509,6 @@ pub fn app(server: Server) -> Router {
 
 #[derive(Debug)]
 pub enum SessionMessage {
-    GetAccessories {
-        respond_to: oneshot::Sender<Vec<accessory::ID>>,
-    },
-    IsAccessoryConnected {
-        accessory_id: accessory::ID,
-        respond_to: oneshot::Sender<bool>,
-    },
     ReadCharacteristic {
         accessory_id: accessory::ID,
         service_name: ServiceName,
@@ -302,48 +521,234 @@ pub enum SessionMessage {
         characteristic: Characteristic,
         respond_to: oneshot::Sender<oneshot::Receiver<Result<(), accessory::Error>>>,
     },
+    ReadCharacteristics {
+        reads: Vec<CharacteristicRead>,
+        respond_to:
+            oneshot::Sender<oneshot::Receiver<Vec<Result<Characteristic, accessory::Error>>>>,
+    },
+    WriteCharacteristics {
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<oneshot::Receiver<Vec<Result<(), accessory::Error>>>>,
+    },
+    Identify {
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<oneshot::Receiver<Result<(), accessory::Error>>>,
+    },
 }
 
+/// Requests sent to the peer which are awaiting a response, by the ID of the request frame
+type Pending<T> = HashMap<lighthouse::FrameID, oneshot::Sender<T>>;
+
 pub struct LighthouseSession {
     session: ezsockets::Session<hub::ID, SessionMessage>,
+    server: Server,
     hub_id: hub::ID,
     controller: controllers::MasterHandle,
-    connected_accessories: HashSet<accessory::ID>,
     characteristic_write_results:
         HashMap<lighthouse::FrameID, oneshot::Sender<Result<(), accessory::Error>>>,
     characteristic_read_results: HashMap<
         lighthouse::FrameID,
         oneshot::Sender<Result<accessory::characteristics::Characteristic, accessory::Error>>,
     >,
+    batch_write_results:
+        HashMap<lighthouse::FrameID, oneshot::Sender<Vec<Result<(), accessory::Error>>>>,
+    batch_read_results: HashMap<
+        lighthouse::FrameID,
+        oneshot::Sender<Vec<Result<accessory::characteristics::Characteristic, accessory::Error>>>,
+    >,
+    identify_results: HashMap<lighthouse::FrameID, oneshot::Sender<Result<(), accessory::Error>>>,
+    /// Handshake sent by the hub, `None` for legacy hubs which don't send it
+    peer: Option<protocol::Hello>,
+    /// Encoding negotiated during the handshake, JSON until then
+    encoding: protocol::Encoding,
 }
 
 impl LighthouseSession {
-    async fn send(&mut self, message: lighthouse::ServerFrame) -> Result<(), ezsockets::Error> {
-        let json = serde_json::to_string(&message)?;
-        self.session.text(json).await;
+    async fn send(&mut self, message: lighthouse::ServerFrame) -> Result<(), protocol::SendError> {
+        let frame_type = lighthouse::ServerFrameType::from(&message).to_string();
+        if let Some(peer) = &self.peer {
+            if !peer.supports(&frame_type) {
+                return Err(protocol::SendError::UnsupportedFrame(frame_type));
+            }
+        }
+        match self.encoding.encode(&message)? {
+            protocol::Payload::Text(text) => self.session.text(text).await,
+            protocol::Payload::Binary(bytes) => self.session.binary(bytes).await,
+        }
         Ok(())
     }
-}
 
-#[async_trait]
-impl ezsockets::SessionExt for LighthouseSession {
-    type ID = hub::ID;
-    type Args = (hub::ID, hub::PasswordHash);
-    type Params = SessionMessage;
+    /// Sends a frame that doesn't expect a response, frames not supported by the hub are dropped.
+    async fn notify(&mut self, message: lighthouse::ServerFrame) -> Result<(), ezsockets::Error> {
+        match self.send(message).await {
+            Err(protocol::SendError::UnsupportedFrame(frame_type)) => {
+                tracing::warn!(hub_id = %self.hub_id, "hub does not support `{}` frames, dropping", frame_type);
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
 
-    fn id(&self) -> &Self::ID {
-        &self.hub_id
+    /// Sends a request frame and stores `respond_to` in `pending` until the hub responds.
+    ///
+    /// If the hub does not support the frame, `respond_to` is answered right away with the error mapped by `unsupported`.
+    async fn request<T>(
+        &mut self,
+        pending: fn(&mut Self) -> &mut Pending<T>,
+        id: lighthouse::FrameID,
+        message: lighthouse::ServerFrame,
+        respond_to: oneshot::Sender<T>,
+        unsupported: impl FnOnce(accessory::Error) -> T,
+    ) -> Result<(), ezsockets::Error> {
+        pending(self).insert(id, respond_to);
+        match self.send(message).await {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                let respond_to = pending(self).remove(&id).unwrap();
+                match err {
+                    protocol::SendError::UnsupportedFrame(frame_type) => {
+                        tracing::warn!(hub_id = %self.hub_id, "hub does not support `{}` frames", frame_type);
+                        let _ = respond_to.send(unsupported(accessory::Error::RequestNotSupported));
+                        Ok(())
+                    }
+                    err => Err(err.into()),
+                }
+            }
+        }
     }
 
-    async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
-        let frame = serde_json::from_str::<lighthouse::HubFrame>(&text)?;
+    /// Answers the pending request with the result sent by the hub.
+    ///
+    /// Results of unknown requests, e.g. duplicated ones, are dropped and reported back to the hub.
+    async fn respond<T>(
+        &mut self,
+        pending: fn(&mut Self) -> &mut Pending<T>,
+        id: lighthouse::FrameID,
+        result: T,
+    ) -> Result<(), ezsockets::Error> {
+        match pending(self).remove(&id) {
+            Some(respond_to) => {
+                // the requester might have stopped waiting
+                let _ = respond_to.send(result);
+                Ok(())
+            }
+            None => {
+                tracing::warn!(hub_id = %self.hub_id, "received result of unknown request {}, ignoring", id);
+                self.notify(lighthouse::ServerFrame::Error(
+                    protocol::Error::MalformedFrame(format!("unknown request id {}", id)),
+                ))
+                .await
+            }
+        }
+    }
+
+    /// Sends a batch request as a single batch frame, or as one frame per item to hubs which don't support `batch_frame_type`.
+    ///
+    /// Returns the receiver of the results, in the order of the items.
+    #[allow(clippy::too_many_arguments)]
+    async fn batch_request<T, R: std::fmt::Debug + Clone + Send + 'static>(
+        &mut self,
+        items: Vec<T>,
+        batch_frame_type: lighthouse::ServerFrameType,
+        batch_frame: impl FnOnce(lighthouse::FrameID, Vec<T>) -> lighthouse::ServerFrame,
+        single_frame: impl Fn(lighthouse::FrameID, T) -> lighthouse::ServerFrame,
+        pending_batch: fn(&mut Self) -> &mut Pending<routing::Results<R>>,
+        pending_single: fn(&mut Self) -> &mut Pending<Result<R, accessory::Error>>,
+    ) -> Result<oneshot::Receiver<routing::Results<R>>, ezsockets::Error> {
+        let (sender, receiver) = oneshot::channel();
+        if items.is_empty() {
+            sender.send(Vec::new()).unwrap();
+        } else if self.peer_supports(batch_frame_type) {
+            let id = rand::random();
+            let len = items.len();
+            let frame = batch_frame(id, items);
+            self.request(pending_batch, id, frame, sender, |err| vec![Err(err); len])
+                .await?;
+        } else {
+            let mut receivers = Vec::with_capacity(items.len());
+            for item in items {
+                let id = rand::random();
+                let (sender, receiver) = oneshot::channel();
+                self.request(pending_single, id, single_frame(id, item), sender, Err)
+                    .await?;
+                receivers.push(receiver);
+            }
+            tokio::spawn(async move {
+                sender.send(routing::join(receivers).await).unwrap();
+            });
+        }
+        Ok(receiver)
+    }
+
+    /// Returns true if the hub declared support for the frame type, legacy hubs support only single reads and writes.
+    fn peer_supports(&self, frame_type: lighthouse::ServerFrameType) -> bool {
+        self.peer
+            .as_ref()
+            .is_some_and(|peer| peer.supports(&frame_type.to_string()))
+    }
+
+    async fn handshake(&mut self, peer: protocol::Hello) -> Result<(), ezsockets::Error> {
+        let mut hello = protocol::hello!(
+            lighthouse::PROTOCOL_VERSION,
+            lighthouse::MIN_PROTOCOL_VERSION,
+            lighthouse::HubFrameType::VARIANTS,
+        );
+        match hello.negotiate(&peer) {
+            Ok(version) => {
+                let encoding = hello.negotiate_encoding(&peer);
+                tracing::info!(hub_id = %self.hub_id, software_version = %peer.software_version, %encoding, "negotiated lighthouse protocol version {}", version);
+                hello.protocol_version = version;
+                hello.encodings = vec![encoding];
+                self.peer = Some(peer);
+                self.notify(lighthouse::ServerFrame::Hello(hello)).await?;
+                self.encoding = encoding;
+                Ok(())
+            }
+            Err(err) => {
+                tracing::error!(hub_id = %self.hub_id, software_version = %peer.software_version, "rejecting hub: {}", err);
+                self.notify(lighthouse::ServerFrame::Error(err.clone()))
+                    .await?;
+                Err(err.into())
+            }
+        }
+    }
+
+    async fn frame(
+        &mut self,
+        frame: Result<lighthouse::HubFrame, protocol::Error>,
+    ) -> Result<(), ezsockets::Error> {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                tracing::warn!(hub_id = %self.hub_id, "received invalid frame: {}", err);
+                return self.notify(lighthouse::ServerFrame::Error(err)).await;
+            }
+        };
         match frame {
-            lighthouse::HubFrame::AccessoryConnected(accessory) => {
-                self.connected_accessories.insert(accessory.id);
+            lighthouse::HubFrame::Hello(hello) => self.handshake(hello).await?,
+            lighthouse::HubFrame::Error(err) => {
+                tracing::warn!(hub_id = %self.hub_id, "hub responded with error: {}", err);
+            }
+            lighthouse::HubFrame::AccessoryConnected(lighthouse::AccessoryConnected {
+                accessory,
+                capabilities,
+            }) => {
+                let capabilities = capabilities.unwrap_or_else(|| accessory.r#type.capabilities());
+                self.server
+                    .call(LighthouseProviderMessage::AccessoryConnected {
+                        hub_id: self.hub_id,
+                        accessory_id: accessory.id,
+                        capabilities,
+                    })
+                    .await;
                 self.controller.connected(accessory).await;
             }
-            lighthouse::HubFrame::AccessoryDisconnected(accessory_id) => {
-                self.connected_accessories.remove(&accessory_id);
+            lighthouse::HubFrame::AccessoryDisconnected(lighthouse::AccessoryDisconnected {
+                accessory_id,
+            }) => {
+                self.server
+                    .call(LighthouseProviderMessage::AccessoryDisconnected { accessory_id })
+                    .await;
                 self.controller.disconnected(accessory_id).await;
             }
             lighthouse::HubFrame::UpdateCharacteristic(frame) => {
@@ -352,43 +757,72 @@ impl ezsockets::SessionExt for LighthouseSession {
                     .await;
             }
             lighthouse::HubFrame::ReadCharacteristicResult(frame) => {
-                self.characteristic_read_results
-                    .remove(&frame.id)
-                    .unwrap()
-                    .send(frame.result.into())
-                    .unwrap();
+                self.respond(
+                    |session| &mut session.characteristic_read_results,
+                    frame.id,
+                    frame.result.into(),
+                )
+                .await?;
             }
             lighthouse::HubFrame::WriteCharacteristicResult(frame) => {
-                self.characteristic_write_results
-                    .remove(&frame.id)
-                    .unwrap()
-                    .send(frame.result.into())
-                    .unwrap();
+                self.respond(
+                    |session| &mut session.characteristic_write_results,
+                    frame.id,
+                    frame.result.into(),
+                )
+                .await?;
+            }
+            lighthouse::HubFrame::BatchReadCharacteristicResult(frame) => {
+                self.respond(
+                    |session| &mut session.batch_read_results,
+                    frame.id,
+                    frame.results.into_iter().map(Into::into).collect(),
+                )
+                .await?;
+            }
+            lighthouse::HubFrame::BatchWriteCharacteristicResult(frame) => {
+                self.respond(
+                    |session| &mut session.batch_write_results,
+                    frame.id,
+                    frame.results.into_iter().map(Into::into).collect(),
+                )
+                .await?;
+            }
+            lighthouse::HubFrame::IdentifyResult(frame) => {
+                self.respond(
+                    |session| &mut session.identify_results,
+                    frame.id,
+                    frame.result.into(),
+                )
+                .await?;
             }
         };
         Ok(())
     }
+}
+
+#[async_trait]
+impl ezsockets::SessionExt for LighthouseSession {
+    type ID = hub::ID;
+    type Args = hub::ID;
+    type Params = SessionMessage;
+
+    fn id(&self) -> &Self::ID {
+        &self.hub_id
+    }
+
+    async fn text(&mut self, text: String) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode(&text, lighthouse::HubFrameType::VARIANTS);
+        self.frame(frame).await
+    }
 
-    async fn binary(&mut self, _: Vec<u8>) -> Result<(), ezsockets::Error> {
-        todo!()
+    async fn binary(&mut self, bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
+        let frame = protocol::decode_binary(&bytes, lighthouse::HubFrameType::VARIANTS);
+        self.frame(frame).await
     }
 
     async fn call(&mut self, params: Self::Params) -> Result<(), ezsockets::Error> {
         match params {
-            SessionMessage::GetAccessories { respond_to } => respond_to
-                .send(
-                    self.connected_accessories
-                        .clone()
-                        .into_iter()
-                        .collect::<Vec<_>>(),
-                )
-                .unwrap(),
-            SessionMessage::IsAccessoryConnected {
-                accessory_id,
-                respond_to,
-            } => respond_to
-                .send(self.connected_accessories.contains(&accessory_id))
-                .unwrap(),
             SessionMessage::ReadCharacteristic {
                 accessory_id,
                 service_name,
@@ -397,15 +831,18 @@ impl ezsockets::SessionExt for LighthouseSession {
             } => {
                 let id = rand::random();
                 let (sender, receiver) = oneshot::channel();
-                self.characteristic_read_results.insert(id, sender);
-                self.send(lighthouse::ServerFrame::ReadCharacteristic(
-                    lighthouse::ReadCharacteristic {
+                self.request(
+                    |session| &mut session.characteristic_read_results,
+                    id,
+                    lighthouse::ServerFrame::ReadCharacteristic(lighthouse::ReadCharacteristic {
                         id,
                         accessory_id,
                         service_name,
                         characteristic_name,
-                    },
-                ))
+                    }),
+                    sender,
+                    Err,
+                )
                 .await?;
                 respond_to.send(receiver).unwrap();
             }
@@ -417,19 +854,253 @@ impl ezsockets::SessionExt for LighthouseSession {
             } => {
                 let id = rand::random();
                 let (sender, receiver) = oneshot::channel();
-                self.characteristic_write_results.insert(id, sender);
-                self.send(lighthouse::ServerFrame::WriteCharacteristic(
-                    lighthouse::WriteCharacteristic {
+                self.request(
+                    |session| &mut session.characteristic_write_results,
+                    id,
+                    lighthouse::ServerFrame::WriteCharacteristic(lighthouse::WriteCharacteristic {
                         id,
                         accessory_id,
                         service_name,
                         characteristic,
-                    },
-                ))
+                    }),
+                    sender,
+                    Err,
+                )
                 .await?;
                 respond_to.send(receiver).unwrap();
             }
+            SessionMessage::ReadCharacteristics { reads, respond_to } => {
+                let receiver = self
+                    .batch_request(
+                        reads,
+                        lighthouse::ServerFrameType::BatchReadCharacteristic,
+                        |id, reads| {
+                            lighthouse::ServerFrame::BatchReadCharacteristic(
+                                lighthouse::BatchReadCharacteristic { id, reads },
+                            )
+                        },
+                        |id, read| {
+                            lighthouse::ServerFrame::ReadCharacteristic(
+                                lighthouse::ReadCharacteristic {
+                                    id,
+                                    accessory_id: read.accessory_id,
+                                    service_name: read.service_name,
+                                    characteristic_name: read.characteristic_name,
+                                },
+                            )
+                        },
+                        |session| &mut session.batch_read_results,
+                        |session| &mut session.characteristic_read_results,
+                    )
+                    .await?;
+                respond_to.send(receiver).unwrap();
+            }
+            SessionMessage::WriteCharacteristics { writes, respond_to } => {
+                let receiver = self
+                    .batch_request(
+                        writes,
+                        lighthouse::ServerFrameType::BatchWriteCharacteristic,
+                        |id, writes| {
+                            lighthouse::ServerFrame::BatchWriteCharacteristic(
+                                lighthouse::BatchWriteCharacteristic { id, writes },
+                            )
+                        },
+                        |id, write| {
+                            lighthouse::ServerFrame::WriteCharacteristic(
+                                lighthouse::WriteCharacteristic {
+                                    id,
+                                    accessory_id: write.accessory_id,
+                                    service_name: write.service_name,
+                                    characteristic: write.characteristic,
+                                },
+                            )
+                        },
+                        |session| &mut session.batch_write_results,
+                        |session| &mut session.characteristic_write_results,
+                    )
+                    .await?;
+                respond_to.send(receiver).unwrap();
+            }
+            SessionMessage::Identify {
+                accessory_id,
+                respond_to,
+            } => {
+                let (sender, receiver) = oneshot::channel();
+                if self.peer_supports(lighthouse::ServerFrameType::Identify) {
+                    let id = rand::random();
+                    self.request(
+                        |session| &mut session.identify_results,
+                        id,
+                        lighthouse::ServerFrame::Identify(lighthouse::Identify {
+                            id,
+                            accessory_id,
+                        }),
+                        sender,
+                        Err,
+                    )
+                    .await?;
+                } else {
+                    // hubs older than protocol version 5 can't forward it to the accessory
+                    sender
+                        .send(Err(accessory::Error::RequestNotSupported))
+                        .unwrap();
+                }
+                respond_to.send(receiver).unwrap();
+            }
         };
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use houseflow_config::server::providers;
+    use houseflow_types::structure;
+
+    /// Hub which speaks the first version of the protocol, without the handshake
+    struct LegacyHub {
+        client: ezsockets::Client<Self>,
+        accessory: accessory::Accessory,
+    }
+
+    #[async_trait]
+    impl ezsockets::ClientExt for LegacyHub {
+        type Params = ();
+
+        async fn text(&mut self, _text: String) -> Result<(), ezsockets::Error> {
+            Ok(())
+        }
+
+        async fn binary(&mut self, _bytes: Vec<u8>) -> Result<(), ezsockets::Error> {
+            Ok(())
+        }
+
+        async fn call(&mut self, _params: Self::Params) -> Result<(), ezsockets::Error> {
+            Ok(())
+        }
+
+        async fn connected(&mut self) -> Result<(), ezsockets::Error> {
+            let frame = lighthouse::HubFrame::AccessoryConnected(lighthouse::AccessoryConnected {
+                accessory: self.accessory.clone(),
+                capabilities: None,
+            });
+            self.client.text(serde_json::to_string(&frame)?).await;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn password_hash() {
+        let hash = hash_password("hub-password");
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ne!(hash, hash_password("hub-password"), "hashes must be salted");
+        assert_eq!(
+            verify_password("hub-password", &hash),
+            PasswordVerification::Valid
+        );
+        assert_eq!(
+            verify_password("other-password", &hash),
+            PasswordVerification::Invalid
+        );
+
+        let legacy_hash = hex::encode(Sha256::digest(b"hub-password"));
+        assert_eq!(
+            verify_password("hub-password", &legacy_hash),
+            PasswordVerification::Legacy
+        );
+        assert_eq!(
+            verify_password("other-password", &legacy_hash),
+            PasswordVerification::Invalid
+        );
+    }
+
+    #[tokio::test]
+    async fn legacy_hub() {
+        let structure = structure::Structure {
+            id: structure::ID::new_v4(),
+            name: String::from("Zukago"),
+        };
+        let hub_id = hub::ID::new_v4();
+        let config = get_config(GetConfig {
+            structures: vec![structure.clone()],
+            ..Default::default()
+        })
+        .await;
+        let mut updated = (**config.get()).clone();
+        updated.providers.lighthouse = Some(providers::Lighthouse {
+            hubs: vec![LighthouseHub {
+                id: hub_id,
+                name: String::from("Hub"),
+                // hash written by an older version, replaced when the hub connects
+                password_hash: hex::encode(Sha256::digest(b"hub-password")),
+                structure_id: structure.id,
+            }],
+        });
+        config.update(updated);
+
+        let server = new(controllers::MasterHandle::new(), config.0.clone());
+        let app = app(server.clone())
+            .layer(config.clone())
+            .layer(Extension(crate::admin::ConfigFile::default()));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = url::Url::parse(&format!(
+            "ws://{}/websocket",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>()),
+        );
+
+        let accessory = accessory::Accessory {
+            id: accessory::ID::new_v4(),
+            name: String::from("Switch"),
+            room_name: String::from("Bedroom"),
+            r#type: accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Switch),
+        };
+        let accessory_id = accessory.id;
+        let (_client, _future) = ezsockets::connect(
+            |client| LegacyHub { client, accessory },
+            ezsockets::ClientConfig::new(url).basic(&hub_id.to_string(), "hub-password"),
+        )
+        .await;
+        let is_connected = || {
+            server.call_with(|respond_to| {
+                LighthouseProviderMessage::Message(Message::IsConnected {
+                    accessory_id,
+                    respond_to,
+                })
+            })
+        };
+        let connected = async {
+            while !is_connected().await {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), connected)
+            .await
+            .expect("hub did not connect");
+
+        let result = server
+            .call_with(|respond_to| {
+                LighthouseProviderMessage::Message(Message::Identify {
+                    accessory_id,
+                    respond_to,
+                })
+            })
+            .await;
+        assert_eq!(result, Err(accessory::Error::RequestNotSupported));
+
+        let password_hash = config.get().providers.lighthouse.as_ref().unwrap().hubs[0]
+            .password_hash
+            .clone();
+        assert_eq!(
+            verify_password("hub-password", &password_hash),
+            PasswordVerification::Valid
+        );
+    }
+}
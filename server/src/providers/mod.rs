@@ -4,10 +4,17 @@ pub mod lighthouse;
 pub use dummy::DummyProvider;
 pub use lighthouse::LighthouseProvider;
 
+use acu::MasterExt;
+use futures::future;
 use houseflow_types::accessory;
+use houseflow_types::accessory::capabilities::Capabilities;
 use houseflow_types::accessory::characteristics::Characteristic;
 use houseflow_types::accessory::characteristics::CharacteristicName;
 use houseflow_types::accessory::services::ServiceName;
+use houseflow_types::accessory::CharacteristicRead;
+use houseflow_types::accessory::CharacteristicWrite;
+use houseflow_types::routing;
+use houseflow_types::structure;
 use tokio::sync::oneshot;
 
 #[derive(Debug, Clone, PartialEq, Eq, strum::Display, strum::IntoStaticStr)]
@@ -37,6 +44,18 @@ pub enum Message {
         characteristic: Characteristic,
         respond_to: oneshot::Sender<Result<(), accessory::Error>>,
     },
+    ReadCharacteristics {
+        reads: Vec<CharacteristicRead>,
+        respond_to: oneshot::Sender<Vec<Result<Characteristic, accessory::Error>>>,
+    },
+    WriteCharacteristics {
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<Vec<Result<(), accessory::Error>>>,
+    },
+    Identify {
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Result<(), accessory::Error>>,
+    },
     GetAccessories {
         respond_to: oneshot::Sender<Vec<accessory::ID>>,
     },
@@ -44,80 +63,137 @@ pub enum Message {
         accessory_id: accessory::ID,
         respond_to: oneshot::Sender<bool>,
     },
+    GetAccessoryCapabilities {
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Option<Capabilities>>,
+    },
+    GetAccessoryStructure {
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Option<structure::ID>>,
+    },
 }
 
 impl acu::Message for Message {}
 
-use async_trait::async_trait;
+impl routing::ProviderMessage for Message {
+    fn read_characteristic(
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+        respond_to: oneshot::Sender<Result<Characteristic, accessory::Error>>,
+    ) -> Self {
+        Self::ReadCharacteristic {
+            accessory_id,
+            service_name,
+            characteristic_name,
+            respond_to,
+        }
+    }
 
-#[async_trait]
-pub trait ProviderExt {
-    async fn write_characteristic(
-        &self,
+    fn write_characteristic(
         accessory_id: accessory::ID,
         service_name: ServiceName,
         characteristic: Characteristic,
-    ) -> Result<(), accessory::Error>;
-    async fn read_characteristic(
-        &self,
+        respond_to: oneshot::Sender<Result<(), accessory::Error>>,
+    ) -> Self {
+        Self::WriteCharacteristic {
+            accessory_id,
+            service_name,
+            characteristic,
+            respond_to,
+        }
+    }
+
+    fn read_characteristics(
+        reads: Vec<CharacteristicRead>,
+        respond_to: oneshot::Sender<Vec<Result<Characteristic, accessory::Error>>>,
+    ) -> Self {
+        Self::ReadCharacteristics { reads, respond_to }
+    }
+
+    fn write_characteristics(
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<Vec<Result<(), accessory::Error>>>,
+    ) -> Self {
+        Self::WriteCharacteristics { writes, respond_to }
+    }
+
+    fn identify(
         accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic_name: CharacteristicName,
-    ) -> Result<Characteristic, accessory::Error>;
+        respond_to: oneshot::Sender<Result<(), accessory::Error>>,
+    ) -> Self {
+        Self::Identify {
+            accessory_id,
+            respond_to,
+        }
+    }
+
+    fn is_connected(accessory_id: accessory::ID, respond_to: oneshot::Sender<bool>) -> Self {
+        Self::IsConnected {
+            accessory_id,
+            respond_to,
+        }
+    }
+
+    fn get_accessory_capabilities(
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Option<Capabilities>>,
+    ) -> Self {
+        Self::GetAccessoryCapabilities {
+            accessory_id,
+            respond_to,
+        }
+    }
+}
+
+use async_trait::async_trait;
+
+pub use routing::Provider;
+
+#[async_trait]
+pub trait ProviderExt: Provider {
     async fn get_accessories(&self) -> Vec<accessory::ID>;
-    async fn is_connected(&self, accessory_id: accessory::ID) -> bool;
+    /// Returns the structure that a connected accessory belongs to
+    async fn get_accessory_structure(&self, accessory_id: accessory::ID) -> Option<structure::ID>;
 }
 
 pub type Handle = acu::Handle<Message, Name>;
 
+/// Handle to all of the providers, see `routing::Master`.
+pub type MasterHandle = routing::Master<Message, Name>;
+
 #[async_trait]
 impl ProviderExt for Handle {
-    async fn write_characteristic(
-        &self,
-        accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic: Characteristic,
-    ) -> Result<(), accessory::Error> {
+    async fn get_accessories(&self) -> Vec<accessory::ID> {
         self.sender
-            .call_with(|respond_to| Message::WriteCharacteristic {
-                accessory_id,
-                service_name,
-                characteristic,
-                respond_to,
-            })
+            .call_with(|respond_to| Message::GetAccessories { respond_to })
             .await
     }
 
-    async fn read_characteristic(
-        &self,
-        accessory_id: accessory::ID,
-        service_name: ServiceName,
-        characteristic_name: CharacteristicName,
-    ) -> Result<Characteristic, accessory::Error> {
+    async fn get_accessory_structure(&self, accessory_id: accessory::ID) -> Option<structure::ID> {
         self.sender
-            .call_with(|respond_to| Message::ReadCharacteristic {
+            .call_with(|respond_to| Message::GetAccessoryStructure {
                 accessory_id,
-                service_name,
-                characteristic_name,
                 respond_to,
             })
             .await
     }
+}
 
+#[async_trait]
+impl ProviderExt for MasterHandle {
     async fn get_accessories(&self) -> Vec<accessory::ID> {
-        self.sender
-            .call_with(|respond_to| Message::GetAccessories { respond_to })
+        let slaves = self.slaves().await;
+        let futures = slaves.iter().map(|handle| handle.get_accessories());
+        future::join_all(futures)
             .await
+            .into_iter()
+            .flatten()
+            .collect()
     }
 
-    async fn is_connected(&self, accessory_id: accessory::ID) -> bool {
-        self.sender
-            .call_with(|respond_to| Message::IsConnected {
-                accessory_id,
-                respond_to,
-            })
-            .await
+    async fn get_accessory_structure(&self, accessory_id: accessory::ID) -> Option<structure::ID> {
+        let slave = self.route(accessory_id).await?;
+        slave.get_accessory_structure(accessory_id).await
     }
 }
-
-pub type MasterHandle = acu::MasterHandle<Message, Name>;
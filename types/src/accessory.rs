@@ -22,30 +22,385 @@ pub struct Accessory {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "manufacturer", rename_all = "kebab-case")]
-#[non_exhaustive]
 pub enum Type {
     XiaomiMijia(manufacturers::XiaomiMijia),
     Houseflow(manufacturers::Houseflow),
 }
 
+impl Type {
+    /// Services and characteristics supported by the accessory type.
+    ///
+    /// Hive accessories may override them during the handshake.
+    pub fn capabilities(&self) -> capabilities::Capabilities {
+        use capabilities::CharacteristicCapability as Characteristic;
+        use capabilities::Permissions;
+        use capabilities::ServiceCapability as Service;
+        use capabilities::Unit;
+        use characteristics::CharacteristicName;
+        use services::ServiceName;
+
+        let binary_sensor = |service_name: ServiceName| {
+            let detected = service_name
+                .detected(false)
+                .map(|characteristic| CharacteristicName::from(&characteristic))
+                .unwrap();
+            Service::new(
+                service_name,
+                vec![
+                    Characteristic::new(detected, Permissions::READ_NOTIFY),
+                    Characteristic::new(
+                        CharacteristicName::StatusTampered,
+                        Permissions::READ_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::StatusLowBattery,
+                        Permissions::READ_NOTIFY,
+                    ),
+                ],
+            )
+        };
+
+        let energy = || {
+            vec![
+                Characteristic::new(
+                    CharacteristicName::PowerConsumption,
+                    Permissions::READ_NOTIFY,
+                )
+                .with_unit(Unit::Watts),
+                Characteristic::new(CharacteristicName::Voltage, Permissions::READ_NOTIFY)
+                    .with_unit(Unit::Volts),
+                Characteristic::new(
+                    CharacteristicName::ElectricCurrent,
+                    Permissions::READ_NOTIFY,
+                )
+                .with_unit(Unit::Amperes),
+                Characteristic::new(
+                    CharacteristicName::EnergyConsumption,
+                    Permissions::READ_NOTIFY,
+                )
+                .with_unit(Unit::KilowattHours),
+            ]
+        };
+
+        let services = match self {
+            Self::XiaomiMijia(manufacturers::XiaomiMijia::HygroThermometer) => vec![
+                Service::new(
+                    ServiceName::TemperatureSensor,
+                    vec![Characteristic::new(
+                        CharacteristicName::CurrentTemperature,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(-40.0, 60.0)
+                    .with_unit(Unit::Celsius)],
+                ),
+                Service::new(
+                    ServiceName::HumiditySensor,
+                    vec![Characteristic::new(
+                        CharacteristicName::CurrentHumidity,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage)],
+                ),
+                Service::new(
+                    ServiceName::Battery,
+                    vec![Characteristic::new(
+                        CharacteristicName::BatteryLevel,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage)],
+                ),
+            ],
+            Self::Houseflow(manufacturers::Houseflow::Gate)
+            | Self::Houseflow(manufacturers::Houseflow::Garage) => vec![Service::new(
+                ServiceName::GarageDoorOpener,
+                vec![
+                    Characteristic::new(
+                        CharacteristicName::CurrentDoorState,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                    Characteristic::new(
+                        CharacteristicName::TargetDoorState,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Thermostat) => vec![Service::new(
+                ServiceName::Thermostat,
+                vec![
+                    Characteristic::new(
+                        CharacteristicName::CurrentTemperature,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Celsius),
+                    Characteristic::new(
+                        CharacteristicName::TargetTemperature,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(10.0, 38.0)
+                    .with_unit(Unit::Celsius),
+                    Characteristic::new(
+                        CharacteristicName::CurrentHeatingCoolingState,
+                        Permissions::READ_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::TargetHeatingCoolingState,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::TemperatureDisplayUnits,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Lock) => vec![Service::new(
+                ServiceName::LockMechanism,
+                vec![
+                    Characteristic::new(
+                        CharacteristicName::CurrentLockState,
+                        Permissions::READ_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::TargetLockState,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::ContactSensor) => {
+                vec![binary_sensor(ServiceName::ContactSensor)]
+            }
+            Self::Houseflow(manufacturers::Houseflow::MotionSensor) => {
+                vec![binary_sensor(ServiceName::MotionSensor)]
+            }
+            Self::Houseflow(manufacturers::Houseflow::OccupancySensor) => {
+                vec![binary_sensor(ServiceName::OccupancySensor)]
+            }
+            Self::Houseflow(manufacturers::Houseflow::LeakSensor) => {
+                vec![binary_sensor(ServiceName::LeakSensor)]
+            }
+            Self::Houseflow(manufacturers::Houseflow::SmokeSensor) => {
+                vec![binary_sensor(ServiceName::SmokeSensor)]
+            }
+            Self::Houseflow(manufacturers::Houseflow::AirQualitySensor) => vec![Service::new(
+                ServiceName::AirQualitySensor,
+                vec![
+                    Characteristic::new(CharacteristicName::AirQuality, Permissions::READ_NOTIFY),
+                    Characteristic::new(
+                        CharacteristicName::CarbonDioxideLevel,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100000.0)
+                    .with_unit(Unit::PartsPerMillion),
+                    Characteristic::new(CharacteristicName::Pm25Density, Permissions::READ_NOTIFY)
+                        .with_range(0.0, 1000.0)
+                        .with_unit(Unit::MicrogramsPerCubicMeter),
+                    Characteristic::new(CharacteristicName::Pm10Density, Permissions::READ_NOTIFY)
+                        .with_range(0.0, 1000.0)
+                        .with_unit(Unit::MicrogramsPerCubicMeter),
+                    Characteristic::new(CharacteristicName::VocDensity, Permissions::READ_NOTIFY)
+                        .with_range(0.0, 1000.0)
+                        .with_unit(Unit::MicrogramsPerCubicMeter),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Fan) => vec![Service::new(
+                ServiceName::Fan,
+                vec![
+                    Characteristic::new(CharacteristicName::Active, Permissions::READ_WRITE_NOTIFY),
+                    Characteristic::new(
+                        CharacteristicName::RotationSpeed,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                    Characteristic::new(
+                        CharacteristicName::RotationDirection,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::SwingMode,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::AirPurifier) => vec![Service::new(
+                ServiceName::AirPurifier,
+                vec![
+                    Characteristic::new(CharacteristicName::Active, Permissions::READ_WRITE_NOTIFY),
+                    Characteristic::new(
+                        CharacteristicName::RotationSpeed,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                    Characteristic::new(
+                        CharacteristicName::SwingMode,
+                        Permissions::READ_WRITE_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::FilterLifeLevel,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                    Characteristic::new(
+                        CharacteristicName::FilterChangeIndication,
+                        Permissions::READ_NOTIFY,
+                    ),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Blinds) => vec![Service::new(
+                ServiceName::WindowCovering,
+                vec![
+                    Characteristic::new(
+                        CharacteristicName::CurrentPosition,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                    Characteristic::new(
+                        CharacteristicName::TargetPosition,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(0.0, 100.0)
+                    .with_unit(Unit::Percentage),
+                    Characteristic::new(
+                        CharacteristicName::PositionState,
+                        Permissions::READ_NOTIFY,
+                    ),
+                    Characteristic::new(
+                        CharacteristicName::CurrentTiltAngle,
+                        Permissions::READ_NOTIFY,
+                    )
+                    .with_range(-90.0, 90.0)
+                    .with_unit(Unit::ArcDegrees),
+                    Characteristic::new(
+                        CharacteristicName::TargetTiltAngle,
+                        Permissions::READ_WRITE_NOTIFY,
+                    )
+                    .with_range(-90.0, 90.0)
+                    .with_unit(Unit::ArcDegrees),
+                    Characteristic::new(CharacteristicName::HoldPosition, Permissions::WRITE),
+                ],
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Switch) => vec![Service::new(
+                ServiceName::Switch,
+                [Characteristic::new(
+                    CharacteristicName::On,
+                    Permissions::READ_WRITE_NOTIFY,
+                )]
+                .into_iter()
+                .chain(energy())
+                .collect(),
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Outlet) => vec![Service::new(
+                ServiceName::Outlet,
+                [
+                    Characteristic::new(CharacteristicName::On, Permissions::READ_WRITE_NOTIFY),
+                    Characteristic::new(CharacteristicName::OutletInUse, Permissions::READ_NOTIFY),
+                ]
+                .into_iter()
+                .chain(energy())
+                .collect(),
+            )],
+            Self::Houseflow(manufacturers::Houseflow::Lightbulb {
+                brightness,
+                color,
+                color_temperature,
+            }) => {
+                let mut characteristics = vec![Characteristic::new(
+                    CharacteristicName::On,
+                    Permissions::READ_WRITE_NOTIFY,
+                )];
+                if *brightness {
+                    characteristics.push(
+                        Characteristic::new(
+                            CharacteristicName::Brightness,
+                            Permissions::READ_WRITE_NOTIFY,
+                        )
+                        .with_range(0.0, 100.0)
+                        .with_unit(Unit::Percentage),
+                    );
+                }
+                if *color {
+                    characteristics.push(
+                        Characteristic::new(
+                            CharacteristicName::Hue,
+                            Permissions::READ_WRITE_NOTIFY,
+                        )
+                        .with_range(0.0, 360.0)
+                        .with_unit(Unit::ArcDegrees),
+                    );
+                    characteristics.push(
+                        Characteristic::new(
+                            CharacteristicName::Saturation,
+                            Permissions::READ_WRITE_NOTIFY,
+                        )
+                        .with_range(0.0, 100.0)
+                        .with_unit(Unit::Percentage),
+                    );
+                }
+                if *color_temperature {
+                    characteristics.push(
+                        Characteristic::new(
+                            CharacteristicName::ColorTemperature,
+                            Permissions::READ_WRITE_NOTIFY,
+                        )
+                        .with_range(140.0, 500.0)
+                        .with_unit(Unit::Mireds),
+                    );
+                }
+                vec![Service::new(ServiceName::Light, characteristics)]
+            }
+        };
+        capabilities::Capabilities { services }
+    }
+}
+
 pub mod manufacturers {
     use serde::Deserialize;
     use serde::Serialize;
 
     #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
     #[serde(tag = "model", rename_all = "kebab-case")]
-    #[non_exhaustive]
     pub enum XiaomiMijia {
         HygroThermometer,
     }
 
     #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
     #[serde(tag = "model", rename_all = "kebab-case")]
-    #[non_exhaustive]
     pub enum Houseflow {
         Gate,
         Garage,
-        Lightbulb,
+        /// Light which can be turned on and off, brightness and color have to be enabled if the light supports them
+        #[serde(rename_all = "kebab-case")]
+        Lightbulb {
+            #[serde(default)]
+            brightness: bool,
+            /// Color set by hue and saturation
+            #[serde(default)]
+            color: bool,
+            #[serde(default)]
+            color_temperature: bool,
+        },
+        Thermostat,
+        Lock,
+        ContactSensor,
+        MotionSensor,
+        OccupancySensor,
+        LeakSensor,
+        SmokeSensor,
+        Blinds,
+        Switch,
+        Outlet,
+        AirQualitySensor,
+        Fan,
+        AirPurifier,
     }
 }
 
@@ -66,6 +421,15 @@ pub enum Error {
     /// Accessory does not support the specified service
     #[error("service is not supported")]
     ServiceNotSupported,
+    /// Value of the characteristic is outside of the range declared in the capabilities
+    #[error("value is out of range")]
+    ValueOutOfRange,
+    /// Accessory, or the hub it is connected through, is not able to receive the request
+    #[error("request is not supported")]
+    RequestNotSupported,
+    /// Accessory failed to carry out the request, e.g. its hardware or a command it runs failed
+    #[error("accessory failed")]
+    Failed,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, strum::Display)]
@@ -98,6 +462,234 @@ impl<T> From<Result<T>> for std::result::Result<T, Error> {
     }
 }
 
+/// Single read of a batch read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CharacteristicRead {
+    pub accessory_id: ID,
+    pub service_name: services::ServiceName,
+    pub characteristic_name: characteristics::CharacteristicName,
+}
+
+/// Single write of a batch write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CharacteristicWrite {
+    pub accessory_id: ID,
+    pub service_name: services::ServiceName,
+    pub characteristic: characteristics::Characteristic,
+}
+
+pub mod capabilities {
+    use super::characteristics;
+    use super::characteristics::CharacteristicName;
+    use super::characteristics::TemperatureUnits;
+    use super::services::ServiceName;
+    use super::Error;
+    use crate::user;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    /// Services and characteristics supported by an accessory
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Capabilities {
+        pub services: Vec<ServiceCapability>,
+    }
+
+    impl Capabilities {
+        pub fn service(&self, service_name: ServiceName) -> Option<&ServiceCapability> {
+            self.services
+                .iter()
+                .find(|service| service.name == service_name)
+        }
+
+        /// Checks whether the characteristic can be written to the service, without sending anything to the accessory.
+        pub fn validate_write(
+            &self,
+            service_name: ServiceName,
+            characteristic: &characteristics::Characteristic,
+        ) -> Result<(), Error> {
+            let service = self
+                .service(service_name)
+                .ok_or(Error::ServiceNotSupported)?;
+            let capability = service
+                .characteristic(CharacteristicName::from(characteristic))
+                .ok_or(Error::CharacteristicNotSupported)?;
+            if !capability.permissions.write {
+                return Err(Error::CharacteristicReadOnly);
+            }
+            match (&capability.range, characteristic.value()) {
+                (Some(range), Some(value)) if !range.contains(value) => Err(Error::ValueOutOfRange),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct ServiceCapability {
+        pub name: ServiceName,
+        pub characteristics: Vec<CharacteristicCapability>,
+    }
+
+    impl ServiceCapability {
+        pub fn new(name: ServiceName, characteristics: Vec<CharacteristicCapability>) -> Self {
+            Self {
+                name,
+                characteristics,
+            }
+        }
+
+        pub fn characteristic(
+            &self,
+            characteristic_name: CharacteristicName,
+        ) -> Option<&CharacteristicCapability> {
+            self.characteristics
+                .iter()
+                .find(|characteristic| characteristic.name == characteristic_name)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct CharacteristicCapability {
+        pub name: CharacteristicName,
+        pub permissions: Permissions,
+        /// Range of the numeric value of the characteristic, `None` for non-numeric characteristics
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub range: Option<Range>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub unit: Option<Unit>,
+    }
+
+    impl CharacteristicCapability {
+        pub fn new(name: CharacteristicName, permissions: Permissions) -> Self {
+            Self {
+                name,
+                permissions,
+                range: None,
+                unit: name.unit(),
+            }
+        }
+
+        pub fn with_range(mut self, min: f32, max: f32) -> Self {
+            self.range = Some(Range {
+                min,
+                max,
+                step: None,
+            });
+            self
+        }
+
+        pub fn with_unit(mut self, unit: Unit) -> Self {
+            self.unit = Some(unit);
+            self
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Permissions {
+        /// Characteristic can be read
+        #[serde(default)]
+        pub read: bool,
+        /// Characteristic can be written
+        #[serde(default)]
+        pub write: bool,
+        /// Accessory sends updates when the characteristic changes
+        #[serde(default)]
+        pub notify: bool,
+    }
+
+    impl Permissions {
+        pub const READ_NOTIFY: Self = Self {
+            read: true,
+            write: false,
+            notify: true,
+        };
+
+        pub const READ_WRITE_NOTIFY: Self = Self {
+            read: true,
+            write: true,
+            notify: true,
+        };
+
+        pub const WRITE: Self = Self {
+            read: false,
+            write: true,
+            notify: false,
+        };
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Range {
+        pub min: f32,
+        pub max: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub step: Option<f32>,
+    }
+
+    impl Range {
+        pub fn contains(&self, value: f32) -> bool {
+            (self.min..=self.max).contains(&value)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum Unit {
+        Celsius,
+        Fahrenheit,
+        Percentage,
+        ArcDegrees,
+        Mireds,
+        Watts,
+        Volts,
+        Amperes,
+        KilowattHours,
+        PartsPerMillion,
+        MicrogramsPerCubicMeter,
+    }
+
+    impl Unit {
+        pub fn symbol(&self) -> &'static str {
+            match self {
+                Self::Celsius => "°C",
+                Self::Fahrenheit => "°F",
+                Self::Percentage => "%",
+                Self::ArcDegrees => "°",
+                Self::Mireds => "mired",
+                Self::Watts => "W",
+                Self::Volts => "V",
+                Self::Amperes => "A",
+                Self::KilowattHours => "kWh",
+                Self::PartsPerMillion => "ppm",
+                Self::MicrogramsPerCubicMeter => "µg/m³",
+            }
+        }
+
+        /// Converts a value from this unit to the one preferred by the user.
+        pub fn localize(self, value: f32, preferences: &user::Preferences) -> (Self, f32) {
+            match self {
+                Self::Celsius
+                    if preferences.temperature_units() == TemperatureUnits::Fahrenheit =>
+                {
+                    (Self::Fahrenheit, value * 9.0 / 5.0 + 32.0)
+                }
+                Self::Fahrenheit
+                    if preferences.temperature_units() == TemperatureUnits::Celsius =>
+                {
+                    (Self::Celsius, (value - 32.0) * 5.0 / 9.0)
+                }
+                _ => (self, value),
+            }
+        }
+    }
+}
+
 pub mod services {
     use super::characteristics;
     use serde::Deserialize;
@@ -133,10 +725,51 @@ pub mod services {
         GarageDoorOpener(GarageDoorOpener),
         Light(Light),
         Battery(Battery),
+        Thermostat(Thermostat),
+        LockMechanism(LockMechanism),
+        ContactSensor(ContactSensor),
+        MotionSensor(MotionSensor),
+        OccupancySensor(OccupancySensor),
+        LeakSensor(LeakSensor),
+        SmokeSensor(SmokeSensor),
+        WindowCovering(WindowCovering),
+        Switch(Switch),
+        Outlet(Outlet),
+        AirQualitySensor(AirQualitySensor),
+        Fan(Fan),
+        AirPurifier(AirPurifier),
     }
 
     impl ServiceName {
         pub const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;
+
+        /// Characteristic reporting whether a binary sensor service detected something, `None` if the service isn't a binary sensor
+        pub fn detected(&self, detected: bool) -> Option<characteristics::Characteristic> {
+            use characteristics::*;
+
+            match self {
+                Self::ContactSensor => {
+                    Some(Characteristic::ContactSensorState(ContactSensorState {
+                        contact_detected: detected,
+                    }))
+                }
+                Self::MotionSensor => Some(Characteristic::MotionDetected(MotionDetected {
+                    motion_detected: detected,
+                })),
+                Self::OccupancySensor => {
+                    Some(Characteristic::OccupancyDetected(OccupancyDetected {
+                        occupancy_detected: detected,
+                    }))
+                }
+                Self::LeakSensor => Some(Characteristic::LeakDetected(LeakDetected {
+                    leak_detected: detected,
+                })),
+                Self::SmokeSensor => Some(Characteristic::SmokeDetected(SmokeDetected {
+                    smoke_detected: detected,
+                })),
+                _ => None,
+            }
+        }
     }
 
     #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -159,15 +792,123 @@ pub mod services {
     pub struct Light {
         pub on: characteristics::On,
         pub brightness: Option<characteristics::Brightness>,
+        pub hue: Option<characteristics::Hue>,
+        pub saturation: Option<characteristics::Saturation>,
+        pub color_temperature: Option<characteristics::ColorTemperature>,
     }
 
     #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     pub struct Battery {
         pub battery_level: characteristics::BatteryLevel,
     }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Thermostat {
+        pub current_temperature: characteristics::CurrentTemperature,
+        pub target_temperature: characteristics::TargetTemperature,
+        pub current_heating_cooling_state: characteristics::CurrentHeatingCoolingState,
+        pub target_heating_cooling_state: characteristics::TargetHeatingCoolingState,
+        pub temperature_display_units: characteristics::TemperatureDisplayUnits,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ContactSensor {
+        pub contact_sensor_state: characteristics::ContactSensorState,
+        pub status_tampered: Option<characteristics::StatusTampered>,
+        pub status_low_battery: Option<characteristics::StatusLowBattery>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct MotionSensor {
+        pub motion_detected: characteristics::MotionDetected,
+        pub status_tampered: Option<characteristics::StatusTampered>,
+        pub status_low_battery: Option<characteristics::StatusLowBattery>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct OccupancySensor {
+        pub occupancy_detected: characteristics::OccupancyDetected,
+        pub status_tampered: Option<characteristics::StatusTampered>,
+        pub status_low_battery: Option<characteristics::StatusLowBattery>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct LeakSensor {
+        pub leak_detected: characteristics::LeakDetected,
+        pub status_tampered: Option<characteristics::StatusTampered>,
+        pub status_low_battery: Option<characteristics::StatusLowBattery>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SmokeSensor {
+        pub smoke_detected: characteristics::SmokeDetected,
+        pub status_tampered: Option<characteristics::StatusTampered>,
+        pub status_low_battery: Option<characteristics::StatusLowBattery>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Switch {
+        pub on: characteristics::On,
+        pub power_consumption: Option<characteristics::PowerConsumption>,
+        pub voltage: Option<characteristics::Voltage>,
+        pub electric_current: Option<characteristics::ElectricCurrent>,
+        pub energy_consumption: Option<characteristics::EnergyConsumption>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Outlet {
+        pub on: characteristics::On,
+        pub outlet_in_use: characteristics::OutletInUse,
+        pub power_consumption: Option<characteristics::PowerConsumption>,
+        pub voltage: Option<characteristics::Voltage>,
+        pub electric_current: Option<characteristics::ElectricCurrent>,
+        pub energy_consumption: Option<characteristics::EnergyConsumption>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AirQualitySensor {
+        pub air_quality: characteristics::AirQuality,
+        pub carbon_dioxide_level: Option<characteristics::CarbonDioxideLevel>,
+        pub pm25_density: Option<characteristics::Pm25Density>,
+        pub pm10_density: Option<characteristics::Pm10Density>,
+        pub voc_density: Option<characteristics::VocDensity>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Fan {
+        pub active: characteristics::Active,
+        pub rotation_speed: Option<characteristics::RotationSpeed>,
+        pub rotation_direction: Option<characteristics::RotationDirection>,
+        pub swing_mode: Option<characteristics::SwingMode>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AirPurifier {
+        pub active: characteristics::Active,
+        pub rotation_speed: Option<characteristics::RotationSpeed>,
+        pub swing_mode: Option<characteristics::SwingMode>,
+        pub filter_life_level: Option<characteristics::FilterLifeLevel>,
+        pub filter_change_indication: Option<characteristics::FilterChangeIndication>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct WindowCovering {
+        pub current_position: characteristics::CurrentPosition,
+        pub target_position: characteristics::TargetPosition,
+        pub position_state: characteristics::PositionState,
+        pub current_tilt_angle: Option<characteristics::CurrentTiltAngle>,
+        pub target_tilt_angle: Option<characteristics::TargetTiltAngle>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct LockMechanism {
+        pub current_lock_state: characteristics::CurrentLockState,
+        pub target_lock_state: characteristics::TargetLockState,
+    }
 }
 
 pub mod characteristics {
+    use super::capabilities::Unit;
     use serde::Deserialize;
     use serde::Serialize;
     use strum::EnumDiscriminants;
@@ -193,10 +934,133 @@ pub mod characteristics {
         TargetDoorState(TargetDoorState),
         BatteryLevel(BatteryLevel),
         ChargingState(ChargingState),
+        TargetTemperature(TargetTemperature),
+        CurrentHeatingCoolingState(CurrentHeatingCoolingState),
+        TargetHeatingCoolingState(TargetHeatingCoolingState),
+        TemperatureDisplayUnits(TemperatureDisplayUnits),
+        Brightness(Brightness),
+        Hue(Hue),
+        Saturation(Saturation),
+        ColorTemperature(ColorTemperature),
+        CurrentLockState(CurrentLockState),
+        TargetLockState(TargetLockState),
+        ContactSensorState(ContactSensorState),
+        MotionDetected(MotionDetected),
+        OccupancyDetected(OccupancyDetected),
+        LeakDetected(LeakDetected),
+        SmokeDetected(SmokeDetected),
+        StatusTampered(StatusTampered),
+        StatusLowBattery(StatusLowBattery),
+        CurrentPosition(CurrentPosition),
+        TargetPosition(TargetPosition),
+        PositionState(PositionState),
+        CurrentTiltAngle(CurrentTiltAngle),
+        TargetTiltAngle(TargetTiltAngle),
+        HoldPosition(HoldPosition),
+        OutletInUse(OutletInUse),
+        PowerConsumption(PowerConsumption),
+        Voltage(Voltage),
+        ElectricCurrent(ElectricCurrent),
+        EnergyConsumption(EnergyConsumption),
+        AirQuality(AirQuality),
+        CarbonDioxideLevel(CarbonDioxideLevel),
+        Pm25Density(Pm25Density),
+        Pm10Density(Pm10Density),
+        VocDensity(VocDensity),
+        Active(Active),
+        RotationSpeed(RotationSpeed),
+        RotationDirection(RotationDirection),
+        SwingMode(SwingMode),
+        FilterLifeLevel(FilterLifeLevel),
+        FilterChangeIndication(FilterChangeIndication),
     }
 
     impl CharacteristicName {
         pub const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;
+
+        /// Canonical unit of the characteristic value, `None` for non-numeric or unitless characteristics
+        pub fn unit(&self) -> Option<Unit> {
+            match self {
+                Self::CurrentTemperature | Self::TargetTemperature => Some(Unit::Celsius),
+                Self::CurrentHumidity
+                | Self::CurrentDoorState
+                | Self::TargetDoorState
+                | Self::BatteryLevel
+                | Self::Brightness
+                | Self::Saturation
+                | Self::CurrentPosition
+                | Self::TargetPosition
+                | Self::RotationSpeed
+                | Self::FilterLifeLevel => Some(Unit::Percentage),
+                Self::Hue | Self::CurrentTiltAngle | Self::TargetTiltAngle => {
+                    Some(Unit::ArcDegrees)
+                }
+                Self::ColorTemperature => Some(Unit::Mireds),
+                Self::PowerConsumption => Some(Unit::Watts),
+                Self::Voltage => Some(Unit::Volts),
+                Self::ElectricCurrent => Some(Unit::Amperes),
+                Self::EnergyConsumption => Some(Unit::KilowattHours),
+                Self::CarbonDioxideLevel => Some(Unit::PartsPerMillion),
+                Self::Pm25Density | Self::Pm10Density | Self::VocDensity => {
+                    Some(Unit::MicrogramsPerCubicMeter)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    impl Characteristic {
+        /// Numeric value of the characteristic in its canonical unit, `None` for non-numeric characteristics
+        pub fn value(&self) -> Option<f32> {
+            match self {
+                Characteristic::CurrentTemperature(v) => Some(v.temperature),
+                Characteristic::CurrentHumidity(v) => Some(v.humidity),
+                Characteristic::CurrentDoorState(v) => Some(v.open_percent.into()),
+                Characteristic::TargetDoorState(v) => Some(v.open_percent.into()),
+                Characteristic::BatteryLevel(v) => Some(v.battery_level_percent.into()),
+                Characteristic::TargetTemperature(v) => Some(v.temperature),
+                Characteristic::Brightness(v) => Some(v.percentage.into()),
+                Characteristic::Hue(v) => Some(v.degrees),
+                Characteristic::Saturation(v) => Some(v.percentage),
+                Characteristic::ColorTemperature(v) => Some(v.mireds.into()),
+                Characteristic::CurrentPosition(v) => Some(v.position.into()),
+                Characteristic::TargetPosition(v) => Some(v.position.into()),
+                Characteristic::CurrentTiltAngle(v) => Some(v.degrees.into()),
+                Characteristic::TargetTiltAngle(v) => Some(v.degrees.into()),
+                Characteristic::PowerConsumption(v) => Some(v.watts),
+                Characteristic::Voltage(v) => Some(v.volts),
+                Characteristic::ElectricCurrent(v) => Some(v.amperes),
+                Characteristic::EnergyConsumption(v) => Some(v.kilowatt_hours),
+                Characteristic::CarbonDioxideLevel(v) => Some(v.ppm),
+                Characteristic::Pm25Density(v) => Some(v.density),
+                Characteristic::Pm10Density(v) => Some(v.density),
+                Characteristic::VocDensity(v) => Some(v.density),
+                Characteristic::RotationSpeed(v) => Some(v.percentage.into()),
+                Characteristic::FilterLifeLevel(v) => Some(v.percentage.into()),
+                Characteristic::On(_)
+                | Characteristic::ChargingState(_)
+                | Characteristic::CurrentHeatingCoolingState(_)
+                | Characteristic::TargetHeatingCoolingState(_)
+                | Characteristic::TemperatureDisplayUnits(_)
+                | Characteristic::CurrentLockState(_)
+                | Characteristic::TargetLockState(_)
+                | Characteristic::ContactSensorState(_)
+                | Characteristic::MotionDetected(_)
+                | Characteristic::OccupancyDetected(_)
+                | Characteristic::LeakDetected(_)
+                | Characteristic::SmokeDetected(_)
+                | Characteristic::StatusTampered(_)
+                | Characteristic::StatusLowBattery(_)
+                | Characteristic::PositionState(_)
+                | Characteristic::HoldPosition(_)
+                | Characteristic::OutletInUse(_)
+                | Characteristic::AirQuality(_)
+                | Characteristic::Active(_)
+                | Characteristic::RotationDirection(_)
+                | Characteristic::SwingMode(_)
+                | Characteristic::FilterChangeIndication(_) => None,
+            }
+        }
     }
 
     #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -235,6 +1099,26 @@ pub mod characteristics {
         pub percentage: u8,
     }
 
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Hue {
+        /// Hue in degrees, from 0 to 360
+        pub degrees: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Saturation {
+        pub percentage: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct ColorTemperature {
+        /// Color temperature in mireds, that is one million divided by the temperature in kelvins
+        pub mireds: u16,
+    }
+
     #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
     #[serde(rename_all = "kebab-case")]
     pub struct BatteryLevel {
@@ -248,4 +1132,578 @@ pub mod characteristics {
         Charging,
         NotChargeable,
     }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct TargetTemperature {
+        pub temperature: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct CurrentHeatingCoolingState {
+        pub state: HeatingCoolingState,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct TargetHeatingCoolingState {
+        pub mode: HeatingCoolingMode,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct TemperatureDisplayUnits {
+        pub units: TemperatureUnits,
+    }
+
+    /// What the thermostat is currently doing
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum HeatingCoolingState {
+        Off,
+        Heat,
+        Cool,
+    }
+
+    /// Mode the thermostat is set to
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum HeatingCoolingMode {
+        Off,
+        Heat,
+        Cool,
+        /// Heat or cool as needed to reach the target temperature
+        Auto,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct CurrentLockState {
+        pub state: LockState,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct TargetLockState {
+        pub state: LockTargetState,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct ContactSensorState {
+        /// True when the contact is closed, e.g. the door is shut
+        pub contact_detected: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct MotionDetected {
+        pub motion_detected: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct OccupancyDetected {
+        pub occupancy_detected: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct LeakDetected {
+        pub leak_detected: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct SmokeDetected {
+        pub smoke_detected: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct StatusTampered {
+        pub tampered: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct StatusLowBattery {
+        pub low_battery: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct CurrentPosition {
+        /// How far the covering is open, 0 is fully closed
+        pub position: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct TargetPosition {
+        /// How far the covering should open, 0 is fully closed
+        pub position: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct PositionState {
+        pub movement: Movement,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct CurrentTiltAngle {
+        /// Tilt of the slats, from -90 to 90 degrees
+        pub degrees: i8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct TargetTiltAngle {
+        /// Tilt of the slats, from -90 to 90 degrees
+        pub degrees: i8,
+    }
+
+    /// Stops the covering where it currently is
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct HoldPosition {
+        pub hold: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct OutletInUse {
+        /// True when something plugged into the outlet draws power
+        pub in_use: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct PowerConsumption {
+        /// Instantaneous power
+        pub watts: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Voltage {
+        pub volts: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct ElectricCurrent {
+        pub amperes: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct EnergyConsumption {
+        /// Energy consumed since the accessory started counting
+        pub kilowatt_hours: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct AirQuality {
+        pub quality: AirQualityLevel,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct CarbonDioxideLevel {
+        /// Concentration in parts per million
+        pub ppm: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Pm25Density {
+        /// Density of particulate matter up to 2.5 µm in µg/m³
+        pub density: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Pm10Density {
+        /// Density of particulate matter up to 10 µm in µg/m³
+        pub density: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct VocDensity {
+        /// Density of volatile organic compounds in µg/m³
+        pub density: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Active {
+        pub active: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct RotationSpeed {
+        /// Percentage of the maximum speed
+        pub percentage: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct RotationDirection {
+        pub direction: Rotation,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct SwingMode {
+        /// True when the fan oscillates
+        pub swing: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct FilterLifeLevel {
+        /// Percentage of the filter life left
+        pub percentage: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct FilterChangeIndication {
+        pub change_needed: bool,
+    }
+
+    /// Direction in which the fan blades rotate
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum Rotation {
+        Clockwise,
+        CounterClockwise,
+    }
+
+    /// Overall air quality, from best to worst
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum AirQualityLevel {
+        Unknown,
+        Excellent,
+        Good,
+        Fair,
+        Inferior,
+        Poor,
+    }
+
+    /// Direction in which the covering is moving
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum Movement {
+        Closing,
+        Opening,
+        Stopped,
+    }
+
+    /// State reported by the lock
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum LockState {
+        Unsecured,
+        Secured,
+        /// Lock couldn't reach the target state
+        Jammed,
+        /// Lock can't determine its state
+        Unknown,
+    }
+
+    /// State the lock is requested to reach
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum LockTargetState {
+        Unsecured,
+        Secured,
+    }
+
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+    )]
+    #[serde(rename_all = "kebab-case")]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum TemperatureUnits {
+        Celsius,
+        Fahrenheit,
+    }
+}
+
+/// Conversions between the color representations used by the controllers.
+pub mod color {
+    /// Color in the HSV model
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Hsv {
+        /// Hue in degrees, from 0 to 360
+        pub hue: f32,
+        /// Saturation from 0 to 1
+        pub saturation: f32,
+        /// Value from 0 to 1
+        pub value: f32,
+    }
+
+    /// Converts a color packed as `0xRRGGBB` to HSV.
+    pub fn rgb_to_hsv(rgb: u32) -> Hsv {
+        let r = ((rgb >> 16) & 0xFF) as f32 / 255.0;
+        let g = ((rgb >> 8) & 0xFF) as f32 / 255.0;
+        let b = (rgb & 0xFF) as f32 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        Hsv {
+            hue,
+            saturation,
+            value: max,
+        }
+    }
+
+    /// Converts HSV to a color packed as `0xRRGGBB`.
+    pub fn hsv_to_rgb(hsv: Hsv) -> u32 {
+        let Hsv {
+            hue,
+            saturation,
+            value,
+        } = hsv;
+        let chroma = value * saturation;
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let channel = |c: f32| ((c + m) * 255.0).round() as u32;
+        (channel(r) << 16) | (channel(g) << 8) | channel(b)
+    }
+
+    /// Converts color temperature in kelvins to mireds.
+    pub fn kelvin_to_mireds(kelvin: u32) -> u16 {
+        (1_000_000 / kelvin.max(1)).min(u16::MAX.into()) as u16
+    }
+
+    /// Converts color temperature in mireds to kelvins.
+    pub fn mireds_to_kelvin(mireds: u16) -> u32 {
+        1_000_000 / u32::from(mireds.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::characteristics::Characteristic;
+    use super::characteristics::CharacteristicName;
+    use super::services::ServiceName;
+    use super::*;
+
+    #[test]
+    fn validate_write() {
+        let capabilities = Type::Houseflow(manufacturers::Houseflow::Garage).capabilities();
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::GarageDoorOpener,
+                &Characteristic::TargetDoorState(characteristics::TargetDoorState {
+                    open_percent: 100
+                })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::GarageDoorOpener,
+                &Characteristic::TargetDoorState(characteristics::TargetDoorState {
+                    open_percent: 101
+                })
+            ),
+            Err(Error::ValueOutOfRange)
+        );
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::GarageDoorOpener,
+                &Characteristic::CurrentDoorState(characteristics::CurrentDoorState {
+                    open_percent: 0
+                })
+            ),
+            Err(Error::CharacteristicReadOnly)
+        );
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::GarageDoorOpener,
+                &Characteristic::On(characteristics::On { on: true })
+            ),
+            Err(Error::CharacteristicNotSupported)
+        );
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::Light,
+                &Characteristic::On(characteristics::On { on: true })
+            ),
+            Err(Error::ServiceNotSupported)
+        );
+    }
+
+    #[test]
+    fn validate_thermostat_write() {
+        let capabilities = Type::Houseflow(manufacturers::Houseflow::Thermostat).capabilities();
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::Thermostat,
+                &Characteristic::TargetTemperature(characteristics::TargetTemperature {
+                    temperature: 21.5
+                })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::Thermostat,
+                &Characteristic::TargetTemperature(characteristics::TargetTemperature {
+                    temperature: 45.0
+                })
+            ),
+            Err(Error::ValueOutOfRange)
+        );
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::Thermostat,
+                &Characteristic::CurrentHeatingCoolingState(
+                    characteristics::CurrentHeatingCoolingState {
+                        state: characteristics::HeatingCoolingState::Heat
+                    }
+                )
+            ),
+            Err(Error::CharacteristicReadOnly)
+        );
+    }
+
+    #[test]
+    fn lightbulb_features() {
+        let r#type: Type = serde_json::from_value(serde_json::json!({
+            "manufacturer": "houseflow",
+            "model": "lightbulb",
+        }))
+        .unwrap();
+        let capabilities = r#type.capabilities();
+        let light = capabilities.service(ServiceName::Light).unwrap();
+        assert_eq!(light.characteristics.len(), 1);
+        assert_eq!(
+            capabilities.validate_write(
+                ServiceName::Light,
+                &Characteristic::Brightness(characteristics::Brightness { percentage: 50 })
+            ),
+            Err(Error::CharacteristicNotSupported)
+        );
+
+        let r#type: Type = serde_json::from_value(serde_json::json!({
+            "manufacturer": "houseflow",
+            "model": "lightbulb",
+            "brightness": true,
+            "color-temperature": true,
+        }))
+        .unwrap();
+        let capabilities = r#type.capabilities();
+        let light = capabilities.service(ServiceName::Light).unwrap();
+        assert!(light
+            .characteristic(CharacteristicName::Brightness)
+            .is_some());
+        assert!(light
+            .characteristic(CharacteristicName::ColorTemperature)
+            .is_some());
+        assert!(light.characteristic(CharacteristicName::Hue).is_none());
+        assert!(light
+            .characteristic(CharacteristicName::Saturation)
+            .is_none());
+    }
+
+    #[test]
+    fn localize_units() {
+        use capabilities::Unit;
+
+        let metric = crate::user::Preferences::default();
+        let imperial = crate::user::Preferences {
+            measurement_system: crate::user::MeasurementSystem::Imperial,
+            temperature_units: None,
+        };
+        assert_eq!(Unit::Celsius.localize(20.0, &metric), (Unit::Celsius, 20.0));
+        assert_eq!(
+            Unit::Celsius.localize(20.0, &imperial),
+            (Unit::Fahrenheit, 68.0)
+        );
+        assert_eq!(
+            Unit::Celsius.localize(
+                20.0,
+                &crate::user::Preferences {
+                    temperature_units: Some(characteristics::TemperatureUnits::Celsius),
+                    ..imperial.clone()
+                }
+            ),
+            (Unit::Celsius, 20.0)
+        );
+        assert_eq!(Unit::Watts.localize(60.0, &imperial), (Unit::Watts, 60.0));
+    }
+
+    #[test]
+    fn color_conversions() {
+        use color::*;
+
+        for rgb in [
+            0x000000, 0xFFFFFF, 0xFF0000, 0x00FF00, 0x0000FF, 0x12AB7F, 0xFFA500,
+        ] {
+            assert_eq!(hsv_to_rgb(rgb_to_hsv(rgb)), rgb);
+        }
+        assert_eq!(
+            rgb_to_hsv(0x00FF00),
+            Hsv {
+                hue: 120.0,
+                saturation: 1.0,
+                value: 1.0
+            }
+        );
+        assert_eq!(kelvin_to_mireds(2000), 500);
+        assert_eq!(mireds_to_kelvin(500), 2000);
+        assert_eq!(kelvin_to_mireds(0), u16::MAX);
+    }
 }
@@ -0,0 +1,50 @@
+use crate::hub;
+use crate::structure;
+use crate::user;
+use crate::user::User;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListUsersResponse {
+    pub users: Vec<User>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AddUserRequest {
+    pub username: String,
+    pub email: lettre::Address,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AddStructureRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegisterHubRequest {
+    pub name: String,
+    pub structure_id: structure::ID,
+}
+
+/// Credentials of the registered hub, the password is not stored by the server and can't be retrieved later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegisterHubResponse {
+    pub id: hub::ID,
+    pub password: hub::Password,
+}
+
+/// Removed user, with the permissions that were removed together with it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoveUserResponse {
+    pub user_id: user::ID,
+    pub removed_permissions: usize,
+}
@@ -39,4 +39,7 @@ pub enum Error {
     /// User does not have permission to a structure
     #[error("user does not have permission to a specified structure")]
     NoStructurePermission,
+    /// User tried to use the admin API without being an admin
+    #[error("user is not an admin")]
+    NotAdmin,
 }
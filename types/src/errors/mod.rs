@@ -59,6 +59,7 @@ impl axum::response::IntoResponse for ServerError {
                 AuthError::NoStructurePermission => StatusCode::UNAUTHORIZED,
                 AuthError::InvalidVerificationCode(_) => StatusCode::UNAUTHORIZED,
                 AuthError::NoAccessoryPermission => StatusCode::UNAUTHORIZED,
+                AuthError::NotAdmin => StatusCode::FORBIDDEN,
                 AuthError::InvalidGoogleJwt(_) => StatusCode::UNAUTHORIZED,
                 AuthError::InvalidCsrfToken => StatusCode::UNAUTHORIZED,
             },
@@ -71,6 +72,9 @@ impl axum::response::IntoResponse for ServerError {
                     accessory::Error::CharacteristicWriteOnly => StatusCode::BAD_REQUEST,
                     accessory::Error::CharacteristicNotSupported => StatusCode::BAD_REQUEST,
                     accessory::Error::ServiceNotSupported => StatusCode::BAD_REQUEST,
+                    accessory::Error::ValueOutOfRange => StatusCode::BAD_REQUEST,
+                    accessory::Error::RequestNotSupported => StatusCode::NOT_IMPLEMENTED,
+                    accessory::Error::Failed => StatusCode::BAD_GATEWAY,
                     accessory::Error::NotConnected => StatusCode::SERVICE_UNAVAILABLE,
                 },
             },
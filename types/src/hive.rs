@@ -2,25 +2,66 @@ use crate::accessory;
 use crate::accessory::characteristics::Characteristic;
 use crate::accessory::characteristics::CharacteristicName;
 use crate::accessory::services::ServiceName;
+use crate::protocol;
 use serde::Deserialize;
 use serde::Serialize;
+use strum::EnumDiscriminants;
 
 pub type FrameID = u16;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Version of the hive protocol implemented by this crate, version 3 adds batch frames, version 4 adds capabilities, version 5 adds identify
+pub const PROTOCOL_VERSION: protocol::Version = 5;
+/// First version of the hive protocol in which the accessory sends its capabilities right after the handshake
+pub const CAPABILITIES_PROTOCOL_VERSION: protocol::Version = 4;
+/// Oldest version of the hive protocol that is still supported, version 1 has no handshake
+pub const MIN_PROTOCOL_VERSION: protocol::Version = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumDiscriminants)]
+#[strum_discriminants(derive(strum::Display, strum::EnumVariantNames))]
+#[strum_discriminants(name(HubFrameType))]
+#[strum_discriminants(strum(serialize_all = "kebab-case"))]
 #[non_exhaustive]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum HubFrame {
+    Hello(protocol::Hello),
+    Error(protocol::Error),
     ReadCharacteristic(ReadCharacteristic),
     WriteCharacteristic(WriteCharacteristic),
+    BatchReadCharacteristic(BatchReadCharacteristic),
+    BatchWriteCharacteristic(BatchWriteCharacteristic),
+    Identify(Identify),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl HubFrameType {
+    pub const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumDiscriminants)]
+#[strum_discriminants(derive(strum::Display, strum::EnumVariantNames))]
+#[strum_discriminants(name(AccessoryFrameType))]
+#[strum_discriminants(strum(serialize_all = "kebab-case"))]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum AccessoryFrame {
+    Hello(protocol::Hello),
+    Error(protocol::Error),
+    Capabilities(Capabilities),
     UpdateCharacteristic(UpdateCharacteristic),
     CharacteristicReadResult(CharacteristicReadResult),
     CharacteristicWriteResult(CharateristicWriteResult),
+    BatchCharacteristicReadResult(BatchCharacteristicReadResult),
+    BatchCharacteristicWriteResult(BatchCharacteristicWriteResult),
+    IdentifyResult(IdentifyResult),
+}
+
+impl AccessoryFrameType {
+    pub const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;
+}
+
+/// Sent by the accessory right after the handshake, the hub announces the accessory once it is received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Capabilities declared by the accessory, `None` to use the defaults of the accessory type
+    pub capabilities: Option<accessory::capabilities::Capabilities>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -45,6 +86,41 @@ pub struct WriteCharacteristic {
     pub characteristic: Characteristic,
 }
 
+/// Asks the accessory to identify itself, e.g by blinking an LED
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Identify {
+    pub id: FrameID,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CharacteristicRead {
+    pub service_name: ServiceName,
+    pub characteristic_name: CharacteristicName,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CharacteristicWrite {
+    pub service_name: ServiceName,
+    pub characteristic: Characteristic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchReadCharacteristic {
+    pub id: FrameID,
+    pub reads: Vec<CharacteristicRead>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchWriteCharacteristic {
+    pub id: FrameID,
+    pub writes: Vec<CharacteristicWrite>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct CharacteristicReadResult {
@@ -58,3 +134,159 @@ pub struct CharateristicWriteResult {
     pub id: FrameID,
     pub result: accessory::Result<()>,
 }
+
+/// Results of a batch read, in the same order as the reads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchCharacteristicReadResult {
+    pub id: FrameID,
+    pub results: Vec<accessory::Result<Characteristic>>,
+}
+
+/// Results of a batch write, in the same order as the writes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchCharacteristicWriteResult {
+    pub id: FrameID,
+    pub results: Vec<accessory::Result<()>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IdentifyResult {
+    pub id: FrameID,
+    pub result: accessory::Result<()>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessory::characteristics;
+    use crate::protocol::Encoding;
+    use crate::protocol::test_support;
+    use crate::protocol::test_support::round_trip;
+
+    fn hello() -> protocol::Hello {
+        test_support::hello(PROTOCOL_VERSION, MIN_PROTOCOL_VERSION)
+    }
+
+    #[test]
+    fn hub_frame_round_trip() {
+        let frames = [
+            HubFrame::Hello(hello()),
+            HubFrame::Error(protocol::Error::MalformedFrame(String::from(
+                "missing field",
+            ))),
+            HubFrame::ReadCharacteristic(ReadCharacteristic {
+                id: 1,
+                service_name: ServiceName::TemperatureSensor,
+                characteristic_name: CharacteristicName::CurrentTemperature,
+            }),
+            HubFrame::WriteCharacteristic(WriteCharacteristic {
+                id: 2,
+                service_name: ServiceName::GarageDoorOpener,
+                characteristic: Characteristic::TargetDoorState(characteristics::TargetDoorState {
+                    open_percent: 100,
+                }),
+            }),
+            HubFrame::BatchReadCharacteristic(BatchReadCharacteristic {
+                id: 3,
+                reads: vec![
+                    CharacteristicRead {
+                        service_name: ServiceName::TemperatureSensor,
+                        characteristic_name: CharacteristicName::CurrentTemperature,
+                    },
+                    CharacteristicRead {
+                        service_name: ServiceName::HumiditySensor,
+                        characteristic_name: CharacteristicName::CurrentHumidity,
+                    },
+                ],
+            }),
+            HubFrame::BatchWriteCharacteristic(BatchWriteCharacteristic {
+                id: 4,
+                writes: vec![
+                    CharacteristicWrite {
+                        service_name: ServiceName::Light,
+                        characteristic: Characteristic::On(characteristics::On { on: true }),
+                    },
+                    CharacteristicWrite {
+                        service_name: ServiceName::Light,
+                        characteristic: Characteristic::Hue(characteristics::Hue {
+                            degrees: 210.5,
+                        }),
+                    },
+                    CharacteristicWrite {
+                        service_name: ServiceName::Light,
+                        characteristic: Characteristic::ColorTemperature(
+                            characteristics::ColorTemperature { mireds: 370 },
+                        ),
+                    },
+                ],
+            }),
+            HubFrame::Identify(Identify { id: 5 }),
+        ];
+        assert_eq!(frames.len(), HubFrameType::VARIANTS.len());
+        for frame in frames {
+            round_trip(frame, HubFrameType::VARIANTS);
+        }
+    }
+
+    #[test]
+    fn accessory_frame_round_trip() {
+        let frames = [
+            AccessoryFrame::Hello(hello().prefer(Encoding::MessagePack)),
+            AccessoryFrame::Error(protocol::Error::UnknownFrame(String::from("unknown"))),
+            AccessoryFrame::Capabilities(Capabilities {
+                capabilities: Some(
+                    accessory::Type::Houseflow(accessory::manufacturers::Houseflow::Garage)
+                        .capabilities(),
+                ),
+            }),
+            AccessoryFrame::UpdateCharacteristic(UpdateCharacteristic {
+                service_name: ServiceName::Battery,
+                characteristic: Characteristic::BatteryLevel(characteristics::BatteryLevel {
+                    battery_level_percent: 80,
+                }),
+            }),
+            AccessoryFrame::CharacteristicReadResult(CharacteristicReadResult {
+                id: 1,
+                result: accessory::Result::Ok(Characteristic::On(characteristics::On {
+                    on: false,
+                })),
+            }),
+            AccessoryFrame::CharacteristicWriteResult(CharateristicWriteResult {
+                id: 2,
+                result: accessory::Result::Ok(()),
+            }),
+            AccessoryFrame::BatchCharacteristicReadResult(BatchCharacteristicReadResult {
+                id: 3,
+                results: vec![
+                    accessory::Result::Ok(Characteristic::CurrentTemperature(
+                        characteristics::CurrentTemperature { temperature: 20.0 },
+                    )),
+                    accessory::Result::Ok(Characteristic::EnergyConsumption(
+                        characteristics::EnergyConsumption {
+                            kilowatt_hours: 12.25,
+                        },
+                    )),
+                    accessory::Result::Err(accessory::Error::ServiceNotSupported),
+                ],
+            }),
+            AccessoryFrame::BatchCharacteristicWriteResult(BatchCharacteristicWriteResult {
+                id: 4,
+                results: vec![
+                    accessory::Result::Ok(()),
+                    accessory::Result::Err(accessory::Error::CharacteristicReadOnly),
+                ],
+            }),
+            AccessoryFrame::IdentifyResult(IdentifyResult {
+                id: 5,
+                result: accessory::Result::Ok(()),
+            }),
+        ];
+        assert_eq!(frames.len(), AccessoryFrameType::VARIANTS.len());
+        for frame in frames {
+            round_trip(frame, AccessoryFrameType::VARIANTS);
+        }
+    }
+}
@@ -37,6 +37,9 @@ impl axum::response::IntoResponse for Error {
                 accessory::Error::CharacteristicWriteOnly => StatusCode::BAD_REQUEST,
                 accessory::Error::CharacteristicNotSupported => StatusCode::BAD_REQUEST,
                 accessory::Error::ServiceNotSupported => StatusCode::BAD_REQUEST,
+                accessory::Error::ValueOutOfRange => StatusCode::BAD_REQUEST,
+                accessory::Error::RequestNotSupported => StatusCode::NOT_IMPLEMENTED,
+                accessory::Error::Failed => StatusCode::BAD_GATEWAY,
                 accessory::Error::NotConnected => StatusCode::SERVICE_UNAVAILABLE,
             },
         };
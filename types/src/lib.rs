@@ -3,6 +3,7 @@ pub mod client;
 pub mod hive;
 pub mod hub;
 pub mod permission;
+pub mod protocol;
 pub mod room;
 pub mod structure;
 pub mod user;
@@ -10,6 +11,9 @@ pub mod user;
 pub mod code;
 pub mod errors;
 
+#[cfg(feature = "admin")]
+pub mod admin;
+
 #[cfg(feature = "auth")]
 pub mod auth;
 
@@ -19,6 +23,9 @@ pub mod lighthouse;
 #[cfg(feature = "meta")]
 pub mod meta;
 
+#[cfg(feature = "routing")]
+pub mod routing;
+
 #[cfg(feature = "token")]
 pub mod token;
 
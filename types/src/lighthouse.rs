@@ -3,27 +3,74 @@ use crate::accessory::characteristics::Characteristic;
 use crate::accessory::characteristics::CharacteristicName;
 use crate::accessory::services::ServiceName;
 use crate::accessory::Accessory;
+use crate::accessory::CharacteristicRead;
+use crate::accessory::CharacteristicWrite;
+use crate::protocol;
 use serde::Deserialize;
 use serde::Serialize;
+use strum::EnumDiscriminants;
 
 pub type FrameID = u16;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Version of the lighthouse protocol implemented by this crate, version 3 adds batch frames, version 4 adds capabilities, version 5 adds identify
+pub const PROTOCOL_VERSION: protocol::Version = 5;
+/// Oldest version of the lighthouse protocol that is still supported, version 1 has no handshake
+pub const MIN_PROTOCOL_VERSION: protocol::Version = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumDiscriminants)]
+#[strum_discriminants(derive(strum::Display, strum::EnumVariantNames))]
+#[strum_discriminants(name(ServerFrameType))]
+#[strum_discriminants(strum(serialize_all = "kebab-case"))]
 #[non_exhaustive]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ServerFrame {
+    Hello(protocol::Hello),
+    Error(protocol::Error),
     ReadCharacteristic(ReadCharacteristic),
     WriteCharacteristic(WriteCharacteristic),
+    BatchReadCharacteristic(BatchReadCharacteristic),
+    BatchWriteCharacteristic(BatchWriteCharacteristic),
+    Identify(Identify),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl ServerFrameType {
+    pub const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumDiscriminants)]
+#[strum_discriminants(derive(strum::Display, strum::EnumVariantNames))]
+#[strum_discriminants(name(HubFrameType))]
+#[strum_discriminants(strum(serialize_all = "kebab-case"))]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum HubFrame {
-    AccessoryConnected(Accessory),
-    AccessoryDisconnected(accessory::ID),
+    Hello(protocol::Hello),
+    Error(protocol::Error),
+    AccessoryConnected(AccessoryConnected),
+    AccessoryDisconnected(AccessoryDisconnected),
     UpdateCharacteristic(UpdateCharacteristic),
     ReadCharacteristicResult(ReadCharacteristicResult),
     WriteCharacteristicResult(WriteCharacteristicResult),
+    BatchReadCharacteristicResult(BatchReadCharacteristicResult),
+    BatchWriteCharacteristicResult(BatchWriteCharacteristicResult),
+    IdentifyResult(IdentifyResult),
+}
+
+impl HubFrameType {
+    pub const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessoryConnected {
+    #[serde(flatten)]
+    pub accessory: Accessory,
+    /// Capabilities of the accessory, `None` if sent by a hub older than version 4
+    #[serde(default)]
+    pub capabilities: Option<accessory::capabilities::Capabilities>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessoryDisconnected {
+    pub accessory_id: accessory::ID,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -49,6 +96,25 @@ pub struct WriteCharacteristic {
     pub characteristic: Characteristic,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchReadCharacteristic {
+    pub id: FrameID,
+    pub reads: Vec<CharacteristicRead>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchWriteCharacteristic {
+    pub id: FrameID,
+    pub writes: Vec<CharacteristicWrite>,
+}
+
+/// Asks the accessory to identify itself, e.g by blinking an LED
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identify {
+    pub id: FrameID,
+    pub accessory_id: accessory::ID,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReadCharacteristicResult {
     pub id: FrameID,
@@ -60,3 +126,179 @@ pub struct WriteCharacteristicResult {
     pub id: FrameID,
     pub result: accessory::Result<()>,
 }
+
+/// Results of a batch read, in the same order as the reads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchReadCharacteristicResult {
+    pub id: FrameID,
+    pub results: Vec<accessory::Result<Characteristic>>,
+}
+
+/// Results of a batch write, in the same order as the writes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchWriteCharacteristicResult {
+    pub id: FrameID,
+    pub results: Vec<accessory::Result<()>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdentifyResult {
+    pub id: FrameID,
+    pub result: accessory::Result<()>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessory::characteristics;
+    use crate::accessory::manufacturers;
+    use crate::protocol::Encoding;
+    use crate::protocol::test_support;
+    use crate::protocol::test_support::round_trip;
+
+    fn hello() -> protocol::Hello {
+        test_support::hello(PROTOCOL_VERSION, MIN_PROTOCOL_VERSION)
+    }
+
+    #[test]
+    fn server_frame_round_trip() {
+        let accessory_id = accessory::ID::new_v4();
+        let frames = [
+            ServerFrame::Hello(hello()),
+            ServerFrame::Error(protocol::Error::UnsupportedVersion {
+                version: 3,
+                min: 1,
+                max: 2,
+            }),
+            ServerFrame::ReadCharacteristic(ReadCharacteristic {
+                id: 1,
+                accessory_id,
+                service_name: ServiceName::TemperatureSensor,
+                characteristic_name: CharacteristicName::CurrentTemperature,
+            }),
+            ServerFrame::WriteCharacteristic(WriteCharacteristic {
+                id: 2,
+                accessory_id,
+                service_name: ServiceName::Light,
+                characteristic: Characteristic::On(characteristics::On { on: true }),
+            }),
+            ServerFrame::BatchReadCharacteristic(BatchReadCharacteristic {
+                id: 3,
+                reads: vec![CharacteristicRead {
+                    accessory_id,
+                    service_name: ServiceName::Battery,
+                    characteristic_name: CharacteristicName::BatteryLevel,
+                }],
+            }),
+            ServerFrame::BatchWriteCharacteristic(BatchWriteCharacteristic {
+                id: 4,
+                writes: vec![
+                    CharacteristicWrite {
+                        accessory_id,
+                        service_name: ServiceName::Light,
+                        characteristic: Characteristic::On(characteristics::On { on: false }),
+                    },
+                    CharacteristicWrite {
+                        accessory_id: accessory::ID::new_v4(),
+                        service_name: ServiceName::GarageDoorOpener,
+                        characteristic: Characteristic::TargetDoorState(
+                            characteristics::TargetDoorState { open_percent: 0 },
+                        ),
+                    },
+                ],
+            }),
+            ServerFrame::Identify(Identify {
+                id: 5,
+                accessory_id,
+            }),
+        ];
+        assert_eq!(frames.len(), ServerFrameType::VARIANTS.len());
+        for frame in frames {
+            round_trip(frame, ServerFrameType::VARIANTS);
+        }
+    }
+
+    #[test]
+    fn hub_frame_round_trip() {
+        let accessory_id = accessory::ID::new_v4();
+        let frames = [
+            HubFrame::Hello(hello().prefer(Encoding::MessagePack)),
+            HubFrame::Error(protocol::Error::UnknownFrame(String::from("unknown"))),
+            HubFrame::AccessoryConnected(AccessoryConnected {
+                accessory: Accessory {
+                    id: accessory_id,
+                    name: String::from("Thermometer"),
+                    room_name: String::from("Bedroom"),
+                    r#type: accessory::Type::XiaomiMijia(
+                        manufacturers::XiaomiMijia::HygroThermometer,
+                    ),
+                },
+                capabilities: Some(
+                    accessory::Type::XiaomiMijia(manufacturers::XiaomiMijia::HygroThermometer)
+                        .capabilities(),
+                ),
+            }),
+            HubFrame::AccessoryDisconnected(AccessoryDisconnected { accessory_id }),
+            HubFrame::UpdateCharacteristic(UpdateCharacteristic {
+                accessory_id,
+                service_name: ServiceName::TemperatureSensor,
+                characteristic: Characteristic::CurrentTemperature(
+                    characteristics::CurrentTemperature { temperature: 21.5 },
+                ),
+            }),
+            HubFrame::ReadCharacteristicResult(ReadCharacteristicResult {
+                id: 1,
+                result: accessory::Result::Ok(Characteristic::CurrentHumidity(
+                    characteristics::CurrentHumidity { humidity: 40.0 },
+                )),
+            }),
+            HubFrame::WriteCharacteristicResult(WriteCharacteristicResult {
+                id: 2,
+                result: accessory::Result::Err(accessory::Error::CharacteristicReadOnly),
+            }),
+            HubFrame::BatchReadCharacteristicResult(BatchReadCharacteristicResult {
+                id: 3,
+                results: vec![
+                    accessory::Result::Err(accessory::Error::NotConnected),
+                    accessory::Result::Ok(Characteristic::CurrentDoorState(
+                        characteristics::CurrentDoorState { open_percent: 50 },
+                    )),
+                ],
+            }),
+            HubFrame::BatchWriteCharacteristicResult(BatchWriteCharacteristicResult {
+                id: 4,
+                results: vec![accessory::Result::Ok(())],
+            }),
+            HubFrame::IdentifyResult(IdentifyResult {
+                id: 5,
+                result: accessory::Result::Err(accessory::Error::NotConnected),
+            }),
+        ];
+        assert_eq!(frames.len(), HubFrameType::VARIANTS.len());
+        for frame in frames {
+            round_trip(frame, HubFrameType::VARIANTS);
+        }
+    }
+
+    #[test]
+    fn accessory_connected_without_capabilities() {
+        let accessory_id = accessory::ID::new_v4();
+        let text = format!(
+            r#"{{"type": "accessory-connected", "id": "{}", "name": "Gate", "room-name": "Garden", "manufacturer": "houseflow", "model": "gate"}}"#,
+            accessory_id
+        );
+        let frame: HubFrame = protocol::decode(&text, HubFrameType::VARIANTS).unwrap();
+        assert_eq!(
+            frame,
+            HubFrame::AccessoryConnected(AccessoryConnected {
+                accessory: Accessory {
+                    id: accessory_id,
+                    name: String::from("Gate"),
+                    room_name: String::from("Garden"),
+                    r#type: accessory::Type::Houseflow(manufacturers::Houseflow::Gate),
+                },
+                capabilities: None,
+            })
+        );
+    }
+}
@@ -1 +1,229 @@
+use crate::accessory;
+use crate::accessory::capabilities::Capabilities;
+use crate::accessory::capabilities::Unit;
+use crate::accessory::characteristics::Characteristic;
+use crate::accessory::services::ServiceName;
+use crate::accessory::Accessory;
+use crate::structure::Structure;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
 
+/// Characteristic with its value converted to the display preferences of the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReadResponse {
+    #[serde(flatten)]
+    pub characteristic: Characteristic,
+    /// `None` for non-numeric characteristics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<DisplayValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DisplayValue {
+    pub value: f32,
+    pub unit: Unit,
+}
+
+impl std::fmt::Display for DisplayValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.value, self.unit.symbol())
+    }
+}
+
+/// Accessories which are currently connected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListAccessoriesResponse {
+    pub accessories: Vec<ConnectedAccessory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConnectedAccessory {
+    pub id: accessory::ID,
+    pub capabilities: Capabilities,
+    /// Structure of the hub that the accessory is connected through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structure: Option<Structure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchReadRequest {
+    pub reads: Vec<accessory::CharacteristicRead>,
+}
+
+/// Results of the batch read, in the same order as the reads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchReadResponse {
+    pub results: Vec<accessory::Result<Characteristic>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchWriteRequest {
+    pub writes: Vec<accessory::CharacteristicWrite>,
+}
+
+/// Results of the batch write, in the same order as the writes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchWriteResponse {
+    pub results: Vec<accessory::Result<()>>,
+}
+
+/// Event streamed to the watchers, accessories connected before subscribing are sent as `Connected` events first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Event {
+    Connected {
+        accessory: Accessory,
+    },
+    #[serde(rename_all = "kebab-case")]
+    Disconnected {
+        accessory_id: accessory::ID,
+    },
+    #[serde(rename_all = "kebab-case")]
+    Updated {
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic: Characteristic,
+    },
+}
+
+impl Event {
+    pub fn accessory_id(&self) -> accessory::ID {
+        match self {
+            Self::Connected { accessory } => accessory.id,
+            Self::Disconnected { accessory_id } => *accessory_id,
+            Self::Updated { accessory_id, .. } => *accessory_id,
+        }
+    }
+}
+
+/// Narrows down the watched events, unset fields match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessory_id: Option<accessory::ID>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_name: Option<String>,
+    /// Filters only the `Updated` events, connections of the accessories are always passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<ServiceName>,
+}
+
+impl EventFilter {
+    /// Checks whether the event passes the filter, `accessory` is the accessory that the event refers to, if it's known.
+    pub fn matches(&self, event: &Event, accessory: Option<&Accessory>) -> bool {
+        if let Some(accessory_id) = self.accessory_id {
+            if event.accessory_id() != accessory_id {
+                return false;
+            }
+        }
+        if let Some(room_name) = &self.room_name {
+            if accessory.map(|accessory| &accessory.room_name) != Some(room_name) {
+                return false;
+            }
+        }
+        match (self.service_name, event) {
+            (Some(expected), Event::Updated { service_name, .. }) => expected == *service_name,
+            _ => true,
+        }
+    }
+}
+
+/// Filters the events sent to a single watcher.
+///
+/// Remembers the accessories from the `Connected` events, so the events which carry only the accessory ID can be filtered by the room.
+#[derive(Debug, Clone, Default)]
+pub struct Watcher {
+    filter: EventFilter,
+    accessories: HashMap<accessory::ID, Accessory>,
+}
+
+impl Watcher {
+    pub fn new(filter: EventFilter) -> Self {
+        Self {
+            filter,
+            accessories: Default::default(),
+        }
+    }
+
+    /// Checks whether the event passes the filter of the watcher
+    pub fn matches(&mut self, event: &Event) -> bool {
+        if let Event::Connected { accessory } = event {
+            self.accessories.insert(accessory.id, accessory.clone());
+        }
+        let matches = self
+            .filter
+            .matches(event, self.accessories.get(&event.accessory_id()));
+        if let Event::Disconnected { accessory_id } = event {
+            self.accessories.remove(accessory_id);
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessory::characteristics;
+    use crate::accessory::manufacturers;
+
+    #[test]
+    fn event_filter() {
+        let accessory = Accessory {
+            id: accessory::ID::new_v4(),
+            name: String::from("Thermometer"),
+            room_name: String::from("Bedroom"),
+            r#type: accessory::Type::XiaomiMijia(manufacturers::XiaomiMijia::HygroThermometer),
+        };
+        let updated = Event::Updated {
+            accessory_id: accessory.id,
+            service_name: ServiceName::TemperatureSensor,
+            characteristic: Characteristic::CurrentTemperature(
+                characteristics::CurrentTemperature { temperature: 21.5 },
+            ),
+        };
+        let connected = Event::Connected {
+            accessory: accessory.clone(),
+        };
+
+        assert!(EventFilter::default().matches(&updated, None));
+        let filter = EventFilter {
+            room_name: Some(String::from("Bedroom")),
+            service_name: Some(ServiceName::HumiditySensor),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&updated, Some(&accessory)));
+        assert!(filter.matches(&connected, Some(&accessory)));
+        assert!(!filter.matches(&connected, None));
+        let filter = EventFilter {
+            accessory_id: Some(accessory::ID::new_v4()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&connected, Some(&accessory)));
+    }
+
+    #[test]
+    fn event_serialization() {
+        let event = Event::Disconnected {
+            accessory_id: accessory::ID::nil(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "disconnected",
+                "accessory-id": "00000000-0000-0000-0000-000000000000",
+            })
+        );
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+    }
+}
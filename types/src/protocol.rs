@@ -0,0 +1,346 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+
+pub type Version = u16;
+
+/// First frame sent by each side of a websocket connection.
+///
+/// The connecting side (accessory or hub) sends its `Hello` right after the connection is established,
+/// and the accepting side responds with its own `Hello` carrying the negotiated protocol version,
+/// or with an error frame if the versions are incompatible.
+///
+/// Peers that never send a `Hello` are treated as legacy peers speaking the oldest protocol version.
+///
+/// Handshake frames are always JSON encoded, the encoding of the following frames is negotiated using `encodings`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Hello {
+    /// Highest protocol version supported by the peer
+    pub protocol_version: Version,
+    /// Lowest protocol version supported by the peer
+    pub min_protocol_version: Version,
+    /// Name and version of the software running on the peer, e.g `houseflow-hub 0.1.0`
+    pub software_version: String,
+    /// Types of frames that the peer is able to receive
+    pub frame_types: Vec<String>,
+    /// Encodings supported by the peer, in order of preference.
+    ///
+    /// In the response of the accepting side it contains only the negotiated encoding.
+    #[serde(default = "default_encodings")]
+    pub encodings: Vec<Encoding>,
+}
+
+fn default_encodings() -> Vec<Encoding> {
+    vec![Encoding::Json]
+}
+
+impl Hello {
+    pub fn new(
+        protocol_version: Version,
+        min_protocol_version: Version,
+        software_version: impl Into<String>,
+        frame_types: &[&str],
+    ) -> Self {
+        Self {
+            protocol_version,
+            min_protocol_version,
+            software_version: software_version.into(),
+            frame_types: frame_types.iter().map(ToString::to_string).collect(),
+            encodings: Encoding::ALL.to_vec(),
+        }
+    }
+
+    /// Moves the encoding to the front of the supported encodings list.
+    pub fn prefer(mut self, encoding: Encoding) -> Self {
+        self.encodings.retain(|v| *v != encoding);
+        self.encodings.insert(0, encoding);
+        self
+    }
+
+    /// Returns the highest protocol version supported by both peers.
+    pub fn negotiate(&self, remote: &Hello) -> Result<Version, Error> {
+        let version = self.protocol_version.min(remote.protocol_version);
+        if version < self.min_protocol_version || version < remote.min_protocol_version {
+            return Err(Error::UnsupportedVersion {
+                version: remote.protocol_version,
+                min: self.min_protocol_version,
+                max: self.protocol_version,
+            });
+        }
+        Ok(version)
+    }
+
+    /// Returns the encoding most preferred by the remote peer that is also supported by us, falls back to JSON.
+    pub fn negotiate_encoding(&self, remote: &Hello) -> Encoding {
+        remote
+            .encodings
+            .iter()
+            .find(|encoding| self.encodings.contains(encoding))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the encoding chosen by the accepting peer.
+    pub fn encoding(&self) -> Encoding {
+        self.encodings.first().copied().unwrap_or_default()
+    }
+
+    /// Returns true if the peer declared that it is able to receive frames of the given type.
+    pub fn supports(&self, frame_type: &str) -> bool {
+        self.frame_types.iter().any(|v| v == frame_type)
+    }
+}
+
+/// Builds the `Hello` of the calling crate, using its name and version as the software version.
+///
+/// Takes the protocol version constants and the types of frames that the calling side is able to receive.
+#[macro_export]
+macro_rules! hello {
+    ($protocol_version:expr, $min_protocol_version:expr, $frame_types:expr $(,)?) => {
+        $crate::protocol::Hello::new(
+            $protocol_version,
+            $min_protocol_version,
+            concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")),
+            $frame_types,
+        )
+    };
+}
+
+pub use crate::hello;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "error", content = "description", rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Error {
+    /// Protocol versions of the peers do not overlap
+    #[error("unsupported protocol version {version}, supported versions are {min}..={max}")]
+    UnsupportedVersion {
+        version: Version,
+        min: Version,
+        max: Version,
+    },
+    /// Received frame has a type that is not known by the receiver
+    #[error("unknown frame type: {0}")]
+    UnknownFrame(String),
+    /// Received frame could not be parsed
+    #[error("malformed frame: {0}")]
+    MalformedFrame(String),
+}
+
+/// Encoding of the frames sent after the handshake
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, strum::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Encoding {
+    /// JSON, frames are sent as text messages
+    #[default]
+    Json,
+    /// MessagePack, frames are sent as binary messages
+    #[serde(rename = "msgpack")]
+    #[strum(serialize = "msgpack")]
+    MessagePack,
+}
+
+impl Encoding {
+    pub const ALL: &'static [Encoding] = &[Encoding::Json, Encoding::MessagePack];
+
+    pub fn encode<T: Serialize>(self, frame: &T) -> Result<Payload, EncodeError> {
+        Ok(match self {
+            Self::Json => Payload::Text(serde_json::to_string(frame)?),
+            Self::MessagePack => Payload::Binary(rmp_serde::to_vec_named(frame)?),
+        })
+    }
+}
+
+/// Encoded frame, ready to be sent as a websocket message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("msgpack: {0}")]
+    MessagePack(#[from] rmp_serde::encode::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SendError {
+    /// Peer did not declare in its `Hello` that it is able to receive frames of this type
+    #[error("peer does not support `{0}` frames")]
+    UnsupportedFrame(String),
+    #[error("encode: {0}")]
+    Encode(#[from] EncodeError),
+}
+
+/// Parses a JSON frame, distinguishing frames of unknown type from malformed ones.
+pub fn decode<T: DeserializeOwned>(text: &str, frame_types: &[&str]) -> Result<T, Error> {
+    serde_json::from_str(text).map_err(|err| {
+        let frame_type = serde_json::from_str::<serde_json::Value>(text)
+            .ok()
+            .and_then(|value| value.get("type")?.as_str().map(ToString::to_string));
+        decode_error(frame_type, frame_types, err)
+    })
+}
+
+/// Parses a MessagePack frame, distinguishing frames of unknown type from malformed ones.
+pub fn decode_binary<T: DeserializeOwned>(bytes: &[u8], frame_types: &[&str]) -> Result<T, Error> {
+    rmp_serde::from_slice(bytes).map_err(|err| {
+        #[derive(Deserialize)]
+        struct Tag {
+            r#type: String,
+        }
+
+        let frame_type = rmp_serde::from_slice::<Tag>(bytes)
+            .ok()
+            .map(|tag| tag.r#type);
+        decode_error(frame_type, frame_types, err)
+    })
+}
+
+fn decode_error(
+    frame_type: Option<String>,
+    frame_types: &[&str],
+    err: impl std::fmt::Display,
+) -> Error {
+    match frame_type {
+        Some(frame_type) if !frame_types.contains(&frame_type.as_str()) => {
+            Error::UnknownFrame(frame_type)
+        }
+        _ => Error::MalformedFrame(err.to_string()),
+    }
+}
+
+/// Fixtures shared by the frame tests of the hive and lighthouse protocols
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+
+    pub fn hello(protocol_version: Version, min_protocol_version: Version) -> Hello {
+        Hello::new(
+            protocol_version,
+            min_protocol_version,
+            "test 0.0.0",
+            &["a", "b"],
+        )
+    }
+
+    /// Asserts that the frame decodes back to itself in every encoding.
+    pub fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(
+        frame: T,
+        frame_types: &[&str],
+    ) {
+        for encoding in Encoding::ALL {
+            let decoded: T = match encoding.encode(&frame).unwrap() {
+                Payload::Text(text) => decode(&text, frame_types),
+                Payload::Binary(bytes) => decode_binary(&bytes, frame_types),
+            }
+            .unwrap_or_else(|err| panic!("{} round trip of {:?} failed: {}", encoding, frame, err));
+            assert_eq!(decoded, frame, "{} round trip", encoding);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::hello;
+    use super::*;
+
+    #[test]
+    fn negotiate_picks_lower_version() {
+        assert_eq!(hello(3, 1).negotiate(&hello(2, 1)), Ok(2));
+        assert_eq!(hello(2, 1).negotiate(&hello(3, 1)), Ok(2));
+    }
+
+    #[test]
+    fn negotiate_rejects_disjoint_versions() {
+        assert_eq!(
+            hello(5, 4).negotiate(&hello(3, 1)),
+            Err(Error::UnsupportedVersion {
+                version: 3,
+                min: 4,
+                max: 5
+            })
+        );
+        assert!(hello(3, 1).negotiate(&hello(5, 4)).is_err());
+    }
+
+    #[test]
+    fn decode_unknown_frame() {
+        #[derive(Debug, Deserialize)]
+        #[serde(tag = "type", rename_all = "kebab-case")]
+        enum Frame {
+            A { id: u16 },
+        }
+
+        assert!(matches!(
+            decode::<Frame>(r#"{"type": "a", "id": 1}"#, &["a"]),
+            Ok(Frame::A { id: 1 })
+        ));
+        assert_eq!(
+            decode::<Frame>(r#"{"type": "c"}"#, &["a"]).unwrap_err(),
+            Error::UnknownFrame(String::from("c"))
+        );
+        assert!(matches!(
+            decode::<Frame>(r#"{"type": "a"}"#, &["a"]).unwrap_err(),
+            Error::MalformedFrame(_)
+        ));
+    }
+
+    #[test]
+    fn negotiate_encoding() {
+        let local = hello(2, 1);
+        let remote = hello(2, 1).prefer(Encoding::MessagePack);
+        assert_eq!(remote.encodings, [Encoding::MessagePack, Encoding::Json]);
+        assert_eq!(local.negotiate_encoding(&remote), Encoding::MessagePack);
+        assert_eq!(remote.negotiate_encoding(&local), Encoding::Json);
+
+        let legacy: Hello = serde_json::from_str(
+            r#"{"protocol-version": 2, "min-protocol-version": 1, "software-version": "test 0.0.0", "frame-types": []}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.encoding(), Encoding::Json);
+        assert_eq!(local.negotiate_encoding(&legacy), Encoding::Json);
+    }
+
+    #[test]
+    fn decode_binary_unknown_frame() {
+        #[derive(Debug, Serialize, Deserialize)]
+        #[serde(tag = "type", rename_all = "kebab-case")]
+        enum Frame {
+            A { id: u16 },
+        }
+
+        #[derive(Debug, Serialize)]
+        #[serde(tag = "type", rename_all = "kebab-case")]
+        enum NewerFrame {
+            C,
+        }
+
+        fn encode(frame: &impl Serialize) -> Vec<u8> {
+            match Encoding::MessagePack.encode(frame).unwrap() {
+                Payload::Binary(bytes) => bytes,
+                Payload::Text(_) => unreachable!(),
+            }
+        }
+
+        assert!(matches!(
+            decode_binary::<Frame>(&encode(&Frame::A { id: 1 }), &["a"]),
+            Ok(Frame::A { id: 1 })
+        ));
+        assert_eq!(
+            decode_binary::<Frame>(&encode(&NewerFrame::C), &["a"]).unwrap_err(),
+            Error::UnknownFrame(String::from("c"))
+        );
+        assert!(matches!(
+            decode_binary::<Frame>(&[0xff, 0x00], &["a"]).unwrap_err(),
+            Error::MalformedFrame(_)
+        ));
+    }
+}
@@ -0,0 +1,851 @@
+use crate::accessory;
+use crate::accessory::capabilities::Capabilities;
+use crate::accessory::characteristics::Characteristic;
+use crate::accessory::characteristics::CharacteristicName;
+use crate::accessory::services::ServiceName;
+use crate::accessory::CharacteristicRead;
+use crate::accessory::CharacteristicWrite;
+use acu::MasterExt;
+use async_trait::async_trait;
+use futures::future;
+use futures::Future;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+use tokio::sync::oneshot;
+
+/// Providers that requests to the accessories can be routed to
+#[async_trait]
+pub trait Providers: Send + Sync {
+    type Provider: Clone + Send + Sync;
+
+    /// Returns all of the providers
+    async fn providers(&self) -> Vec<Self::Provider>;
+
+    /// Returns true if the accessory is connected to the provider
+    async fn is_connected(provider: &Self::Provider, accessory_id: accessory::ID) -> bool;
+}
+
+/// Routing table of accessories and the providers they are connected to.
+///
+/// Lets requests be sent directly to the right provider instead of asking every provider whether the accessory is connected.
+/// Kept up to date with `connected` and `disconnected` when the accessories connect and disconnect.
+#[derive(Debug)]
+pub struct Routes<P> {
+    table: Arc<RwLock<Table<P>>>,
+}
+
+#[derive(Debug)]
+struct Table<P> {
+    routes: HashMap<accessory::ID, P>,
+    /// Incremented on every disconnection, lookups which were in flight meanwhile don't store their results
+    disconnections: u64,
+}
+
+impl<P> Default for Table<P> {
+    fn default() -> Self {
+        Self {
+            routes: Default::default(),
+            disconnections: 0,
+        }
+    }
+}
+
+impl<P> Default for Routes<P> {
+    fn default() -> Self {
+        Self {
+            table: Default::default(),
+        }
+    }
+}
+
+impl<P> Clone for Routes<P> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<P: Clone + Send + Sync> Routes<P> {
+    /// Returns the provider that the accessory is connected to, if it is in the routing table.
+    pub fn get(&self, accessory_id: accessory::ID) -> Option<P> {
+        self.table
+            .read()
+            .unwrap()
+            .routes
+            .get(&accessory_id)
+            .cloned()
+    }
+
+    /// Returns the provider that the accessory is connected to.
+    ///
+    /// Falls back to asking every provider if the accessory is missing in the routing table,
+    /// e.g when the request comes before the connection of the accessory is handled.
+    pub async fn route(
+        &self,
+        accessory_id: accessory::ID,
+        providers: &impl Providers<Provider = P>,
+    ) -> Option<P> {
+        match self.get(accessory_id) {
+            Some(provider) => Some(provider),
+            None => self.resolve(accessory_id, providers).await,
+        }
+    }
+
+    /// Asks every provider whether the accessory is connected to it, and stores the result in the routing table.
+    ///
+    /// The result isn't stored if an accessory has disconnected during the lookup, as it could be stale by then.
+    pub async fn resolve<R: Providers<Provider = P>>(
+        &self,
+        accessory_id: accessory::ID,
+        providers: &R,
+    ) -> Option<P> {
+        let disconnections = self.table.read().unwrap().disconnections;
+        let candidates = providers.providers().await;
+        let futures = candidates
+            .iter()
+            .map(|provider| R::is_connected(provider, accessory_id));
+        let connected = future::join_all(futures).await;
+        let provider = candidates
+            .into_iter()
+            .zip(connected)
+            .find_map(|(provider, connected)| connected.then_some(provider))?;
+        let mut table = self.table.write().unwrap();
+        if table.disconnections == disconnections {
+            table.routes.insert(accessory_id, provider.clone());
+        }
+        Some(provider)
+    }
+
+    /// Updates the route of an accessory that has just connected, returns `None` if no provider has it.
+    pub async fn connected(
+        &self,
+        accessory_id: accessory::ID,
+        providers: &impl Providers<Provider = P>,
+    ) -> Option<P> {
+        self.disconnected(accessory_id);
+        self.resolve(accessory_id, providers).await
+    }
+
+    /// Removes the route of an accessory that has disconnected.
+    pub fn disconnected(&self, accessory_id: accessory::ID) {
+        let mut table = self.table.write().unwrap();
+        table.routes.remove(&accessory_id);
+        table.disconnections += 1;
+    }
+}
+
+/// Results of a batch request, in the order of its items
+pub type Results<R> = Vec<Result<R, accessory::Error>>;
+
+/// Items of a batch request sent to a single target, e.g a provider or a hub
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch<K, T> {
+    pub target: K,
+    /// Positions of the items in the whole request
+    pub indexes: Vec<usize>,
+    pub items: Vec<T>,
+}
+
+/// Splits the items of a batch request into batches, one for each target.
+///
+/// Each item comes with its target, or the error to respond with if it can't be sent, e.g when the accessory is not connected.
+/// Targets are the same if their `group` keys are equal.
+///
+/// Returns the batches and the results of the request, holding the errors of the items that won't be sent.
+/// Results of the sent items are `NotConnected` until they are filled in by `merge`.
+pub fn split<K, G: PartialEq, T, R>(
+    items: impl IntoIterator<Item = (Result<K, accessory::Error>, T)>,
+    group: impl Fn(&K) -> G,
+) -> (Vec<Batch<K, T>>, Results<R>) {
+    let mut batches: Vec<Batch<K, T>> = Vec::new();
+    let mut results = Vec::new();
+    for (index, (target, item)) in items.into_iter().enumerate() {
+        match target {
+            Ok(target) => {
+                let key = group(&target);
+                match batches.iter_mut().find(|batch| group(&batch.target) == key) {
+                    Some(batch) => {
+                        batch.indexes.push(index);
+                        batch.items.push(item);
+                    }
+                    None => batches.push(Batch {
+                        target,
+                        indexes: vec![index],
+                        items: vec![item],
+                    }),
+                }
+                results.push(Err(accessory::Error::NotConnected));
+            }
+            Err(err) => results.push(Err(err)),
+        }
+    }
+    (batches, results)
+}
+
+/// Puts the results of a batch in the results of the whole request, items missing in `batch_results` are left untouched.
+pub fn merge<R>(
+    results: &mut [Result<R, accessory::Error>],
+    indexes: Vec<usize>,
+    batch_results: Results<R>,
+) {
+    for (index, result) in indexes.into_iter().zip(batch_results) {
+        results[index] = result;
+    }
+}
+
+/// Splits the items of a batch request by their targets, sends the batches concurrently using `send`,
+/// and returns the results in the order of the items.
+///
+/// See `split` for the meaning of the arguments.
+pub async fn scatter<K, G, T, R, F, Fut>(
+    items: impl IntoIterator<Item = (Result<K, accessory::Error>, T)>,
+    group: impl Fn(&K) -> G,
+    mut send: F,
+) -> Results<R>
+where
+    G: PartialEq,
+    F: FnMut(K, Vec<T>) -> Fut,
+    Fut: Future<Output = Results<R>>,
+{
+    let (batches, mut results) = split(items, group);
+    let futures = batches.into_iter().map(|batch| {
+        let batch_results = send(batch.target, batch.items);
+        async move { (batch.indexes, batch_results.await) }
+    });
+    for (indexes, batch_results) in future::join_all(futures).await {
+        merge(&mut results, indexes, batch_results);
+    }
+    results
+}
+
+/// Waits for the responses to requests sent one by one, e.g to peers which don't support batch frames.
+///
+/// Requests whose response never arrives, e.g because the peer has disconnected, result in `NotConnected`.
+pub async fn join<R, E>(
+    responses: impl IntoIterator<Item = impl Future<Output = Result<Result<R, accessory::Error>, E>>>,
+) -> Results<R> {
+    future::join_all(responses)
+        .await
+        .into_iter()
+        .map(|response| response.unwrap_or(Err(accessory::Error::NotConnected)))
+        .collect()
+}
+
+/// Messages handled by every provider, which the master routes to the provider that the accessory is connected to
+pub trait ProviderMessage: acu::Message + Sync + 'static {
+    fn read_characteristic(
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+        respond_to: oneshot::Sender<Result<Characteristic, accessory::Error>>,
+    ) -> Self;
+    fn write_characteristic(
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic: Characteristic,
+        respond_to: oneshot::Sender<Result<(), accessory::Error>>,
+    ) -> Self;
+    fn read_characteristics(
+        reads: Vec<CharacteristicRead>,
+        respond_to: oneshot::Sender<Results<Characteristic>>,
+    ) -> Self;
+    fn write_characteristics(
+        writes: Vec<CharacteristicWrite>,
+        respond_to: oneshot::Sender<Results<()>>,
+    ) -> Self;
+    fn identify(
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Result<(), accessory::Error>>,
+    ) -> Self;
+    fn is_connected(accessory_id: accessory::ID, respond_to: oneshot::Sender<bool>) -> Self;
+    fn get_accessory_capabilities(
+        accessory_id: accessory::ID,
+        respond_to: oneshot::Sender<Option<Capabilities>>,
+    ) -> Self;
+}
+
+#[async_trait]
+pub trait Provider {
+    async fn read_characteristic(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+    ) -> Result<Characteristic, accessory::Error>;
+    async fn write_characteristic(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic: Characteristic,
+    ) -> Result<(), accessory::Error>;
+    /// Reads multiple characteristics at once, returns a result for each read in the same order
+    async fn read_characteristics(&self, reads: Vec<CharacteristicRead>)
+        -> Results<Characteristic>;
+    /// Writes multiple characteristics at once, returns a result for each write in the same order
+    async fn write_characteristics(&self, writes: Vec<CharacteristicWrite>) -> Results<()>;
+    /// Asks the accessory to identify itself, e.g by blinking an LED
+    async fn identify(&self, accessory_id: accessory::ID) -> Result<(), accessory::Error>;
+    async fn is_connected(&self, accessory_id: accessory::ID) -> bool;
+    /// Returns the capabilities announced by a connected accessory
+    async fn get_accessory_capabilities(&self, accessory_id: accessory::ID)
+        -> Option<Capabilities>;
+}
+
+#[async_trait]
+impl<M: ProviderMessage, N: acu::Name> Provider for acu::Handle<M, N> {
+    async fn read_characteristic(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+    ) -> Result<Characteristic, accessory::Error> {
+        self.sender
+            .call_with(|respond_to| {
+                M::read_characteristic(accessory_id, service_name, characteristic_name, respond_to)
+            })
+            .await
+    }
+
+    async fn write_characteristic(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic: Characteristic,
+    ) -> Result<(), accessory::Error> {
+        self.sender
+            .call_with(|respond_to| {
+                M::write_characteristic(accessory_id, service_name, characteristic, respond_to)
+            })
+            .await
+    }
+
+    async fn read_characteristics(
+        &self,
+        reads: Vec<CharacteristicRead>,
+    ) -> Results<Characteristic> {
+        self.sender
+            .call_with(|respond_to| M::read_characteristics(reads, respond_to))
+            .await
+    }
+
+    async fn write_characteristics(&self, writes: Vec<CharacteristicWrite>) -> Results<()> {
+        self.sender
+            .call_with(|respond_to| M::write_characteristics(writes, respond_to))
+            .await
+    }
+
+    async fn identify(&self, accessory_id: accessory::ID) -> Result<(), accessory::Error> {
+        self.sender
+            .call_with(|respond_to| M::identify(accessory_id, respond_to))
+            .await
+    }
+
+    async fn is_connected(&self, accessory_id: accessory::ID) -> bool {
+        self.sender
+            .call_with(|respond_to| M::is_connected(accessory_id, respond_to))
+            .await
+    }
+
+    async fn get_accessory_capabilities(
+        &self,
+        accessory_id: accessory::ID,
+    ) -> Option<Capabilities> {
+        self.sender
+            .call_with(|respond_to| M::get_accessory_capabilities(accessory_id, respond_to))
+            .await
+    }
+}
+
+/// Connection of an accessory changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connection {
+    Connected(accessory::ID),
+    Disconnected(accessory::ID),
+}
+
+/// Controller messages which tell about accessories connecting and disconnecting
+pub trait ConnectionEvent: acu::Message + 'static {
+    /// Returns the change of the connection, if the message is about one
+    fn connection(&self) -> Option<Connection>;
+}
+
+/// Handle to all of the providers.
+///
+/// Requests are routed to the provider that the accessory is connected to using a routing table,
+/// which is kept up to date by the handle returned from `router()`, it has to be pushed to the master controller.
+#[derive(Debug)]
+pub struct Master<M: ProviderMessage, N: acu::MasterName + 'static> {
+    master: acu::MasterHandle<M, N>,
+    routes: Routes<acu::Handle<M, N>>,
+}
+
+impl<M: ProviderMessage, N: acu::MasterName + 'static> Default for Master<M, N> {
+    fn default() -> Self {
+        Self {
+            master: Default::default(),
+            routes: Default::default(),
+        }
+    }
+}
+
+impl<M: ProviderMessage, N: acu::MasterName + 'static> Clone for Master<M, N> {
+    fn clone(&self) -> Self {
+        Self {
+            master: self.master.clone(),
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+impl<M: ProviderMessage, N: acu::MasterName + 'static> Master<M, N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the provider that the accessory is connected to.
+    pub async fn route(&self, accessory_id: accessory::ID) -> Option<acu::Handle<M, N>> {
+        self.routes.route(accessory_id, self).await
+    }
+
+    /// Returns a controller handle which updates the routing table on `Connected`/`Disconnected` events.
+    pub fn router<C: ConnectionEvent, CN: acu::Name + 'static>(
+        &self,
+        name: CN,
+    ) -> acu::Handle<C, CN> {
+        let (sender, receiver) = acu::channel(name);
+        let mut actor = Router {
+            receiver,
+            master: self.clone(),
+        };
+        tokio::spawn(async move { actor.run().await });
+        acu::Handle { sender }
+    }
+}
+
+#[async_trait]
+impl<M: ProviderMessage, N: acu::MasterName + 'static> Providers for Master<M, N> {
+    type Provider = acu::Handle<M, N>;
+
+    async fn providers(&self) -> Vec<Self::Provider> {
+        self.slaves().await
+    }
+
+    async fn is_connected(provider: &Self::Provider, accessory_id: accessory::ID) -> bool {
+        provider.is_connected(accessory_id).await
+    }
+}
+
+struct Router<M: ProviderMessage, N: acu::MasterName + 'static, C: ConnectionEvent, CN: acu::Name> {
+    receiver: acu::Receiver<C, CN>,
+    master: Master<M, N>,
+}
+
+impl<M, N, C, CN> Router<M, N, C, CN>
+where
+    M: ProviderMessage,
+    N: acu::MasterName + 'static,
+    C: ConnectionEvent,
+    CN: acu::Name,
+{
+    async fn run(&mut self) {
+        let routes = &self.master.routes;
+        while let Some(message) = self.receiver.recv().await {
+            match message.connection() {
+                Some(Connection::Connected(accessory_id)) => {
+                    let provider = routes.connected(accessory_id, &self.master).await;
+                    if provider.is_none() {
+                        tracing::warn!(%accessory_id, "connected accessory not found in any provider");
+                    }
+                }
+                Some(Connection::Disconnected(accessory_id)) => {
+                    routes.disconnected(accessory_id);
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<M: ProviderMessage, N: acu::MasterName + 'static> MasterExt<M, N> for Master<M, N> {
+    async fn slaves(&self) -> Vec<acu::Handle<M, N>> {
+        self.master.slaves().await
+    }
+
+    async fn find(&self, name: N) -> Option<acu::Handle<M, N>> {
+        self.master.find(name).await
+    }
+
+    async fn push(&self, handle: acu::Handle<M, N>) -> usize {
+        self.master.push(handle).await
+    }
+}
+
+#[async_trait]
+impl<M: ProviderMessage, N: acu::MasterName + 'static> Provider for Master<M, N> {
+    async fn read_characteristic(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic_name: CharacteristicName,
+    ) -> Result<Characteristic, accessory::Error> {
+        let slave = self
+            .route(accessory_id)
+            .await
+            .ok_or(accessory::Error::NotConnected)?;
+        slave
+            .read_characteristic(accessory_id, service_name, characteristic_name)
+            .await
+    }
+
+    async fn write_characteristic(
+        &self,
+        accessory_id: accessory::ID,
+        service_name: ServiceName,
+        characteristic: Characteristic,
+    ) -> Result<(), accessory::Error> {
+        let slave = self
+            .route(accessory_id)
+            .await
+            .ok_or(accessory::Error::NotConnected)?;
+        slave
+            .write_characteristic(accessory_id, service_name, characteristic)
+            .await
+    }
+
+    async fn read_characteristics(
+        &self,
+        reads: Vec<CharacteristicRead>,
+    ) -> Results<Characteristic> {
+        let mut routed = Vec::with_capacity(reads.len());
+        for read in reads {
+            let slave = self
+                .route(read.accessory_id)
+                .await
+                .ok_or(accessory::Error::NotConnected);
+            routed.push((slave, read));
+        }
+        scatter(routed, acu::Handle::name, |slave, reads| async move {
+            slave.read_characteristics(reads).await
+        })
+        .await
+    }
+
+    async fn write_characteristics(&self, writes: Vec<CharacteristicWrite>) -> Results<()> {
+        let mut routed = Vec::with_capacity(writes.len());
+        for write in writes {
+            let slave = self
+                .route(write.accessory_id)
+                .await
+                .ok_or(accessory::Error::NotConnected);
+            routed.push((slave, write));
+        }
+        scatter(routed, acu::Handle::name, |slave, writes| async move {
+            slave.write_characteristics(writes).await
+        })
+        .await
+    }
+
+    async fn identify(&self, accessory_id: accessory::ID) -> Result<(), accessory::Error> {
+        let slave = self
+            .route(accessory_id)
+            .await
+            .ok_or(accessory::Error::NotConnected)?;
+        slave.identify(accessory_id).await
+    }
+
+    async fn is_connected(&self, accessory_id: accessory::ID) -> bool {
+        self.route(accessory_id).await.is_some()
+    }
+
+    async fn get_accessory_capabilities(
+        &self,
+        accessory_id: accessory::ID,
+    ) -> Option<Capabilities> {
+        let slave = self.route(accessory_id).await?;
+        slave.get_accessory_capabilities(accessory_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::FutureExt;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone)]
+    struct Provider {
+        name: &'static str,
+        accessories: Arc<Mutex<HashSet<accessory::ID>>>,
+    }
+
+    impl Provider {
+        fn new(name: &'static str) -> Self {
+            Self {
+                name,
+                accessories: Default::default(),
+            }
+        }
+
+        fn connect(&self, accessory_id: accessory::ID) {
+            self.accessories.lock().unwrap().insert(accessory_id);
+        }
+
+        fn disconnect(&self, accessory_id: accessory::ID) {
+            self.accessories.lock().unwrap().remove(&accessory_id);
+        }
+    }
+
+    #[derive(Default)]
+    struct Master {
+        providers: Vec<Provider>,
+        queries: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Providers for Master {
+        type Provider = Provider;
+
+        async fn providers(&self) -> Vec<Provider> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+            self.providers.clone()
+        }
+
+        async fn is_connected(provider: &Provider, accessory_id: accessory::ID) -> bool {
+            provider.accessories.lock().unwrap().contains(&accessory_id)
+        }
+    }
+
+    fn master() -> Master {
+        Master {
+            providers: vec![Provider::new("hive"), Provider::new("mijia")],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn connected_inserts_route() {
+        let master = master();
+        let routes = Routes::default();
+        let accessory_id = accessory::ID::new_v4();
+        master.providers[1].connect(accessory_id);
+
+        let provider = block_on(routes.connected(accessory_id, &master)).unwrap();
+        assert_eq!(provider.name, "mijia");
+        assert_eq!(routes.get(accessory_id).unwrap().name, "mijia");
+
+        // routed from the table, without asking the providers again
+        let queries = master.queries.load(Ordering::SeqCst);
+        let provider = block_on(routes.route(accessory_id, &master)).unwrap();
+        assert_eq!(provider.name, "mijia");
+        assert_eq!(master.queries.load(Ordering::SeqCst), queries);
+    }
+
+    #[test]
+    fn connected_replaces_stale_route() {
+        let master = master();
+        let routes = Routes::default();
+        let accessory_id = accessory::ID::new_v4();
+        master.providers[0].connect(accessory_id);
+        block_on(routes.connected(accessory_id, &master)).unwrap();
+
+        master.providers[0].disconnect(accessory_id);
+        master.providers[1].connect(accessory_id);
+        let provider = block_on(routes.connected(accessory_id, &master)).unwrap();
+        assert_eq!(provider.name, "mijia");
+    }
+
+    #[test]
+    fn disconnected_removes_route() {
+        let master = master();
+        let routes = Routes::default();
+        let accessory_id = accessory::ID::new_v4();
+        master.providers[0].connect(accessory_id);
+        block_on(routes.connected(accessory_id, &master)).unwrap();
+
+        master.providers[0].disconnect(accessory_id);
+        routes.disconnected(accessory_id);
+        assert!(routes.get(accessory_id).is_none());
+        assert!(block_on(routes.route(accessory_id, &master)).is_none());
+    }
+
+    #[test]
+    fn miss_falls_back_to_providers() {
+        let master = master();
+        let routes = Routes::default();
+        let accessory_id = accessory::ID::new_v4();
+
+        assert!(block_on(routes.connected(accessory_id, &master)).is_none());
+        assert!(block_on(routes.route(accessory_id, &master)).is_none());
+        assert!(routes.get(accessory_id).is_none());
+
+        // connected before the event reached the routing table
+        master.providers[0].connect(accessory_id);
+        let provider = block_on(routes.route(accessory_id, &master)).unwrap();
+        assert_eq!(provider.name, "hive");
+        assert_eq!(routes.get(accessory_id).unwrap().name, "hive");
+    }
+
+    /// Providers which are listed only after the gate opens, to disconnect the accessory during a lookup
+    struct Gated {
+        master: Master,
+        gate: Mutex<Option<futures::channel::oneshot::Receiver<()>>>,
+    }
+
+    #[async_trait]
+    impl Providers for Gated {
+        type Provider = Provider;
+
+        async fn providers(&self) -> Vec<Provider> {
+            let gate = self.gate.lock().unwrap().take();
+            if let Some(gate) = gate {
+                gate.await.unwrap();
+            }
+            self.master.providers().await
+        }
+
+        async fn is_connected(provider: &Provider, accessory_id: accessory::ID) -> bool {
+            Master::is_connected(provider, accessory_id).await
+        }
+    }
+
+    #[test]
+    fn disconnected_during_lookup() {
+        let (open, gate) = futures::channel::oneshot::channel();
+        let providers = Gated {
+            master: master(),
+            gate: Mutex::new(Some(gate)),
+        };
+        let routes = Routes::default();
+        let accessory_id = accessory::ID::new_v4();
+        providers.master.providers[0].connect(accessory_id);
+
+        let lookup = routes.route(accessory_id, &providers);
+        let disconnect = async {
+            routes.disconnected(accessory_id);
+            open.send(()).unwrap();
+        };
+        let (provider, ()) = block_on(future::join(lookup, disconnect));
+        assert_eq!(provider.unwrap().name, "hive");
+        // the route could be stale, so it's looked up again on the next request
+        assert!(routes.get(accessory_id).is_none());
+    }
+
+    #[test]
+    fn scatter_keeps_order() {
+        let items = vec![
+            (Ok("hive"), 0),
+            (Ok("mijia"), 1),
+            (Err(accessory::Error::NotConnected), 2),
+            (Ok("hive"), 3),
+            (Err(accessory::Error::ValueOutOfRange), 4),
+            (Ok("mijia"), 5),
+        ];
+        let batches = Mutex::new(Vec::new());
+        let results = block_on(scatter(
+            items,
+            |target| *target,
+            |target, items: Vec<u8>| {
+                batches.lock().unwrap().push((target, items.clone()));
+                async move {
+                    items
+                        .into_iter()
+                        .map(|item| match item {
+                            5 => Err(accessory::Error::CharacteristicReadOnly),
+                            item => Ok(item * 10),
+                        })
+                        .collect()
+                }
+            },
+        ));
+        assert_eq!(
+            batches.into_inner().unwrap(),
+            [("hive", vec![0, 3]), ("mijia", vec![1, 5])]
+        );
+        assert_eq!(
+            results,
+            [
+                Ok(0),
+                Ok(10),
+                Err(accessory::Error::NotConnected),
+                Ok(30),
+                Err(accessory::Error::ValueOutOfRange),
+                Err(accessory::Error::CharacteristicReadOnly),
+            ]
+        );
+    }
+
+    #[test]
+    fn scatter_missing_results() {
+        let items = vec![(Ok("hive"), 0), (Ok("hive"), 1), (Ok("mijia"), 2)];
+        let results = block_on(scatter(
+            items,
+            |target| *target,
+            |target, items: Vec<u8>| {
+                async move {
+                    match target {
+                        // e.g the hub has disconnected before responding
+                        "mijia" => Vec::new(),
+                        // responded only to the first item
+                        _ => vec![Ok(items[0])],
+                    }
+                }
+            },
+        ));
+        assert_eq!(
+            results,
+            [
+                Ok(0),
+                Err(accessory::Error::NotConnected),
+                Err(accessory::Error::NotConnected),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_without_targets() {
+        let items: Vec<(Result<&str, _>, u8)> = vec![
+            (Err(accessory::Error::ServiceNotSupported), 0),
+            (Err(accessory::Error::NotConnected), 1),
+        ];
+        let (batches, results) = split::<_, _, _, ()>(items, |target| *target);
+        assert!(batches.is_empty());
+        assert_eq!(
+            results,
+            [
+                Err(accessory::Error::ServiceNotSupported),
+                Err(accessory::Error::NotConnected),
+            ]
+        );
+    }
+
+    #[test]
+    fn join_single_requests() {
+        // responses to the single frames sent to peers which don't support batch frames
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let (dropped, disconnected) = futures::channel::oneshot::channel();
+        let responses = vec![
+            future::ready(Ok(Ok(1))).boxed(),
+            receiver.boxed(),
+            disconnected.boxed(),
+            future::ready(Ok(Err(accessory::Error::CharacteristicNotSupported))).boxed(),
+        ];
+        sender.send(Ok(2)).unwrap();
+        drop(dropped);
+        assert_eq!(
+            block_on(join(responses)),
+            [
+                Ok(1),
+                Ok(2),
+                Err(accessory::Error::NotConnected),
+                Err(accessory::Error::CharacteristicNotSupported),
+            ]
+        );
+    }
+}
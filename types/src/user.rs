@@ -1,3 +1,4 @@
+use crate::accessory::characteristics::TemperatureUnits;
 use serde::Deserialize;
 use serde::Serialize;
 use uuid::Uuid;
@@ -14,4 +15,48 @@ pub struct User {
     pub email: lettre::Address,
     /// True if the user is admin.
     pub admin: bool,
+    /// How values are displayed to the user
+    #[serde(default)]
+    pub preferences: Preferences,
+}
+
+/// Display preferences of the user, accessories and the hub always use canonical SI values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Preferences {
+    #[serde(default)]
+    pub measurement_system: MeasurementSystem,
+    /// Overrides the temperature units implied by the measurement system
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_units: Option<TemperatureUnits>,
+}
+
+impl Preferences {
+    pub fn temperature_units(&self) -> TemperatureUnits {
+        self.temperature_units
+            .unwrap_or(match self.measurement_system {
+                MeasurementSystem::Metric => TemperatureUnits::Celsius,
+                MeasurementSystem::Imperial => TemperatureUnits::Fahrenheit,
+            })
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum MeasurementSystem {
+    #[default]
+    Metric,
+    Imperial,
 }